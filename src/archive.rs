@@ -0,0 +1,135 @@
+use super::{Bytes, Error};
+use std::io::{Cursor, Read};
+
+/// Every ZIP archive starts with a local file header signature.
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+/// The most memory reserved up front for a file, whatever size the archive declares for it.
+/// Larger files still unpack, the buffer grows as they are read.
+const MAX_SIZE_HINT: u64 = 16 * 1024 * 1024;
+
+/// Check if the given bytes look like a ZIP archive.
+pub fn is_zip(bytes: &[u8]) -> bool {
+    bytes.starts_with(ZIP_MAGIC)
+}
+
+/// Unpack a ZIP archive returned by Gotenberg into a list of `(filename, bytes)` tuples.
+///
+/// Routes that produce several files (for example a split PDF) answer with a ZIP archive.
+/// Directory entries are skipped and the files are returned in archive order.
+///
+/// # Example
+///
+/// ```no_run
+/// # fn example(response: &[u8]) -> Result<(), gotenberg_pdf::Error> {
+/// use gotenberg_pdf::archive;
+///
+/// for (filename, bytes) in archive::unpack(response)? {
+///     println!("{}: {} bytes", filename, bytes.len());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn unpack(bytes: &[u8]) -> Result<Vec<(String, Bytes)>, Error> {
+    let parse_error = |e: &dyn std::fmt::Display| {
+        Error::ParseError(
+            "ZIP archive".to_string(),
+            format!("<{} bytes>", bytes.len()),
+            e.to_string(),
+        )
+    };
+
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).map_err(|e| parse_error(&e))?;
+
+    let mut files = Vec::with_capacity(archive.len());
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index).map_err(|e| parse_error(&e))?;
+        if file.is_dir() {
+            continue;
+        }
+
+        let mut content = Vec::with_capacity(file.size().min(MAX_SIZE_HINT) as usize);
+        file.read_to_end(&mut content)
+            .map_err(|e| parse_error(&e))?;
+        files.push((file.name().to_string(), Bytes::from(content)));
+    }
+
+    Ok(files)
+}
+
+/// Turn the response of a route that may produce several files into a list of files.
+///
+/// An empty response (webhook mode) yields no files, a ZIP archive is unpacked,
+/// and any other response is returned as a single file named `filename`.
+pub(crate) fn files_from_response(
+    bytes: Bytes,
+    filename: &str,
+) -> Result<Vec<(String, Bytes)>, Error> {
+    if bytes.is_empty() {
+        Ok(vec![])
+    } else if is_zip(&bytes) {
+        unpack(&bytes)
+    } else {
+        Ok(vec![(filename.to_string(), bytes)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    fn make_zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options =
+            SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        writer.add_directory("nested/", options).unwrap();
+        for (name, content) in files {
+            writer.start_file(*name, options).unwrap();
+            writer.write_all(content).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_unpack() {
+        let archive = make_zip(&[("file_0.pdf", b"%PDF-1"), ("file_1.pdf", b"%PDF-2")]);
+        assert!(is_zip(&archive));
+
+        let files = unpack(&archive).unwrap();
+        assert_eq!(
+            files,
+            vec![
+                ("file_0.pdf".to_string(), Bytes::from_static(b"%PDF-1")),
+                ("file_1.pdf".to_string(), Bytes::from_static(b"%PDF-2")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unpack_invalid() {
+        assert!(!is_zip(b"%PDF-1.7"));
+        assert!(matches!(
+            unpack(b"PK\x03\x04 not really"),
+            Err(Error::ParseError(..))
+        ));
+    }
+
+    #[test]
+    fn test_files_from_response() {
+        assert!(files_from_response(Bytes::new(), "file.pdf")
+            .unwrap()
+            .is_empty());
+
+        let single = files_from_response(Bytes::from_static(b"%PDF-1.7"), "file.pdf").unwrap();
+        assert_eq!(
+            single,
+            vec![("file.pdf".to_string(), Bytes::from_static(b"%PDF-1.7"))]
+        );
+
+        let archive = Bytes::from(make_zip(&[("a.pdf", b"a")]));
+        let files = files_from_response(archive, "file.pdf").unwrap();
+        assert_eq!(files, vec![("a.pdf".to_string(), Bytes::from_static(b"a"))]);
+    }
+}
@@ -1,21 +1,31 @@
 use super::*;
-use reqwest::blocking::multipart;
-use reqwest::blocking::{Client as ReqwestClient, Response};
+#[cfg(feature = "blocking")]
+use crate::transport::ReqwestBlockingTransport;
+use crate::transport::{BlockingTransport, BlockingTransportResponse};
+#[cfg(feature = "blocking")]
+use reqwest::blocking::Client as ReqwestClient;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::Arc;
 
 #[cfg(feature = "zeroize")]
 use zeroize::Zeroize;
 
-/// Gotenberg API blocking client. Available when the `blocking` feature is enabled.
+/// Gotenberg API blocking client. Available when the `blocking` or the `ureq` feature is enabled.
+///
+/// The client sends its requests with reqwest when the `blocking` feature is enabled, and with ureq otherwise.
 ///
 /// The client can be freely cloned and moved across threads.
 /// All clones use the same connection pool for connection re-use.
-#[cfg_attr(docsrs, doc(cfg(feature = "blocking")))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "blocking", feature = "ureq"))))]
 #[derive(Clone)]
 pub struct BlockingClient {
-    client: ReqwestClient,
     base_url: String,
     username: Option<String>,
     password: Option<String>,
+    retry_policy: Option<RetryPolicy>,
+    server_version: Option<GotenbergVersion>,
+    transport: Arc<dyn BlockingTransport>,
 }
 
 impl Drop for BlockingClient {
@@ -38,27 +48,29 @@ impl Debug for BlockingClient {
         f.debug_struct("BlockingClient")
             .field("base_url", &self.base_url)
             .field("username", &self.username)
+            .field("retry_policy", &self.retry_policy)
+            .field("server_version", &self.server_version)
+            .field("transport", &self.transport)
             .finish()
     }
 }
 
 impl BlockingClient {
     /// Create a new instance of the API client.
+    #[cfg(feature = "blocking")]
     pub fn new(base_url: &str) -> Self {
-        // Strip trailing slashes
-        let base_url = base_url.trim_end_matches('/');
-
         let client = ReqwestClient::builder()
             .pool_idle_timeout(Some(std::time::Duration::from_secs(25))) // 5 seconds less than the Gotenberg server's idle timeout
             .build()
             .unwrap();
 
-        BlockingClient {
-            client,
-            base_url: base_url.to_string(),
-            username: None,
-            password: None,
-        }
+        Self::new_with_client(base_url, client)
+    }
+
+    /// Create a new instance of the API client, sending its requests with ureq.
+    #[cfg(not(feature = "blocking"))]
+    pub fn new(base_url: &str) -> Self {
+        Self::new_with_transport(base_url, crate::transport::UreqTransport::new())
     }
 
     /// Create a new instance of the API client with a custom Reqwest client.
@@ -66,15 +78,24 @@ impl BlockingClient {
     /// Best practices include:
     ///   - [`reqwest::ClientBuilder::pool_idle_timeout`]. Set the pool timeout on the client to 5 seconds less than the Gotenberg server's idle timeout as set by `--api-timeout`.
     ///   - [`reqwest::ClientBuilder::http2_prior_knowledge`]. Use HTTP/2 without the need for ALPN negotiation. Useful if gotenberg is not behind a proxy. If you want to use HTTP/2 without HTTPS / TLS, this must be set.
+    #[cfg(feature = "blocking")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "blocking")))]
     pub fn new_with_client(base_url: &str, client: ReqwestClient) -> Self {
+        Self::new_with_transport(base_url, ReqwestBlockingTransport::new(client))
+    }
+
+    /// Create a new instance of the API client sending its requests through the given transport.
+    fn new_with_transport(base_url: &str, transport: impl BlockingTransport + 'static) -> Self {
         // Strip trailing slashes
         let base_url = base_url.trim_end_matches('/');
 
         BlockingClient {
-            client,
             base_url: base_url.to_string(),
             username: None,
             password: None,
+            retry_policy: None,
+            server_version: None,
+            transport: Arc::new(transport),
         }
     }
 
@@ -100,60 +121,253 @@ impl BlockingClient {
         client
     }
 
-    /// Generic POST method that takes a multipart form and sends it.
+    /// Retry requests that failed for a transient reason according to the given policy,
+    /// consuming the current client and returning a new instance of the client.
+    pub fn retry_policy(self, policy: RetryPolicy) -> Self {
+        let mut client = self;
+        client.retry_policy = Some(policy);
+
+        client
+    }
+
+    /// Set the version of the Gotenberg server, consuming the current client and returning a new instance of the client.
+    ///
+    /// Requests using a route or an option the server does not support then fail with
+    /// [`Error::UnsupportedFeature`] before anything is sent. Use [`BlockingClient::version_typed`] to query the version.
+    pub fn server_version(self, version: GotenbergVersion) -> Self {
+        let mut client = self;
+        client.server_version = Some(version);
+
+        client
+    }
+
+    /// Send the requests through the given transport instead of the default one, consuming the current client and returning a new instance of the client.
+    ///
+    /// Basic auth and retries still apply. [`BlockingClient::health_check`], [`BlockingClient::version`]
+    /// and [`BlockingClient::metrics`] go through the transport too. See the [`transport`] module for the available backends.
+    pub fn transport(self, transport: impl BlockingTransport + 'static) -> Self {
+        let mut client = self;
+        client.transport = Arc::new(transport);
+
+        client
+    }
+
+    /// Generic POST method that encodes a request into a multipart form and sends it.
+    /// The form is rebuilt for every attempt, as a sent form cannot be reused.
+    /// If webhook options are provided, their headers are added to the request.
+    /// In the case of webhook usage, the server will respond with 204 No Content and no bytes,
+    /// so we return empty bytes.
     fn post(
         &self,
-        endpoint: &str,
-        form: multipart::Form,
-        trace: Option<String>,
+        request: GotenbergRequest,
+        webhook: Option<&WebhookOptions>,
     ) -> Result<Bytes, Error> {
-        let url = format!("{}/{}", self.base_url, endpoint);
+        self.post_output(request, webhook)
+            .map(|output| output.bytes)
+    }
+
+    /// Same as [`BlockingClient::post`], but keeps the response headers alongside the bytes.
+    fn post_output(
+        &self,
+        request: GotenbergRequest,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        version::check_features(
+            self.server_version.as_ref(),
+            &request.endpoint,
+            &request.features,
+        )?;
+        let request = request.webhook(webhook)?;
+
+        self.with_retry(|| self.send(&request))
+    }
 
-        let mut req = self.client.post(&url).multipart(form);
-        if let Some(trace) = trace {
-            req = req.header("Gotenberg-Trace", trace);
+    /// Send the request and return the response to read the output from.
+    /// Only sending the request is retried, as the bytes already read from the response cannot be taken back.
+    fn post_response(&self, request: GotenbergRequest) -> Result<BlockingTransportResponse, Error> {
+        version::check_features(
+            self.server_version.as_ref(),
+            &request.endpoint,
+            &request.features,
+        )?;
+
+        self.with_retry(|| self.send_response(&request))
+    }
+
+    /// Call `send` until it succeeds or the retry policy gives up.
+    fn with_retry<T>(&self, mut send: impl FnMut() -> Result<T, Error>) -> Result<T, Error> {
+        let mut attempt = 1;
+        loop {
+            let result = send();
+
+            let delay = match (&result, &self.retry_policy) {
+                (Err(error), Some(policy)) => policy.delay_after(attempt, error),
+                _ => None,
+            };
+            match delay {
+                Some(delay) => std::thread::sleep(delay),
+                None => return result,
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Send a single request and read the response.
+    fn send(&self, request: &GotenbergRequest) -> Result<RenderOutput, Error> {
+        let mut response = self.send_response(request)?;
+
+        // If webhook is enabled, the server returns 204 No Content.
+        if response.status == 204 {
+            let mut output = RenderOutput::from_parts(&response.headers, Bytes::new());
+            output.trace = output.trace.or(request.trace().map(str::to_string));
+            return Ok(output);
         }
 
+        let mut bytes = Vec::new();
+        response.read_to_end(&mut bytes).map_err(Error::IoError)?;
+        Ok(RenderOutput::from_parts(&response.headers, bytes.into()))
+    }
+
+    /// Send a single request, turning unsuccessful responses into errors.
+    fn send_response(
+        &self,
+        request: &GotenbergRequest,
+    ) -> Result<BlockingTransportResponse, Error> {
+        let url = format!("{}/{}", self.base_url, request.endpoint);
+
+        let mut request = request.clone();
         // Add basic auth if username and password are provided
         if let (Some(username), Some(password)) = (&self.username, &self.password) {
-            req = req.basic_auth(username, Some(password));
+            request = request.header("Authorization", transport::basic_auth(username, password));
         }
 
-        let response: Response = req.send().map_err(Into::into)?;
+        let mut response = self.transport.send(&url, &request)?;
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().unwrap_or_default();
-            return Err(Error::RenderingError(format!(
-                "Failed to render PDF: {} - {}",
-                status, body
+        if !(200..300).contains(&response.status) {
+            let mut body = Vec::new();
+            let _ = response.read_to_end(&mut body);
+            let body = String::from_utf8_lossy(&body).to_string();
+            return Err(Error::RenderingError(ApiError::new(
+                response.status,
+                body,
+                &response.headers,
             )));
         }
 
-        response.bytes().map_err(Into::into)
+        Ok(response)
     }
 
     /// Convert a URL to a PDF using the Chromium engine.
-    pub fn pdf_from_url(&self, url: &str, options: WebOptions) -> Result<Bytes, Error> {
-        let trace = options.trace_id.clone();
-        let form = multipart::Form::new().text("url", url.to_string());
-        let form = options.fill_form_blocking(form);
-        self.post("forms/chromium/convert/url", form, trace)
+    pub fn pdf_from_url(
+        &self,
+        url: &str,
+        options: WebOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<Bytes, Error> {
+        self.pdf_from_url_with_response(url, options, webhook)
+            .map(|output| output.bytes)
+    }
+
+    /// Same as [`BlockingClient::pdf_from_url`], but returns a [`RenderOutput`] with the output filename, content type and trace.
+    pub fn pdf_from_url_with_response(
+        &self,
+        url: &str,
+        options: WebOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        let request = GotenbergRequest::pdf_from_url(url, options)?;
+        self.post_output(request, webhook)
+    }
+
+    /// Same as [`BlockingClient::pdf_from_url`], but copies the PDF into `writer` and returns the number of bytes written.
+    ///
+    /// With `split_mode` set, Gotenberg answers with a ZIP archive of the parts, which is written instead.
+    pub fn pdf_from_url_to_writer(
+        &self,
+        url: &str,
+        options: WebOptions,
+        writer: impl Write,
+    ) -> Result<u64, Error> {
+        let response = self.pdf_from_url_response(url, options)?;
+        sink::copy_response(response, writer, OutputKind::Pdf)
+    }
+
+    /// Same as [`BlockingClient::pdf_from_url`], but writes the PDF to the file at `path` and returns the number of bytes written.
+    ///
+    /// The PDF is written to a temporary file next to `path`, which replaces `path` once the whole PDF was written.
+    /// On error, the temporary file is deleted and `path` is left untouched.
+    pub fn pdf_from_url_to_path(
+        &self,
+        url: &str,
+        options: WebOptions,
+        path: impl AsRef<Path>,
+    ) -> Result<u64, Error> {
+        let response = self.pdf_from_url_response(url, options)?;
+        sink::copy_response_to_path(response, path.as_ref(), OutputKind::Pdf)
+    }
+
+    fn pdf_from_url_response(
+        &self,
+        url: &str,
+        options: WebOptions,
+    ) -> Result<BlockingTransportResponse, Error> {
+        self.post_response(GotenbergRequest::pdf_from_url(url, options)?)
     }
 
     /// Convert HTML to a PDF using the Chromium engine.
-    pub fn pdf_from_html(&self, html: &str, options: WebOptions) -> Result<Bytes, Error> {
-        let trace = options.trace_id.clone();
-
-        let form = multipart::Form::new();
-        let file_bytes = html.to_string().into_bytes();
-        let part = multipart::Part::bytes(file_bytes)
-            .file_name("index.html")
-            .mime_str("text/html")
-            .unwrap();
-        let form = form.part("index.html", part);
-        let form = options.fill_form_blocking(form);
-        self.post("forms/chromium/convert/html", form, trace)
+    ///
+    /// Pass an [`HtmlBundle`] to upload images, stylesheets or fonts alongside the HTML.
+    pub fn pdf_from_html(
+        &self,
+        html: impl Into<HtmlBundle>,
+        options: WebOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<Bytes, Error> {
+        self.pdf_from_html_with_response(html, options, webhook)
+            .map(|output| output.bytes)
+    }
+
+    /// Same as [`BlockingClient::pdf_from_html`], but returns a [`RenderOutput`] with the output filename, content type and trace.
+    pub fn pdf_from_html_with_response(
+        &self,
+        html: impl Into<HtmlBundle>,
+        options: WebOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        let request = GotenbergRequest::pdf_from_html(html, options)?;
+        self.post_output(request, webhook)
+    }
+
+    /// Same as [`BlockingClient::pdf_from_html`], but copies the PDF into `writer` and returns the number of bytes written.
+    pub fn pdf_from_html_to_writer(
+        &self,
+        html: impl Into<HtmlBundle>,
+        options: WebOptions,
+        writer: impl Write,
+    ) -> Result<u64, Error> {
+        let response = self.pdf_from_html_response(html.into(), options)?;
+        sink::copy_response(response, writer, OutputKind::Pdf)
+    }
+
+    /// Same as [`BlockingClient::pdf_from_html`], but writes the PDF to the file at `path` and returns the number of bytes written.
+    ///
+    /// See [`BlockingClient::pdf_from_url_to_path`] for how partial files are avoided.
+    pub fn pdf_from_html_to_path(
+        &self,
+        html: impl Into<HtmlBundle>,
+        options: WebOptions,
+        path: impl AsRef<Path>,
+    ) -> Result<u64, Error> {
+        let response = self.pdf_from_html_response(html.into(), options)?;
+        sink::copy_response_to_path(response, path.as_ref(), OutputKind::Pdf)
+    }
+
+    fn pdf_from_html_response(
+        &self,
+        html: HtmlBundle,
+        options: WebOptions,
+    ) -> Result<BlockingTransportResponse, Error> {
+        self.post_response(GotenbergRequest::pdf_from_html(html, options)?)
     }
 
     /// Convert Markdown to a PDF using the Chromium engine.
@@ -176,102 +390,211 @@ impl BlockingClient {
     /// The markdown files should be in a "filename" => "content" format. The filename key string must end with `.md`.
     pub fn pdf_from_markdown(
         &self,
-        html_template: &str,
+        html_template: impl Into<HtmlBundle>,
         markdown: HashMap<&str, &str>,
         options: WebOptions,
+        webhook: Option<&WebhookOptions>,
     ) -> Result<Bytes, Error> {
-        let trace = options.trace_id.clone();
+        self.pdf_from_markdown_with_response(html_template, markdown, options, webhook)
+            .map(|output| output.bytes)
+    }
 
-        let form = multipart::Form::new();
-        let file_bytes = html_template.to_string().into_bytes();
-        let part = multipart::Part::bytes(file_bytes)
-            .file_name("index.html")
-            .mime_str("text/html")
-            .unwrap();
-        let form = form.part("index.html", part);
-        let form = options.fill_form_blocking(form);
-
-        let form = {
-            let mut form = form;
-            for (filename, content) in markdown {
-                if !filename.ends_with(".md") {
-                    return Err(Error::FilenameError(
-                        "Markdown filename must end with '.md'".to_string(),
-                    ));
-                }
-                let file_bytes = content.to_string().into_bytes();
-                let part = multipart::Part::bytes(file_bytes)
-                    .file_name(filename.to_string())
-                    .mime_str("text/markdown")
-                    .unwrap();
-                form = form.part(filename.to_string(), part);
-            }
-            form
-        };
+    /// Same as [`BlockingClient::pdf_from_markdown`], but returns a [`RenderOutput`] with the output filename, content type and trace.
+    pub fn pdf_from_markdown_with_response(
+        &self,
+        html_template: impl Into<HtmlBundle>,
+        markdown: HashMap<&str, &str>,
+        options: WebOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        let request = GotenbergRequest::pdf_from_markdown(html_template, markdown, options)?;
+        self.post_output(request, webhook)
+    }
 
-        self.post("forms/chromium/convert/markdown", form, trace)
+    /// Same as [`BlockingClient::pdf_from_markdown`], but copies the PDF into `writer` and returns the number of bytes written.
+    pub fn pdf_from_markdown_to_writer(
+        &self,
+        html_template: impl Into<HtmlBundle>,
+        markdown: HashMap<&str, &str>,
+        options: WebOptions,
+        writer: impl Write,
+    ) -> Result<u64, Error> {
+        let response = self.post_response(GotenbergRequest::pdf_from_markdown(
+            html_template,
+            markdown,
+            options,
+        )?)?;
+        sink::copy_response(response, writer, OutputKind::Pdf)
+    }
+
+    /// Same as [`BlockingClient::pdf_from_markdown`], but writes the PDF to the file at `path` and returns the number of bytes written.
+    ///
+    /// See [`BlockingClient::pdf_from_url_to_path`] for how partial files are avoided.
+    pub fn pdf_from_markdown_to_path(
+        &self,
+        html_template: impl Into<HtmlBundle>,
+        markdown: HashMap<&str, &str>,
+        options: WebOptions,
+        path: impl AsRef<Path>,
+    ) -> Result<u64, Error> {
+        let response = self.post_response(GotenbergRequest::pdf_from_markdown(
+            html_template,
+            markdown,
+            options,
+        )?)?;
+        sink::copy_response_to_path(response, path.as_ref(), OutputKind::Pdf)
     }
 
     /// Take a screenshot of a webpage using the Chromium engine.
-    pub fn screenshot_url(&self, url: &str, options: ScreenshotOptions) -> Result<Bytes, Error> {
-        let trace = options.trace_id.clone();
-        let form = multipart::Form::new().text("url", url.to_string());
-        let form = options.fill_form_blocking(form);
-        self.post("forms/chromium/screenshot/url", form, trace)
+    pub fn screenshot_url(
+        &self,
+        url: &str,
+        options: ScreenshotOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<Bytes, Error> {
+        self.screenshot_url_with_response(url, options, webhook)
+            .map(|output| output.bytes)
+    }
+
+    /// Same as [`BlockingClient::screenshot_url`], but returns a [`RenderOutput`] with the output filename, content type and trace.
+    pub fn screenshot_url_with_response(
+        &self,
+        url: &str,
+        options: ScreenshotOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        let request = GotenbergRequest::screenshot_url(url, options);
+        self.post_output(request, webhook)
+    }
+
+    /// Same as [`BlockingClient::screenshot_url`], but copies the screenshot into `writer` and returns the number of bytes written.
+    pub fn screenshot_url_to_writer(
+        &self,
+        url: &str,
+        options: ScreenshotOptions,
+        writer: impl Write,
+    ) -> Result<u64, Error> {
+        let response = self.post_response(GotenbergRequest::screenshot_url(url, options))?;
+        sink::copy_response(response, writer, OutputKind::Image)
+    }
+
+    /// Same as [`BlockingClient::screenshot_url`], but writes the screenshot to the file at `path` and returns the number of bytes written.
+    ///
+    /// See [`BlockingClient::pdf_from_url_to_path`] for how partial files are avoided.
+    pub fn screenshot_url_to_path(
+        &self,
+        url: &str,
+        options: ScreenshotOptions,
+        path: impl AsRef<Path>,
+    ) -> Result<u64, Error> {
+        let response = self.post_response(GotenbergRequest::screenshot_url(url, options))?;
+        sink::copy_response_to_path(response, path.as_ref(), OutputKind::Image)
     }
 
     /// Take a screenshot of an HTML page using the Chromium engine.
-    pub fn screenshot_html(&self, html: &str, options: ScreenshotOptions) -> Result<Bytes, Error> {
-        let trace = options.trace_id.clone();
-
-        let form = multipart::Form::new();
-        let file_bytes = html.to_string().into_bytes();
-        let part = multipart::Part::bytes(file_bytes)
-            .file_name("index.html")
-            .mime_str("text/html")
-            .unwrap();
-        let form = form.part("index.html", part);
-        let form = options.fill_form_blocking(form);
-        self.post("forms/chromium/screenshot/html", form, trace)
+    ///
+    /// Pass an [`HtmlBundle`] to upload images, stylesheets or fonts alongside the HTML.
+    pub fn screenshot_html(
+        &self,
+        html: impl Into<HtmlBundle>,
+        options: ScreenshotOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<Bytes, Error> {
+        self.screenshot_html_with_response(html, options, webhook)
+            .map(|output| output.bytes)
+    }
+
+    /// Same as [`BlockingClient::screenshot_html`], but returns a [`RenderOutput`] with the output filename, content type and trace.
+    pub fn screenshot_html_with_response(
+        &self,
+        html: impl Into<HtmlBundle>,
+        options: ScreenshotOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        let request = GotenbergRequest::screenshot_html(html, options)?;
+        self.post_output(request, webhook)
+    }
+
+    /// Same as [`BlockingClient::screenshot_html`], but copies the screenshot into `writer` and returns the number of bytes written.
+    pub fn screenshot_html_to_writer(
+        &self,
+        html: impl Into<HtmlBundle>,
+        options: ScreenshotOptions,
+        writer: impl Write,
+    ) -> Result<u64, Error> {
+        let response = self.post_response(GotenbergRequest::screenshot_html(html, options)?)?;
+        sink::copy_response(response, writer, OutputKind::Image)
+    }
+
+    /// Same as [`BlockingClient::screenshot_html`], but writes the screenshot to the file at `path` and returns the number of bytes written.
+    ///
+    /// See [`BlockingClient::pdf_from_url_to_path`] for how partial files are avoided.
+    pub fn screenshot_html_to_path(
+        &self,
+        html: impl Into<HtmlBundle>,
+        options: ScreenshotOptions,
+        path: impl AsRef<Path>,
+    ) -> Result<u64, Error> {
+        let response = self.post_response(GotenbergRequest::screenshot_html(html, options)?)?;
+        sink::copy_response_to_path(response, path.as_ref(), OutputKind::Image)
     }
 
     /// Take a screenshot of a set of markdown files using the Chromium engine.
     pub fn screenshot_markdown(
         &self,
-        html_template: &str,
+        html_template: impl Into<HtmlBundle>,
         markdown: HashMap<&str, &str>,
         options: ScreenshotOptions,
+        webhook: Option<&WebhookOptions>,
     ) -> Result<Bytes, Error> {
-        let trace = options.trace_id.clone();
+        self.screenshot_markdown_with_response(html_template, markdown, options, webhook)
+            .map(|output| output.bytes)
+    }
 
-        let form = multipart::Form::new();
-        let file_bytes = html_template.to_string().into_bytes();
-        let part = multipart::Part::bytes(file_bytes)
-            .file_name("index.html")
-            .mime_str("text/html")
-            .unwrap();
-        let form = form.part("index.html", part);
-        let form = options.fill_form_blocking(form);
-
-        let form = {
-            let mut form = form;
-            for (filename, content) in markdown {
-                if !filename.ends_with(".md") {
-                    return Err(Error::FilenameError(
-                        "Markdown filename must end with '.md'".to_string(),
-                    ));
-                }
-                let file_bytes = content.to_string().into_bytes();
-                let part = multipart::Part::bytes(file_bytes)
-                    .file_name(filename.to_string())
-                    .mime_str("text/markdown")
-                    .unwrap();
-                form = form.part(filename.to_string(), part);
-            }
-            form
-        };
+    /// Same as [`BlockingClient::screenshot_markdown`], but returns a [`RenderOutput`] with the output filename, content type and trace.
+    pub fn screenshot_markdown_with_response(
+        &self,
+        html_template: impl Into<HtmlBundle>,
+        markdown: HashMap<&str, &str>,
+        options: ScreenshotOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        let request = GotenbergRequest::screenshot_markdown(html_template, markdown, options)?;
+        self.post_output(request, webhook)
+    }
+
+    /// Same as [`BlockingClient::screenshot_markdown`], but copies the screenshot into `writer` and returns the number of bytes written.
+    pub fn screenshot_markdown_to_writer(
+        &self,
+        html_template: impl Into<HtmlBundle>,
+        markdown: HashMap<&str, &str>,
+        options: ScreenshotOptions,
+        writer: impl Write,
+    ) -> Result<u64, Error> {
+        let response = self.post_response(GotenbergRequest::screenshot_markdown(
+            html_template,
+            markdown,
+            options,
+        )?)?;
+        sink::copy_response(response, writer, OutputKind::Image)
+    }
 
-        self.post("forms/chromium/screenshot/markdown", form, trace)
+    /// Same as [`BlockingClient::screenshot_markdown`], but writes the screenshot to the file at `path` and returns the number of bytes written.
+    ///
+    /// See [`BlockingClient::pdf_from_url_to_path`] for how partial files are avoided.
+    pub fn screenshot_markdown_to_path(
+        &self,
+        html_template: impl Into<HtmlBundle>,
+        markdown: HashMap<&str, &str>,
+        options: ScreenshotOptions,
+        path: impl AsRef<Path>,
+    ) -> Result<u64, Error> {
+        let response = self.post_response(GotenbergRequest::screenshot_markdown(
+            html_template,
+            markdown,
+            options,
+        )?)?;
+        sink::copy_response_to_path(response, path.as_ref(), OutputKind::Image)
     }
 
     /// Convert a document to a PDF using the LibreOffice engine.
@@ -289,88 +612,438 @@ impl BlockingClient {
     /// .wb2 .wk1 .wks .wmf .wpd .wpg .wps .xbm .xhtml .xls .xlsb .xlsm .xlsx .xlt
     /// .xltm .xltx .xlw .xml .xpm .zabw
     /// ```
+    ///
+    /// The document is either uploaded with its filename, eg. `("report.docx", bytes)`, or a [`DownloadFrom`] the server downloads.
     pub fn pdf_from_doc(
         &self,
-        filename: &str,
-        bytes: Vec<u8>,
+        file: impl Into<NamedFile>,
         options: DocumentOptions,
+        webhook: Option<&WebhookOptions>,
     ) -> Result<Bytes, Error> {
-        let trace = options.trace_id.clone();
-        let form = multipart::Form::new();
-        let part = multipart::Part::bytes(bytes).file_name(filename.to_string());
-        let form = form.part("files", part);
-        let form = options.fill_form_blocking(form);
-        self.post("forms/libreoffice/convert", form, trace)
+        self.pdf_from_doc_with_response(file, options, webhook)
+            .map(|output| output.bytes)
+    }
+
+    /// Same as [`BlockingClient::pdf_from_doc`], but returns a [`RenderOutput`] with the output filename, content type and trace.
+    pub fn pdf_from_doc_with_response(
+        &self,
+        file: impl Into<NamedFile>,
+        options: DocumentOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        let request = GotenbergRequest::pdf_from_doc(file, options)?;
+        self.post_output(request, webhook)
+    }
+
+    /// Same as [`BlockingClient::pdf_from_doc`], but copies the PDF into `writer` and returns the number of bytes written.
+    ///
+    /// See [`BlockingClient::pdf_from_url_to_writer`] for split conversions.
+    pub fn pdf_from_doc_to_writer(
+        &self,
+        file: impl Into<NamedFile>,
+        options: DocumentOptions,
+        writer: impl Write,
+    ) -> Result<u64, Error> {
+        let response = self.pdf_from_doc_response(file.into(), options)?;
+        sink::copy_response(response, writer, OutputKind::Pdf)
+    }
+
+    /// Same as [`BlockingClient::pdf_from_doc`], but writes the PDF to the file at `path` and returns the number of bytes written.
+    ///
+    /// See [`BlockingClient::pdf_from_url_to_path`] for how partial files are avoided.
+    pub fn pdf_from_doc_to_path(
+        &self,
+        file: impl Into<NamedFile>,
+        options: DocumentOptions,
+        path: impl AsRef<Path>,
+    ) -> Result<u64, Error> {
+        let response = self.pdf_from_doc_response(file.into(), options)?;
+        sink::copy_response_to_path(response, path.as_ref(), OutputKind::Pdf)
+    }
+
+    fn pdf_from_doc_response(
+        &self,
+        file: NamedFile,
+        options: DocumentOptions,
+    ) -> Result<BlockingTransportResponse, Error> {
+        self.post_response(GotenbergRequest::pdf_from_doc(file, options)?)
+    }
+
+    /// Convert several documents to PDF in a single request using the LibreOffice engine.
+    ///
+    /// With `options.merge` set, the documents are merged into a single PDF in the order they are given,
+    /// otherwise one PDF is returned per document. See [`BlockingClient::pdf_from_doc`] for the supported file formats.
+    ///
+    /// Documents are given as `(filename, bytes)` tuples, or as [`DownloadFrom`] for the server to download them.
+    /// Downloaded documents are named by the server, so they cannot be merged in order: with `options.merge` set,
+    /// a [`DownloadFrom`] fails the request with an [`Error::FilenameError`].
+    pub fn pdfs_from_docs(
+        &self,
+        files: Vec<impl Into<NamedFile>>,
+        options: DocumentOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<ConvertedDocuments, Error> {
+        let merge = options.merge.unwrap_or(false);
+        let files: Vec<NamedFile> = files.into_iter().map(Into::into).collect();
+        let filenames: Vec<String> = files
+            .iter()
+            .filter_map(|file| file.filename().map(str::to_string))
+            .collect();
+        let output = self.pdfs_from_docs_with_response(files, options, webhook)?;
+        ConvertedDocuments::from_response(output.bytes, merge, &filenames)
+    }
+
+    /// Same as [`BlockingClient::pdfs_from_docs`], but returns the raw [`RenderOutput`], see [`RenderOutput::files`] to unpack it.
+    pub fn pdfs_from_docs_with_response(
+        &self,
+        files: Vec<impl Into<NamedFile>>,
+        options: DocumentOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        let request = GotenbergRequest::pdfs_from_docs(files, options)?;
+        self.post_output(request, webhook)
     }
 
     /// Transforms a PDF file into the requested PDF/A format and/or PDF/UA.
     pub fn convert_pdf(
         &self,
-        pdf_bytes: Vec<u8>,
+        pdf: impl Into<InputFile>,
         pdfa: Option<PDFFormat>,
         pdfua: bool,
+        webhook: Option<&WebhookOptions>,
     ) -> Result<Bytes, Error> {
-        let form = multipart::Form::new();
-        let part = multipart::Part::bytes(pdf_bytes).file_name("file.pdf".to_string());
-        let mut form = form.part("file.pdf", part);
-        if let Some(pdfa) = pdfa {
-            form = form.text("pdfa", pdfa.to_string());
-        }
-        let form = form.text("pdfua", pdfua.to_string());
-        self.post("forms/pdfengines/convert", form, None)
+        self.convert_pdf_with_response(pdf, pdfa, pdfua, webhook)
+            .map(|output| output.bytes)
+    }
+
+    /// Same as [`BlockingClient::convert_pdf`], but returns a [`RenderOutput`] with the output filename, content type and trace.
+    pub fn convert_pdf_with_response(
+        &self,
+        pdf: impl Into<InputFile>,
+        pdfa: Option<PDFFormat>,
+        pdfua: bool,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        let request = GotenbergRequest::convert_pdf(pdf, pdfa, pdfua);
+        self.post_output(request, webhook)
+    }
+
+    /// Same as [`BlockingClient::convert_pdf`], but copies the PDF into `writer` and returns the number of bytes written.
+    pub fn convert_pdf_to_writer(
+        &self,
+        pdf: impl Into<InputFile>,
+        pdfa: Option<PDFFormat>,
+        pdfua: bool,
+        writer: impl Write,
+    ) -> Result<u64, Error> {
+        let response = self.post_response(GotenbergRequest::convert_pdf(pdf, pdfa, pdfua))?;
+        sink::copy_response(response, writer, OutputKind::Pdf)
+    }
+
+    /// Same as [`BlockingClient::convert_pdf`], but writes the PDF to the file at `path` and returns the number of bytes written.
+    ///
+    /// See [`BlockingClient::pdf_from_url_to_path`] for how partial files are avoided.
+    pub fn convert_pdf_to_path(
+        &self,
+        pdf: impl Into<InputFile>,
+        pdfa: Option<PDFFormat>,
+        pdfua: bool,
+        path: impl AsRef<Path>,
+    ) -> Result<u64, Error> {
+        let response = self.post_response(GotenbergRequest::convert_pdf(pdf, pdfa, pdfua))?;
+        sink::copy_response_to_path(response, path.as_ref(), OutputKind::Pdf)
+    }
+
+    /// Merge several PDF files into a single PDF.
+    ///
+    /// The files are merged in the order they are given. Every filename must end with `.pdf`.
+    /// Files downloaded by the server are named by the server, so their order could not be kept: a
+    /// [`DownloadFrom`] fails the request with an [`Error::FilenameError`].
+    pub fn merge_pdfs(
+        &self,
+        files: Vec<impl Into<NamedFile>>,
+        options: MergeOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<Bytes, Error> {
+        self.merge_pdfs_with_response(files, options, webhook)
+            .map(|output| output.bytes)
+    }
+
+    /// Same as [`BlockingClient::merge_pdfs`], but returns a [`RenderOutput`] with the output filename, content type and trace.
+    pub fn merge_pdfs_with_response(
+        &self,
+        files: Vec<impl Into<NamedFile>>,
+        options: MergeOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        let request = GotenbergRequest::merge_pdfs(files, options)?;
+        self.post_output(request, webhook)
+    }
+
+    /// Same as [`BlockingClient::merge_pdfs`], but copies the PDF into `writer` and returns the number of bytes written.
+    pub fn merge_pdfs_to_writer(
+        &self,
+        files: Vec<impl Into<NamedFile>>,
+        options: MergeOptions,
+        writer: impl Write,
+    ) -> Result<u64, Error> {
+        let response = self.post_response(GotenbergRequest::merge_pdfs(files, options)?)?;
+        sink::copy_response(response, writer, OutputKind::Pdf)
+    }
+
+    /// Same as [`BlockingClient::merge_pdfs`], but writes the PDF to the file at `path` and returns the number of bytes written.
+    ///
+    /// See [`BlockingClient::pdf_from_url_to_path`] for how partial files are avoided.
+    pub fn merge_pdfs_to_path(
+        &self,
+        files: Vec<impl Into<NamedFile>>,
+        options: MergeOptions,
+        path: impl AsRef<Path>,
+    ) -> Result<u64, Error> {
+        let response = self.post_response(GotenbergRequest::merge_pdfs(files, options)?)?;
+        sink::copy_response_to_path(response, path.as_ref(), OutputKind::Pdf)
+    }
+
+    /// Split a PDF file into several PDF files.
+    ///
+    /// The ZIP archive returned by the server is unpacked into a list of `(filename, bytes)` tuples.
+    /// If the server returns a single PDF (eg. when `split_unify` is set), it is returned as `file.pdf`.
+    pub fn split_pdf(
+        &self,
+        pdf: impl Into<InputFile>,
+        options: SplitOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<Vec<(String, Bytes)>, Error> {
+        let output = self.split_pdf_with_response(pdf, options, webhook)?;
+        archive::files_from_response(output.bytes, "file.pdf")
+    }
+
+    /// Same as [`BlockingClient::split_pdf`], but returns the raw [`RenderOutput`], see [`RenderOutput::files`] to unpack it.
+    pub fn split_pdf_with_response(
+        &self,
+        pdf: impl Into<InputFile>,
+        options: SplitOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        self.post_output(GotenbergRequest::split_pdf(pdf, options)?, webhook)
+    }
+
+    /// Encrypt a PDF file with a user password and an optional owner password.
+    ///
+    /// The user password is required to open the PDF, the owner password grants full access to it.
+    pub fn encrypt_pdf(
+        &self,
+        pdf: impl Into<InputFile>,
+        user_password: impl Into<Secret>,
+        owner_password: Option<Secret>,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<Bytes, Error> {
+        self.encrypt_pdf_with_response(pdf, user_password, owner_password, webhook)
+            .map(|output| output.bytes)
+    }
+
+    /// Same as [`BlockingClient::encrypt_pdf`], but returns a [`RenderOutput`] with the output filename, content type and trace.
+    pub fn encrypt_pdf_with_response(
+        &self,
+        pdf: impl Into<InputFile>,
+        user_password: impl Into<Secret>,
+        owner_password: Option<Secret>,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        let request = GotenbergRequest::encrypt_pdf(pdf, user_password, owner_password);
+        self.post_output(request, webhook)
+    }
+
+    /// Same as [`BlockingClient::encrypt_pdf`], but copies the PDF into `writer` and returns the number of bytes written.
+    pub fn encrypt_pdf_to_writer(
+        &self,
+        pdf: impl Into<InputFile>,
+        user_password: impl Into<Secret>,
+        owner_password: Option<Secret>,
+        writer: impl Write,
+    ) -> Result<u64, Error> {
+        let response = self.post_response(GotenbergRequest::encrypt_pdf(
+            pdf,
+            user_password,
+            owner_password,
+        ))?;
+        sink::copy_response(response, writer, OutputKind::Pdf)
+    }
+
+    /// Same as [`BlockingClient::encrypt_pdf`], but writes the PDF to the file at `path` and returns the number of bytes written.
+    ///
+    /// See [`BlockingClient::pdf_from_url_to_path`] for how partial files are avoided.
+    pub fn encrypt_pdf_to_path(
+        &self,
+        pdf: impl Into<InputFile>,
+        user_password: impl Into<Secret>,
+        owner_password: Option<Secret>,
+        path: impl AsRef<Path>,
+    ) -> Result<u64, Error> {
+        let response = self.post_response(GotenbergRequest::encrypt_pdf(
+            pdf,
+            user_password,
+            owner_password,
+        ))?;
+        sink::copy_response_to_path(response, path.as_ref(), OutputKind::Pdf)
+    }
+
+    /// Flatten a PDF file, merging its form fields and annotations into the page content so they can no longer be edited.
+    pub fn flatten_pdf(
+        &self,
+        pdf: impl Into<InputFile>,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<Bytes, Error> {
+        self.flatten_pdf_with_response(pdf, webhook)
+            .map(|output| output.bytes)
+    }
+
+    /// Same as [`BlockingClient::flatten_pdf`], but returns a [`RenderOutput`] with the output filename, content type and trace.
+    pub fn flatten_pdf_with_response(
+        &self,
+        pdf: impl Into<InputFile>,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        self.post_output(GotenbergRequest::flatten_pdf(pdf), webhook)
+    }
+
+    /// Same as [`BlockingClient::flatten_pdf`], but copies the PDF into `writer` and returns the number of bytes written.
+    pub fn flatten_pdf_to_writer(
+        &self,
+        pdf: impl Into<InputFile>,
+        writer: impl Write,
+    ) -> Result<u64, Error> {
+        let response = self.post_response(GotenbergRequest::flatten_pdf(pdf))?;
+        sink::copy_response(response, writer, OutputKind::Pdf)
+    }
+
+    /// Same as [`BlockingClient::flatten_pdf`], but writes the PDF to the file at `path` and returns the number of bytes written.
+    ///
+    /// See [`BlockingClient::pdf_from_url_to_path`] for how partial files are avoided.
+    pub fn flatten_pdf_to_path(
+        &self,
+        pdf: impl Into<InputFile>,
+        path: impl AsRef<Path>,
+    ) -> Result<u64, Error> {
+        let response = self.post_response(GotenbergRequest::flatten_pdf(pdf))?;
+        sink::copy_response_to_path(response, path.as_ref(), OutputKind::Pdf)
+    }
+
+    /// Embed files into a PDF as attachments, eg. the XML invoice of a ZUGFeRD / Factur-X document.
+    pub fn embed_files(
+        &self,
+        pdf: impl Into<InputFile>,
+        attachments: Vec<impl Into<EmbeddedFile>>,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<Bytes, Error> {
+        self.embed_files_with_response(pdf, attachments, webhook)
+            .map(|output| output.bytes)
+    }
+
+    /// Same as [`BlockingClient::embed_files`], but returns a [`RenderOutput`] with the output filename, content type and trace.
+    pub fn embed_files_with_response(
+        &self,
+        pdf: impl Into<InputFile>,
+        attachments: Vec<impl Into<EmbeddedFile>>,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        let request = GotenbergRequest::embed_files(pdf, attachments)?;
+        self.post_output(request, webhook)
+    }
+
+    /// Same as [`BlockingClient::embed_files`], but copies the PDF into `writer` and returns the number of bytes written.
+    pub fn embed_files_to_writer(
+        &self,
+        pdf: impl Into<InputFile>,
+        attachments: Vec<impl Into<EmbeddedFile>>,
+        writer: impl Write,
+    ) -> Result<u64, Error> {
+        let response = self.post_response(GotenbergRequest::embed_files(pdf, attachments)?)?;
+        sink::copy_response(response, writer, OutputKind::Pdf)
+    }
+
+    /// Same as [`BlockingClient::embed_files`], but writes the PDF to the file at `path` and returns the number of bytes written.
+    ///
+    /// See [`BlockingClient::pdf_from_url_to_path`] for how partial files are avoided.
+    pub fn embed_files_to_path(
+        &self,
+        pdf: impl Into<InputFile>,
+        attachments: Vec<impl Into<EmbeddedFile>>,
+        path: impl AsRef<Path>,
+    ) -> Result<u64, Error> {
+        let response = self.post_response(GotenbergRequest::embed_files(pdf, attachments)?)?;
+        sink::copy_response_to_path(response, path.as_ref(), OutputKind::Pdf)
     }
 
     /// Read the metadata of a PDF file
     pub fn read_metadata(
         &self,
-        pdf_bytes: Vec<u8>,
+        pdf: impl Into<InputFile>,
     ) -> Result<HashMap<String, serde_json::Value>, Error> {
-        let form = multipart::Form::new();
-        let part = multipart::Part::bytes(pdf_bytes).file_name("file.pdf".to_string());
-        let form = form.part("file.pdf", part);
-
-        #[derive(Debug, Deserialize)]
-        pub struct MetadataContainer {
-            #[serde(rename = "file.pdf")]
-            pub filepdf: HashMap<String, serde_json::Value>,
-        }
-
-        let bytes = self.post("forms/pdfengines/metadata/read", form, None)?;
-        let metadata: MetadataContainer = serde_json::from_slice(&bytes).map_err(|e| {
-            Error::ParseError(
-                "Metadata".to_string(),
-                String::from_utf8_lossy(&bytes).to_string(),
-                e.to_string(),
-            )
-        })?;
-        Ok(metadata.filepdf)
+        let bytes = self.post(GotenbergRequest::read_metadata(pdf), None)?;
+        parse_metadata(&bytes)
     }
 
     /// Write metadata to a PDF file
     pub fn write_metadata(
         &self,
-        pdf_bytes: Vec<u8>,
+        pdf: impl Into<InputFile>,
         metadata: HashMap<String, serde_json::Value>,
+        webhook: Option<&WebhookOptions>,
     ) -> Result<Bytes, Error> {
-        let form = multipart::Form::new();
-        let part = multipart::Part::bytes(pdf_bytes).file_name("file.pdf".to_string());
-        let form = form.part("file.pdf", part);
+        self.write_metadata_with_response(pdf, metadata, webhook)
+            .map(|output| output.bytes)
+    }
 
-        let metadata = serde_json::to_string(&metadata).map_err(|e| {
-            Error::ParseError("Metadata".to_string(), "".to_string(), e.to_string())
-        })?;
+    /// Same as [`BlockingClient::write_metadata`], but returns a [`RenderOutput`] with the output filename, content type and trace.
+    pub fn write_metadata_with_response(
+        &self,
+        pdf: impl Into<InputFile>,
+        metadata: HashMap<String, serde_json::Value>,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        let request = GotenbergRequest::write_metadata(pdf, metadata)?;
+        self.post_output(request, webhook)
+    }
+
+    /// Same as [`BlockingClient::write_metadata`], but copies the PDF into `writer` and returns the number of bytes written.
+    pub fn write_metadata_to_writer(
+        &self,
+        pdf: impl Into<InputFile>,
+        metadata: HashMap<String, serde_json::Value>,
+        writer: impl Write,
+    ) -> Result<u64, Error> {
+        let response = self.post_response(GotenbergRequest::write_metadata(pdf, metadata)?)?;
+        sink::copy_response(response, writer, OutputKind::Pdf)
+    }
 
-        let part = multipart::Part::text(metadata);
-        let form = form.part("metadata", part);
+    /// Same as [`BlockingClient::write_metadata`], but writes the PDF to the file at `path` and returns the number of bytes written.
+    ///
+    /// See [`BlockingClient::pdf_from_url_to_path`] for how partial files are avoided.
+    pub fn write_metadata_to_path(
+        &self,
+        pdf: impl Into<InputFile>,
+        metadata: HashMap<String, serde_json::Value>,
+        path: impl AsRef<Path>,
+    ) -> Result<u64, Error> {
+        let response = self.post_response(GotenbergRequest::write_metadata(pdf, metadata)?)?;
+        sink::copy_response_to_path(response, path.as_ref(), OutputKind::Pdf)
+    }
 
-        self.post("forms/pdfengines/metadata/write", form, None)
+    /// Send a GET request to `url` through the transport and return the body of the response.
+    fn get_text(&self, url: &str) -> Result<String, Error> {
+        let mut body = Vec::new();
+        self.transport
+            .get(url)?
+            .read_to_end(&mut body)
+            .map_err(Error::IoError)?;
+        Ok(String::from_utf8_lossy(&body).into_owned())
     }
 
     /// Get the health status of the Gotenberg server.
     pub fn health_check(&self) -> Result<health::Health, Error> {
         let url = format!("{}/health", self.base_url);
-        let response = self.client.get(&url).send().map_err(Into::into)?;
-        let body = response.text().map_err(Into::into)?;
+        let body = self.get_text(&url)?;
         serde_json::from_str(&body)
             .map_err(|e| Error::ParseError("Health".to_string(), body, e.to_string()))
     }
@@ -378,11 +1051,15 @@ impl BlockingClient {
     /// Get the version of the Gotenberg server.
     pub fn version(&self) -> Result<String, Error> {
         let url = format!("{}/version", self.base_url);
-        let response = self.client.get(&url).send().map_err(Into::into)?;
-        let body = response.text().map_err(Into::into)?;
+        let body = self.get_text(&url)?;
         Ok(body)
     }
 
+    /// Get the version of the Gotenberg server, parsed into a [`GotenbergVersion`].
+    pub fn version_typed(&self) -> Result<GotenbergVersion, Error> {
+        self.version()?.parse()
+    }
+
     /// Get the metrics of the Gotenberg server in prometheus format.
     /// The results will not be parsed and are returned as a multi-line string.
     ///
@@ -394,8 +1071,15 @@ impl BlockingClient {
     /// - `{namespace}_libreoffice_restarts_count`      Current number of LibreOffice restarts.
     pub fn metrics(&self) -> Result<String, Error> {
         let url = format!("{}/prometheus/metrics", self.base_url);
-        let response = self.client.get(&url).send().map_err(Into::into)?;
-        let body = response.text().map_err(Into::into)?;
+        let body = self.get_text(&url)?;
         Ok(body)
     }
+
+    /// Get the metrics of the Gotenberg server, parsed into [`metrics::GotenbergMetrics`].
+    ///
+    /// The namespace set with `--prometheus-namespace` is detected from the metric names.
+    pub fn metrics_typed(&self) -> Result<metrics::GotenbergMetrics, Error> {
+        let body = self.metrics()?;
+        metrics::GotenbergMetrics::parse(&body)
+    }
 }
@@ -1,6 +1,17 @@
 use super::*;
-use reqwest::multipart;
-use reqwest::{Client as ReqwestClient, Response};
+use reqwest::Client as ReqwestClient;
+use std::future::Future;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::cluster::Cluster;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::limiter::Limiters;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::transport::{ReqwestTransport, StreamedFile, Transport, TransportRequest};
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
 
 #[cfg(feature = "zeroize")]
 use zeroize::Zeroize;
@@ -11,12 +22,31 @@ use zeroize::Zeroize;
 /// All clones use the same connection pool for connection re-use.
 #[derive(Clone)]
 pub struct Client {
+    #[cfg(target_arch = "wasm32")]
     client: ReqwestClient,
     base_url: String,
     username: Option<String>,
     password: Option<String>,
+    retry_policy: Option<RetryPolicy>,
+    server_version: Option<GotenbergVersion>,
+    #[cfg(not(target_arch = "wasm32"))]
+    cluster: Option<Arc<Cluster>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    balancing: Balancing,
+    #[cfg(not(target_arch = "wasm32"))]
+    limiters: Arc<Limiters>,
+    #[cfg(not(target_arch = "wasm32"))]
+    transport: Arc<dyn Transport>,
 }
 
+/// The files streamed after the fields and files of a request, see [`FileUpload`].
+#[cfg(not(target_arch = "wasm32"))]
+type Uploads = Vec<StreamedFile>;
+
+/// Streamed uploads are not available on wasm32.
+#[cfg(target_arch = "wasm32")]
+type Uploads = ();
+
 impl Drop for Client {
     fn drop(&mut self) {
         // Securely zeroize the username and password
@@ -34,10 +64,21 @@ impl Drop for Client {
 
 impl Debug for Client {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("Client")
+        let mut debug = f.debug_struct("Client");
+        debug
             .field("base_url", &self.base_url)
             .field("username", &self.username)
-            .finish()
+            .field("retry_policy", &self.retry_policy)
+            .field("server_version", &self.server_version);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        debug
+            .field("cluster", &self.cluster)
+            .field("balancing", &self.balancing)
+            .field("limiters", &self.limiters)
+            .field("transport", &self.transport);
+
+        debug.finish()
     }
 }
 
@@ -50,10 +91,21 @@ impl Client {
         let client = Client::create_client();
 
         Client {
-            client,
+            #[cfg(target_arch = "wasm32")]
+            client: client.clone(),
             base_url: base_url.to_string(),
             username: None,
             password: None,
+            retry_policy: None,
+            server_version: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            cluster: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            balancing: Balancing::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            limiters: Arc::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            transport: Arc::new(ReqwestTransport::new(client.clone())),
         }
     }
 
@@ -79,10 +131,139 @@ impl Client {
         // Strip trailing slashes
         let base_url = base_url.trim_end_matches('/');
         Client {
-            client,
+            #[cfg(target_arch = "wasm32")]
+            client: client.clone(),
             base_url: base_url.to_string(),
             username: None,
             password: None,
+            retry_policy: None,
+            server_version: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            cluster: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            balancing: Balancing::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            limiters: Arc::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            transport: Arc::new(ReqwestTransport::new(client.clone())),
+        }
+    }
+
+    /// Create a new instance of the API client that spreads requests over several Gotenberg instances.
+    ///
+    /// Requests are sent round-robin by default, see [`Client::balancing`]. If an instance cannot be reached
+    /// or answers `503 Service Unavailable`, the request is sent to the next instance. Instances that fail
+    /// a health probe, or whose Chromium or LibreOffice module is down, are skipped for the matching routes
+    /// while [`Client::health_monitor`] runs. [`Client::health_check`], [`Client::version`] and [`Client::metrics`]
+    /// query the first instance.
+    ///
+    /// Not available on wasm32.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `base_urls` is empty.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gotenberg_pdf::{Balancing, Client};
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::with_endpoints(vec![
+    ///         "http://gotenberg-1:3000".to_string(),
+    ///         "http://gotenberg-2:3000".to_string(),
+    ///     ])
+    ///     .balancing(Balancing::LeastInFlight);
+    ///
+    ///     tokio::spawn(client.health_monitor(Duration::from_secs(10)));
+    ///
+    ///     // Now you can use the client to make requests
+    /// }
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_endpoints(base_urls: Vec<String>) -> Self {
+        assert!(
+            !base_urls.is_empty(),
+            "Client::with_endpoints requires at least one base URL"
+        );
+
+        let mut client = Client::new(&base_urls[0]);
+        client.cluster = Some(Arc::new(Cluster::new(&base_urls)));
+        client
+    }
+
+    /// Set how requests are spread over the instances of a client created with [`Client::with_endpoints`],
+    /// consuming the current client and returning a new instance of the client.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn balancing(self, balancing: Balancing) -> Self {
+        let mut client = self;
+        client.balancing = balancing;
+
+        client
+    }
+
+    /// Limit the number of concurrent requests per engine, consuming the current client and returning a new instance of the client.
+    ///
+    /// Requests over the limit wait in a queue until a slot is free. The limits are shared by all clones of the
+    /// returned client, see [`ConcurrencyLimits`]. Retries give up their slot while waiting for the backoff.
+    ///
+    /// Not available on wasm32.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn concurrency_limits(self, limits: ConcurrencyLimits) -> Self {
+        let mut client = self;
+        client.limiters = Arc::new(Limiters::new(limits));
+
+        client
+    }
+
+    /// The queue depth and wait times of the requests to the given engine,
+    /// or `None` if there is no limit set for the engine.
+    ///
+    /// Not available on wasm32.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn limiter_stats(&self, engine: Engine) -> Option<LimiterStats> {
+        self.limiters.get(engine).map(|limiter| limiter.stats())
+    }
+
+    /// Probe the health of every instance of a client created with [`Client::with_endpoints`] once.
+    ///
+    /// Instances that take longer than `timeout` to answer are considered unreachable.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn refresh_health(&self, timeout: Duration) {
+        let Some(cluster) = &self.cluster else {
+            return;
+        };
+
+        for index in 0..cluster.len() {
+            let url = format!("{}/health", cluster.base_url(index));
+            let health = async {
+                let body = tokio::time::timeout(timeout, self.get_text(url))
+                    .await
+                    .ok()?
+                    .ok()?;
+                serde_json::from_str::<health::Health>(&body).ok()
+            }
+            .await;
+            cluster.update(index, health.as_ref());
+        }
+    }
+
+    /// Probe the health of every instance of a client created with [`Client::with_endpoints`] at the given interval.
+    ///
+    /// The returned future runs forever and should be spawned on the runtime, eg. with `tokio::spawn`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn health_monitor(
+        &self,
+        interval: Duration,
+    ) -> impl std::future::Future<Output = ()> + Send + 'static {
+        let client = self.clone();
+        async move {
+            loop {
+                client.refresh_health(interval).await;
+                tokio::time::sleep(interval).await;
+            }
         }
     }
 
@@ -109,48 +290,254 @@ impl Client {
         client
     }
 
-    /// Generic POST method that takes a multipart form and sends it.
+    /// Retry requests that failed for a transient reason according to the given policy,
+    /// consuming the current client and returning a new instance of the client.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gotenberg_pdf::{Client, RetryPolicy};
+    ///
+    /// let client = Client::new("http://localhost:3000").retry_policy(RetryPolicy::new(5));
+    /// ```
+    pub fn retry_policy(self, policy: RetryPolicy) -> Self {
+        let mut client = self;
+        client.retry_policy = Some(policy);
+
+        client
+    }
+
+    /// Set the version of the Gotenberg server, consuming the current client and returning a new instance of the client.
+    ///
+    /// Requests using a route or an option the server does not support then fail with
+    /// [`Error::UnsupportedFeature`] before anything is sent. Use [`Client::version_typed`] to query the version.
+    pub fn server_version(self, version: GotenbergVersion) -> Self {
+        let mut client = self;
+        client.server_version = Some(version);
+
+        client
+    }
+
+    /// Send the requests through the given transport instead of reqwest, consuming the current client and returning a new instance of the client.
+    ///
+    /// Basic auth, retries, failover and concurrency limits still apply. [`Client::health_check`], [`Client::version`]
+    /// and [`Client::metrics`] go through the transport too. See the [`transport`] module for the available backends.
+    ///
+    /// Not available on wasm32.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn transport(self, transport: impl Transport + 'static) -> Self {
+        let mut client = self;
+        client.transport = Arc::new(transport);
+
+        client
+    }
+
+    /// Generic POST method that encodes a request into a multipart form and sends it.
+    /// The form is rebuilt for every attempt, as a sent form cannot be reused.
     /// If webhook options are provided, their headers are added to the request.
     /// In the case of webhook usage, the server will respond with 204 No Content and no bytes,
     /// so we return empty bytes.
     async fn post(
         &self,
-        endpoint: &str,
-        form: multipart::Form,
-        trace: Option<String>,
+        request: GotenbergRequest,
         webhook: Option<&WebhookOptions>,
     ) -> Result<Bytes, Error> {
-        let url = format!("{}/{}", self.base_url, endpoint);
-        let mut req = self.client.post(&url).multipart(form);
+        self.post_output(request, webhook)
+            .await
+            .map(|output| output.bytes)
+    }
+
+    /// Same as [`Client::post`], but keeps the response headers alongside the bytes.
+    async fn post_output(
+        &self,
+        request: GotenbergRequest,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        self.post_form(request, webhook, || async { Ok(Uploads::default()) })
+            .await
+    }
+
+    /// Send the request followed by the files opened by `uploads`, which is called for every attempt.
+    async fn post_form<F>(
+        &self,
+        request: GotenbergRequest,
+        webhook: Option<&WebhookOptions>,
+        uploads: impl Fn() -> F,
+    ) -> Result<RenderOutput, Error>
+    where
+        F: Future<Output = Result<Uploads, Error>>,
+    {
+        version::check_features(
+            self.server_version.as_ref(),
+            &request.endpoint,
+            &request.features,
+        )?;
+        let request = request.webhook(webhook)?;
+
+        let mut attempt = 1;
+        loop {
+            let result = {
+                #[cfg(not(target_arch = "wasm32"))]
+                let _permit = match Engine::from_endpoint(&request.endpoint)
+                    .and_then(|engine| self.limiters.get(engine))
+                {
+                    Some(limiter) => Some(limiter.acquire().await),
+                    None => None,
+                };
+
+                self.send_balanced(&request, &uploads).await
+            };
+
+            let delay = match (&result, &self.retry_policy) {
+                (Err(error), Some(policy)) => policy.delay_after(attempt, error),
+                _ => None,
+            };
+            match delay {
+                Some(delay) => retry::sleep(delay).await,
+                None => return result,
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Send a request to the best instance, failing over to the others if it is unreachable or unavailable.
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn send_balanced<F>(
+        &self,
+        request: &GotenbergRequest,
+        uploads: &impl Fn() -> F,
+    ) -> Result<RenderOutput, Error>
+    where
+        F: Future<Output = Result<Uploads, Error>>,
+    {
+        let Some(cluster) = &self.cluster else {
+            return self.send(&self.base_url, request, uploads().await?).await;
+        };
+
+        let mut last_error = None;
+        let engine = Engine::from_endpoint(&request.endpoint);
+        for index in cluster.candidates(engine, self.balancing) {
+            let uploads = match uploads().await {
+                Ok(uploads) => uploads,
+                // Readers and streams cannot be sent again, report why the previous instance failed instead
+                Err(error) => return Err(last_error.unwrap_or(error)),
+            };
+            let _in_flight = cluster.begin(index);
+            let result = self.send(cluster.base_url(index), request, uploads).await;
+
+            match result {
+                Err(Error::CommunicationError(error)) if !error.is_builder() => {
+                    cluster.mark_unreachable(index);
+                    last_error = Some(Error::CommunicationError(error));
+                }
+                Err(Error::TransportError(error)) => {
+                    cluster.mark_unreachable(index);
+                    last_error = Some(Error::TransportError(error));
+                }
+                Err(Error::RenderingError(error)) if error.kind == ErrorKind::Unavailable => {
+                    last_error = Some(Error::RenderingError(error));
+                }
+                result => return result,
+            }
+        }
+
+        Err(last_error.expect("a cluster has at least one instance"))
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    async fn send_balanced<F>(
+        &self,
+        request: &GotenbergRequest,
+        uploads: &impl Fn() -> F,
+    ) -> Result<RenderOutput, Error>
+    where
+        F: Future<Output = Result<Uploads, Error>>,
+    {
+        self.send(&self.base_url, request, uploads().await?).await
+    }
+
+    /// Send a single request to the given instance through the transport.
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn send(
+        &self,
+        base_url: &str,
+        request: &GotenbergRequest,
+        uploads: Uploads,
+    ) -> Result<RenderOutput, Error> {
+        let mut request = request.clone();
+        // Add basic auth if username and password are provided
+        if let (Some(username), Some(password)) = (&self.username, &self.password) {
+            request = request.header("Authorization", transport::basic_auth(username, password));
+        }
+        let trace = request.trace().map(str::to_string);
 
-        if let Some(trace) = trace {
-            req = req.header("Gotenberg-Trace", trace);
+        let response = self
+            .transport
+            .send(TransportRequest {
+                url: format!("{}/{}", base_url, request.endpoint),
+                request,
+                uploads,
+            })
+            .await?;
+
+        // If webhook is enabled, the server returns 204 No Content.
+        if response.status == 204 {
+            let mut output = RenderOutput::from_parts(&response.headers, Bytes::new());
+            output.trace = output.trace.or(trace);
+            return Ok(output);
         }
-        if let Some(webhook) = webhook {
-            req = req.headers(webhook.to_headers());
+
+        if !(200..300).contains(&response.status) {
+            let status = response.status;
+            let headers = response.headers.clone();
+            let body = response.bytes().await.unwrap_or_default();
+            let body = String::from_utf8_lossy(&body).to_string();
+            return Err(Error::RenderingError(ApiError::new(status, body, &headers)));
+        }
+
+        let headers = response.headers.clone();
+        let bytes = response.bytes().await?;
+        Ok(RenderOutput::from_parts(&headers, bytes))
+    }
+
+    /// Send a single request to the given instance.
+    #[cfg(target_arch = "wasm32")]
+    async fn send(
+        &self,
+        base_url: &str,
+        request: &GotenbergRequest,
+        _uploads: Uploads,
+    ) -> Result<RenderOutput, Error> {
+        let url = format!("{}/{}", base_url, request.endpoint);
+        let mut req = self.client.post(&url).multipart(request.form()?);
+
+        for (name, value) in &request.headers {
+            req = req.header(name, value);
         }
         // Add basic auth if username and password are provided
         if let (Some(username), Some(password)) = (&self.username, &self.password) {
             req = req.basic_auth(username, Some(password));
         }
 
-        let response: Response = req.send().await.map_err(Into::into)?;
+        let response: reqwest::Response = req.send().await.map_err(Into::into)?;
 
         // If webhook is enabled, the server returns 204 No Content.
         if response.status() == reqwest::StatusCode::NO_CONTENT {
-            return Ok(Bytes::new());
+            let mut output = RenderOutput::from_parts(response.headers(), Bytes::new());
+            output.trace = output.trace.or(request.trace().map(str::to_string));
+            return Ok(output);
         }
 
         if !response.status().is_success() {
-            let status = response.status();
+            let status = response.status().as_u16();
+            let headers = response.headers().clone();
             let body = response.text().await.unwrap_or_default();
-            return Err(Error::RenderingError(format!(
-                "Failed to render PDF: {} - {}",
-                status, body
-            )));
+            return Err(Error::RenderingError(ApiError::new(status, body, &headers)));
         }
 
-        response.bytes().await.map_err(Into::into)
+        let headers = response.headers().clone();
+        let bytes = response.bytes().await.map_err(Into::into)?;
+        Ok(RenderOutput::from_parts(&headers, bytes))
     }
 
     /// Convert a URL to a PDF using the Chromium engine.
@@ -160,31 +547,45 @@ impl Client {
         options: WebOptions,
         webhook: Option<&WebhookOptions>,
     ) -> Result<Bytes, Error> {
-        let trace = options.trace_id.clone();
-        let form = multipart::Form::new().text("url", url.to_string());
-        let form = options.fill_form(form);
-        self.post("forms/chromium/convert/url", form, trace, webhook)
+        self.pdf_from_url_with_response(url, options, webhook)
             .await
+            .map(|output| output.bytes)
+    }
+
+    /// Same as [`Client::pdf_from_url`], but returns a [`RenderOutput`] with the output filename, content type and trace.
+    pub async fn pdf_from_url_with_response(
+        &self,
+        url: &str,
+        options: WebOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        let request = GotenbergRequest::pdf_from_url(url, options)?;
+        self.post_output(request, webhook).await
     }
 
     /// Convert HTML to a PDF using the Chromium engine.
+    ///
+    /// Pass an [`HtmlBundle`] to upload images, stylesheets or fonts alongside the HTML.
     pub async fn pdf_from_html(
         &self,
-        html: &str,
+        html: impl Into<HtmlBundle>,
         options: WebOptions,
         webhook: Option<&WebhookOptions>,
     ) -> Result<Bytes, Error> {
-        let trace = options.trace_id.clone();
-        let form = multipart::Form::new();
-        let file_bytes = html.to_string().into_bytes();
-        let part = multipart::Part::bytes(file_bytes)
-            .file_name("index.html")
-            .mime_str("text/html")
-            .unwrap();
-        let form = form.part("index.html", part);
-        let form = options.fill_form(form);
-        self.post("forms/chromium/convert/html", form, trace, webhook)
+        self.pdf_from_html_with_response(html, options, webhook)
             .await
+            .map(|output| output.bytes)
+    }
+
+    /// Same as [`Client::pdf_from_html`], but returns a [`RenderOutput`] with the output filename, content type and trace.
+    pub async fn pdf_from_html_with_response(
+        &self,
+        html: impl Into<HtmlBundle>,
+        options: WebOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        let request = GotenbergRequest::pdf_from_html(html, options)?;
+        self.post_output(request, webhook).await
     }
 
     /// Convert Markdown to a PDF using the Chromium engine.
@@ -207,43 +608,26 @@ impl Client {
     /// The markdown files should be in a "filename" => "content" format. The filename key string must end with `.md`.
     pub async fn pdf_from_markdown(
         &self,
-        html_template: &str,
+        html_template: impl Into<HtmlBundle>,
         markdown: HashMap<&str, &str>,
         options: WebOptions,
         webhook: Option<&WebhookOptions>,
     ) -> Result<Bytes, Error> {
-        let trace = options.trace_id.clone();
-
-        let form = multipart::Form::new();
-
-        let file_bytes = html_template.to_string().into_bytes();
-        let part = multipart::Part::bytes(file_bytes)
-            .file_name("index.html")
-            .mime_str("text/html")
-            .unwrap();
-        let form = form.part("index.html", part);
-        let form = options.fill_form(form);
-
-        let form = {
-            let mut form = form;
-            for (filename, content) in markdown {
-                if !filename.ends_with(".md") {
-                    return Err(Error::FilenameError(
-                        "Markdown filename must end with '.md'".to_string(),
-                    ));
-                }
-                let file_bytes = content.to_string().into_bytes();
-                let part = multipart::Part::bytes(file_bytes)
-                    .file_name(filename.to_string())
-                    .mime_str("text/markdown")
-                    .unwrap();
-                form = form.part(filename.to_string(), part);
-            }
-            form
-        };
-
-        self.post("forms/chromium/convert/markdown", form, trace, webhook)
+        self.pdf_from_markdown_with_response(html_template, markdown, options, webhook)
             .await
+            .map(|output| output.bytes)
+    }
+
+    /// Same as [`Client::pdf_from_markdown`], but returns a [`RenderOutput`] with the output filename, content type and trace.
+    pub async fn pdf_from_markdown_with_response(
+        &self,
+        html_template: impl Into<HtmlBundle>,
+        markdown: HashMap<&str, &str>,
+        options: WebOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        let request = GotenbergRequest::pdf_from_markdown(html_template, markdown, options)?;
+        self.post_output(request, webhook).await
     }
 
     /// Take a screenshot of a webpage using the Chromium engine.
@@ -253,74 +637,70 @@ impl Client {
         options: ScreenshotOptions,
         webhook: Option<&WebhookOptions>,
     ) -> Result<Bytes, Error> {
-        let trace = options.trace_id.clone();
-        let form = multipart::Form::new().text("url", url.to_string());
-        let form = options.fill_form(form);
-        self.post("forms/chromium/screenshot/url", form, trace, webhook)
+        self.screenshot_url_with_response(url, options, webhook)
             .await
+            .map(|output| output.bytes)
+    }
+
+    /// Same as [`Client::screenshot_url`], but returns a [`RenderOutput`] with the output filename, content type and trace.
+    pub async fn screenshot_url_with_response(
+        &self,
+        url: &str,
+        options: ScreenshotOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        let request = GotenbergRequest::screenshot_url(url, options);
+        self.post_output(request, webhook).await
     }
 
     /// Take a screenshot of an HTML page using the Chromium engine.
+    ///
+    /// Pass an [`HtmlBundle`] to upload images, stylesheets or fonts alongside the HTML.
     pub async fn screenshot_html(
         &self,
-        html: &str,
+        html: impl Into<HtmlBundle>,
         options: ScreenshotOptions,
         webhook: Option<&WebhookOptions>,
     ) -> Result<Bytes, Error> {
-        let trace = options.trace_id.clone();
-
-        let form = multipart::Form::new();
-        let file_bytes = html.to_string().into_bytes();
-        let part = multipart::Part::bytes(file_bytes)
-            .file_name("index.html")
-            .mime_str("text/html")
-            .unwrap();
-        let form = form.part("index.html", part);
-        let form = options.fill_form(form);
-        self.post("forms/chromium/screenshot/html", form, trace, webhook)
+        self.screenshot_html_with_response(html, options, webhook)
             .await
+            .map(|output| output.bytes)
+    }
+
+    /// Same as [`Client::screenshot_html`], but returns a [`RenderOutput`] with the output filename, content type and trace.
+    pub async fn screenshot_html_with_response(
+        &self,
+        html: impl Into<HtmlBundle>,
+        options: ScreenshotOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        let request = GotenbergRequest::screenshot_html(html, options)?;
+        self.post_output(request, webhook).await
     }
 
     /// Take a screenshot of a set of markdown files using the Chromium engine.
     pub async fn screenshot_markdown(
         &self,
-        html_template: &str,
+        html_template: impl Into<HtmlBundle>,
         markdown: HashMap<&str, &str>,
         options: ScreenshotOptions,
         webhook: Option<&WebhookOptions>,
     ) -> Result<Bytes, Error> {
-        let trace = options.trace_id.clone();
-
-        let form = multipart::Form::new();
-
-        let file_bytes = html_template.to_string().into_bytes();
-        let part = multipart::Part::bytes(file_bytes)
-            .file_name("index.html")
-            .mime_str("text/html")
-            .unwrap();
-        let form = form.part("index.html", part);
-        let form = options.fill_form(form);
-
-        let form = {
-            let mut form = form;
-            for (filename, content) in markdown {
-                if !filename.ends_with(".md") {
-                    return Err(Error::FilenameError(
-                        "Markdown filename must end with '.md'".to_string(),
-                    ));
-                }
-                let file_bytes = content.to_string().into_bytes();
-                let part = multipart::Part::bytes(file_bytes)
-                    .file_name(filename.to_string())
-                    .mime_str("text/markdown")
-                    .unwrap();
-                form = form.part(filename.to_string(), part);
-            }
-            form
-        };
-
-        self.post("forms/chromium/screenshot/markdown", form, trace, webhook)
+        self.screenshot_markdown_with_response(html_template, markdown, options, webhook)
             .await
+            .map(|output| output.bytes)
+    }
+
+    /// Same as [`Client::screenshot_markdown`], but returns a [`RenderOutput`] with the output filename, content type and trace.
+    pub async fn screenshot_markdown_with_response(
+        &self,
+        html_template: impl Into<HtmlBundle>,
+        markdown: HashMap<&str, &str>,
+        options: ScreenshotOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        let request = GotenbergRequest::screenshot_markdown(html_template, markdown, options)?;
+        self.post_output(request, webhook).await
     }
 
     /// Convert a document to a PDF using the LibreOffice engine.
@@ -338,95 +718,267 @@ impl Client {
     /// .wb2 .wk1 .wks .wmf .wpd .wpg .wps .xbm .xhtml .xls .xlsb .xlsm .xlsx .xlt
     /// .xltm .xltx .xlw .xml .xpm .zabw
     /// ```
+    ///
+    /// The document is either uploaded with its filename, eg. `("report.docx", bytes)`, or a [`DownloadFrom`] the server downloads.
     pub async fn pdf_from_doc(
         &self,
-        filename: &str,
-        bytes: Vec<u8>,
+        file: impl Into<NamedFile>,
         options: DocumentOptions,
         webhook: Option<&WebhookOptions>,
     ) -> Result<Bytes, Error> {
-        let trace = options.trace_id.clone();
-
-        let form = multipart::Form::new();
-        let part = multipart::Part::bytes(bytes).file_name(filename.to_string());
-        let form = form.part("files", part);
-        let form = options.fill_form(form);
-        self.post("forms/libreoffice/convert", form, trace, webhook)
+        self.pdf_from_doc_with_response(file, options, webhook)
             .await
+            .map(|output| output.bytes)
+    }
+
+    /// Same as [`Client::pdf_from_doc`], but returns a [`RenderOutput`] with the output filename, content type and trace.
+    pub async fn pdf_from_doc_with_response(
+        &self,
+        file: impl Into<NamedFile>,
+        options: DocumentOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        let request = GotenbergRequest::pdf_from_doc(file, options)?;
+        self.post_output(request, webhook).await
+    }
+
+    /// Convert several documents to PDF in a single request using the LibreOffice engine.
+    ///
+    /// With `options.merge` set, the documents are merged into a single PDF in the order they are given,
+    /// otherwise one PDF is returned per document. See [`Client::pdf_from_doc`] for the supported file formats.
+    ///
+    /// Documents are given as `(filename, bytes)` tuples, or as [`DownloadFrom`] for the server to download them.
+    /// Downloaded documents are named by the server, so they cannot be merged in order: with `options.merge` set,
+    /// a [`DownloadFrom`] fails the request with an [`Error::FilenameError`].
+    pub async fn pdfs_from_docs(
+        &self,
+        files: Vec<impl Into<NamedFile>>,
+        options: DocumentOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<ConvertedDocuments, Error> {
+        let merge = options.merge.unwrap_or(false);
+        let files: Vec<NamedFile> = files.into_iter().map(Into::into).collect();
+        let filenames: Vec<String> = files
+            .iter()
+            .filter_map(|file| file.filename().map(str::to_string))
+            .collect();
+        let output = self
+            .pdfs_from_docs_with_response(files, options, webhook)
+            .await?;
+        ConvertedDocuments::from_response(output.bytes, merge, &filenames)
+    }
+
+    /// Same as [`Client::pdfs_from_docs`], but returns the raw [`RenderOutput`], see [`RenderOutput::files`] to unpack it.
+    pub async fn pdfs_from_docs_with_response(
+        &self,
+        files: Vec<impl Into<NamedFile>>,
+        options: DocumentOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        let request = GotenbergRequest::pdfs_from_docs(files, options)?;
+        self.post_output(request, webhook).await
     }
 
     /// Transforms a PDF file into the requested PDF/A format and/or PDF/UA.
     pub async fn convert_pdf(
         &self,
-        pdf_bytes: Vec<u8>,
+        pdf: impl Into<InputFile>,
         pdfa: Option<PDFFormat>,
         pdfua: bool,
         webhook: Option<&WebhookOptions>,
     ) -> Result<Bytes, Error> {
-        let form = multipart::Form::new();
-        let part = multipart::Part::bytes(pdf_bytes).file_name("file.pdf".to_string());
-        let mut form = form.part("file.pdf", part);
-        if let Some(pdfa) = pdfa {
-            form = form.text("pdfa", pdfa.to_string());
-        }
-        let form = form.text("pdfua", pdfua.to_string());
-        self.post("forms/pdfengines/convert", form, None, webhook)
+        self.convert_pdf_with_response(pdf, pdfa, pdfua, webhook)
+            .await
+            .map(|output| output.bytes)
+    }
+
+    /// Same as [`Client::convert_pdf`], but returns a [`RenderOutput`] with the output filename, content type and trace.
+    pub async fn convert_pdf_with_response(
+        &self,
+        pdf: impl Into<InputFile>,
+        pdfa: Option<PDFFormat>,
+        pdfua: bool,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        let request = GotenbergRequest::convert_pdf(pdf, pdfa, pdfua);
+        self.post_output(request, webhook).await
+    }
+
+    /// Merge several PDF files into a single PDF.
+    ///
+    /// The files are merged in the order they are given. Every filename must end with `.pdf`.
+    /// Files downloaded by the server are named by the server, so their order could not be kept: a
+    /// [`DownloadFrom`] fails the request with an [`Error::FilenameError`].
+    pub async fn merge_pdfs(
+        &self,
+        files: Vec<impl Into<NamedFile>>,
+        options: MergeOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<Bytes, Error> {
+        self.merge_pdfs_with_response(files, options, webhook)
+            .await
+            .map(|output| output.bytes)
+    }
+
+    /// Same as [`Client::merge_pdfs`], but returns a [`RenderOutput`] with the output filename, content type and trace.
+    pub async fn merge_pdfs_with_response(
+        &self,
+        files: Vec<impl Into<NamedFile>>,
+        options: MergeOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        let request = GotenbergRequest::merge_pdfs(files, options)?;
+        self.post_output(request, webhook).await
+    }
+
+    /// Split a PDF file into several PDF files.
+    ///
+    /// The ZIP archive returned by the server is unpacked into a list of `(filename, bytes)` tuples.
+    /// If the server returns a single PDF (eg. when `split_unify` is set), it is returned as `file.pdf`.
+    pub async fn split_pdf(
+        &self,
+        pdf: impl Into<InputFile>,
+        options: SplitOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<Vec<(String, Bytes)>, Error> {
+        let output = self.split_pdf_with_response(pdf, options, webhook).await?;
+        archive::files_from_response(output.bytes, "file.pdf")
+    }
+
+    /// Same as [`Client::split_pdf`], but returns the raw [`RenderOutput`], see [`RenderOutput::files`] to unpack it.
+    pub async fn split_pdf_with_response(
+        &self,
+        pdf: impl Into<InputFile>,
+        options: SplitOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        let request = GotenbergRequest::split_pdf(pdf, options)?;
+        self.post_output(request, webhook).await
+    }
+
+    /// Encrypt a PDF file with a user password and an optional owner password.
+    ///
+    /// The user password is required to open the PDF, the owner password grants full access to it.
+    pub async fn encrypt_pdf(
+        &self,
+        pdf: impl Into<InputFile>,
+        user_password: impl Into<Secret>,
+        owner_password: Option<Secret>,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<Bytes, Error> {
+        self.encrypt_pdf_with_response(pdf, user_password, owner_password, webhook)
+            .await
+            .map(|output| output.bytes)
+    }
+
+    /// Same as [`Client::encrypt_pdf`], but returns a [`RenderOutput`] with the output filename, content type and trace.
+    pub async fn encrypt_pdf_with_response(
+        &self,
+        pdf: impl Into<InputFile>,
+        user_password: impl Into<Secret>,
+        owner_password: Option<Secret>,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        let request = GotenbergRequest::encrypt_pdf(pdf, user_password, owner_password);
+        self.post_output(request, webhook).await
+    }
+
+    /// Flatten a PDF file, merging its form fields and annotations into the page content so they can no longer be edited.
+    pub async fn flatten_pdf(
+        &self,
+        pdf: impl Into<InputFile>,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<Bytes, Error> {
+        self.flatten_pdf_with_response(pdf, webhook)
+            .await
+            .map(|output| output.bytes)
+    }
+
+    /// Same as [`Client::flatten_pdf`], but returns a [`RenderOutput`] with the output filename, content type and trace.
+    pub async fn flatten_pdf_with_response(
+        &self,
+        pdf: impl Into<InputFile>,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        self.post_output(GotenbergRequest::flatten_pdf(pdf), webhook)
+            .await
+    }
+
+    /// Embed files into a PDF as attachments, eg. the XML invoice of a ZUGFeRD / Factur-X document.
+    pub async fn embed_files(
+        &self,
+        pdf: impl Into<InputFile>,
+        attachments: Vec<impl Into<EmbeddedFile>>,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<Bytes, Error> {
+        self.embed_files_with_response(pdf, attachments, webhook)
             .await
+            .map(|output| output.bytes)
+    }
+
+    /// Same as [`Client::embed_files`], but returns a [`RenderOutput`] with the output filename, content type and trace.
+    pub async fn embed_files_with_response(
+        &self,
+        pdf: impl Into<InputFile>,
+        attachments: Vec<impl Into<EmbeddedFile>>,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        let request = GotenbergRequest::embed_files(pdf, attachments)?;
+        self.post_output(request, webhook).await
     }
 
     /// Read the metadata of a PDF file
     pub async fn read_metadata(
         &self,
-        pdf_bytes: Vec<u8>,
+        pdf: impl Into<InputFile>,
     ) -> Result<HashMap<String, serde_json::Value>, Error> {
-        let form = multipart::Form::new();
-        let part = multipart::Part::bytes(pdf_bytes).file_name("file.pdf".to_string());
-        let form = form.part("file.pdf", part);
-
-        #[derive(Debug, Deserialize)]
-        pub struct MeatadataContainer {
-            #[serde(rename = "file.pdf")]
-            pub filepdf: HashMap<String, serde_json::Value>,
-        }
-
         let bytes = self
-            .post("forms/pdfengines/metadata/read", form, None, None)
+            .post(GotenbergRequest::read_metadata(pdf), None)
             .await?;
-        let metadata: MeatadataContainer = serde_json::from_slice(&bytes).map_err(|e| {
-            Error::ParseError(
-                "Metadata".to_string(),
-                String::from_utf8_lossy(&bytes).to_string(),
-                e.to_string(),
-            )
-        })?;
-
-        Ok(metadata.filepdf)
+        parse_metadata(&bytes)
     }
 
     /// Write metadata to a PDF file
     pub async fn write_metadata(
         &self,
-        pdf_bytes: Vec<u8>,
+        pdf: impl Into<InputFile>,
         metadata: HashMap<String, serde_json::Value>,
         webhook: Option<&WebhookOptions>,
     ) -> Result<Bytes, Error> {
-        let form = multipart::Form::new();
-        let part = multipart::Part::bytes(pdf_bytes).file_name("file.pdf".to_string());
-        let form = form.part("file.pdf", part);
-        let metadata = serde_json::to_string(&metadata).map_err(|e| {
-            Error::ParseError("Metadata".to_string(), "".to_string(), e.to_string())
-        })?;
-        let part = multipart::Part::text(metadata);
-        let form = form.part("metadata", part);
-        self.post("forms/pdfengines/metadata/write", form, None, webhook)
+        self.write_metadata_with_response(pdf, metadata, webhook)
             .await
+            .map(|output| output.bytes)
+    }
+
+    /// Same as [`Client::write_metadata`], but returns a [`RenderOutput`] with the output filename, content type and trace.
+    pub async fn write_metadata_with_response(
+        &self,
+        pdf: impl Into<InputFile>,
+        metadata: HashMap<String, serde_json::Value>,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        let request = GotenbergRequest::write_metadata(pdf, metadata)?;
+        self.post_output(request, webhook).await
+    }
+
+    /// Send a GET request to `url` through the transport and return the body of the response.
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn get_text(&self, url: String) -> Result<String, Error> {
+        let body = self.transport.get(url).await?.bytes().await?;
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+
+    /// Send a GET request to `url` and return the body of the response.
+    #[cfg(target_arch = "wasm32")]
+    async fn get_text(&self, url: String) -> Result<String, Error> {
+        let response = self.client.get(&url).send().await.map_err(Into::into)?;
+        response.text().await.map_err(Into::into)
     }
 
     /// Get the health status of the Gotenberg server.
     pub async fn health_check(&self) -> Result<health::Health, Error> {
         let url = format!("{}/health", self.base_url);
-        let response = self.client.get(&url).send().await.map_err(Into::into)?;
-        let body = response.text().await.map_err(Into::into)?;
+        let body = self.get_text(url).await?;
         serde_json::from_str(&body)
             .map_err(|e| Error::ParseError("Health".to_string(), body, e.to_string()))
     }
@@ -434,11 +986,15 @@ impl Client {
     /// Get the version of the Gotenberg server.
     pub async fn version(&self) -> Result<String, Error> {
         let url = format!("{}/version", self.base_url);
-        let response = self.client.get(&url).send().await.map_err(Into::into)?;
-        let body = response.text().await.map_err(Into::into)?;
+        let body = self.get_text(url).await?;
         Ok(body)
     }
 
+    /// Get the version of the Gotenberg server, parsed into a [`GotenbergVersion`].
+    pub async fn version_typed(&self) -> Result<GotenbergVersion, Error> {
+        self.version().await?.parse()
+    }
+
     /// Get the metrics of the Gotenberg server in prometheus format.
     /// The results will not be parsed and are returned as a multi-line string.
     ///
@@ -450,8 +1006,123 @@ impl Client {
     /// - `{namespace}_libreoffice_restarts_count`	    Current number of LibreOffice restarts.
     pub async fn metrics(&self) -> Result<String, Error> {
         let url = format!("{}/prometheus/metrics", self.base_url);
-        let response = self.client.get(&url).send().await.map_err(Into::into)?;
-        let body = response.text().await.map_err(Into::into)?;
+        let body = self.get_text(url).await?;
         Ok(body)
     }
+
+    /// Get the metrics of the Gotenberg server, parsed into [`metrics::GotenbergMetrics`].
+    ///
+    /// The namespace set with `--prometheus-namespace` is detected from the metric names.
+    pub async fn metrics_typed(&self) -> Result<metrics::GotenbergMetrics, Error> {
+        let body = self.metrics().await?;
+        metrics::GotenbergMetrics::parse(&body)
+    }
+}
+
+/// Uploads streamed from files, readers and streams instead of in-memory bytes.
+#[cfg(all(feature = "stream", not(target_arch = "wasm32")))]
+impl Client {
+    /// Readers and streams can only be sent once, so requests uploading them are not retried.
+    fn for_upload(&self, upload: &FileUpload) -> std::borrow::Cow<'_, Self> {
+        if upload.is_repeatable() {
+            return std::borrow::Cow::Borrowed(self);
+        }
+
+        let mut client = self.clone();
+        client.retry_policy = None;
+        std::borrow::Cow::Owned(client)
+    }
+
+    /// Same as [`Client::pdf_from_doc`], but streams the document instead of loading it into memory.
+    ///
+    /// The filename of the upload must have an extension, as LibreOffice uses it to detect the format of the document.
+    pub async fn pdf_from_doc_upload(
+        &self,
+        upload: FileUpload,
+        options: DocumentOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<Bytes, Error> {
+        self.pdf_from_doc_upload_with_response(upload, options, webhook)
+            .await
+            .map(|output| output.bytes)
+    }
+
+    /// Same as [`Client::pdf_from_doc_upload`], but returns a [`RenderOutput`] with the output filename, content type and trace.
+    pub async fn pdf_from_doc_upload_with_response(
+        &self,
+        upload: FileUpload,
+        options: DocumentOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        upload.check_filename()?;
+        let request = GotenbergRequest::pdf_from_doc_fields(options)?;
+        self.for_upload(&upload)
+            .post_form(request, webhook, || upload.doc_file())
+            .await
+    }
+
+    /// Same as [`Client::convert_pdf`], but streams the PDF instead of loading it into memory.
+    pub async fn convert_pdf_upload(
+        &self,
+        upload: FileUpload,
+        pdfa: Option<PDFFormat>,
+        pdfua: bool,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<Bytes, Error> {
+        self.convert_pdf_upload_with_response(upload, pdfa, pdfua, webhook)
+            .await
+            .map(|output| output.bytes)
+    }
+
+    /// Same as [`Client::convert_pdf_upload`], but returns a [`RenderOutput`] with the output filename, content type and trace.
+    pub async fn convert_pdf_upload_with_response(
+        &self,
+        upload: FileUpload,
+        pdfa: Option<PDFFormat>,
+        pdfua: bool,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        let request = GotenbergRequest::convert_pdf_fields(pdfa, pdfua);
+        self.for_upload(&upload)
+            .post_form(request, webhook, || upload.pdf_file())
+            .await
+    }
+
+    /// Same as [`Client::read_metadata`], but streams the PDF instead of loading it into memory.
+    pub async fn read_metadata_upload(
+        &self,
+        upload: FileUpload,
+    ) -> Result<HashMap<String, serde_json::Value>, Error> {
+        let request = GotenbergRequest::new("forms/pdfengines/metadata/read");
+        let output = self
+            .for_upload(&upload)
+            .post_form(request, None, || upload.pdf_file())
+            .await?;
+        parse_metadata(&output.bytes)
+    }
+
+    /// Same as [`Client::write_metadata`], but streams the PDF instead of loading it into memory.
+    pub async fn write_metadata_upload(
+        &self,
+        upload: FileUpload,
+        metadata: HashMap<String, serde_json::Value>,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<Bytes, Error> {
+        self.write_metadata_upload_with_response(upload, metadata, webhook)
+            .await
+            .map(|output| output.bytes)
+    }
+
+    /// Same as [`Client::write_metadata_upload`], but returns a [`RenderOutput`] with the output filename, content type and trace.
+    pub async fn write_metadata_upload_with_response(
+        &self,
+        upload: FileUpload,
+        metadata: HashMap<String, serde_json::Value>,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        let request = GotenbergRequest::write_metadata_fields(metadata)?;
+        self.for_upload(&upload)
+            .post_form(request, webhook, || upload.pdf_file())
+            .await
+    }
 }
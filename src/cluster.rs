@@ -0,0 +1,252 @@
+use super::*;
+use health::{Health, HealthStatus};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// How a client created with [`Client::with_endpoints`] spreads requests over its Gotenberg instances.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Balancing {
+    /// Send each request to the next instance in turn.
+    #[default]
+    RoundRobin,
+    /// Send each request to the instance with the fewest requests in flight.
+    LeastInFlight,
+}
+
+/// A set of Gotenberg instances serving the same routes.
+#[derive(Debug)]
+pub(crate) struct Cluster {
+    nodes: Vec<Node>,
+    next: AtomicUsize,
+}
+
+/// A single Gotenberg instance and what is known about its health.
+///
+/// Every instance is assumed to be healthy until a health probe or a request says otherwise.
+#[derive(Debug)]
+struct Node {
+    base_url: String,
+    in_flight: AtomicUsize,
+    reachable: AtomicBool,
+    chromium_up: AtomicBool,
+    libreoffice_up: AtomicBool,
+}
+
+impl Node {
+    fn new(base_url: &str) -> Self {
+        Node {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            in_flight: AtomicUsize::new(0),
+            reachable: AtomicBool::new(true),
+            chromium_up: AtomicBool::new(true),
+            libreoffice_up: AtomicBool::new(true),
+        }
+    }
+
+    /// Check if the node can currently serve routes of the given engine.
+    ///
+    /// Gotenberg reports its overall status as down as soon as a single module is down, so only the status
+    /// of the module used by the route matters.
+    fn available(&self, engine: Option<Engine>) -> bool {
+        if !self.reachable.load(Ordering::Relaxed) {
+            return false;
+        }
+
+        match engine {
+            Some(Engine::Chromium) => self.chromium_up.load(Ordering::Relaxed),
+            Some(Engine::LibreOffice) => self.libreoffice_up.load(Ordering::Relaxed),
+            Some(Engine::PdfEngines) | None => true,
+        }
+    }
+}
+
+/// Counts a request as in flight on a node until dropped.
+pub(crate) struct InFlight<'a>(&'a AtomicUsize);
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl Cluster {
+    pub(crate) fn new(base_urls: &[String]) -> Self {
+        Cluster {
+            nodes: base_urls.iter().map(|url| Node::new(url)).collect(),
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    pub(crate) fn base_url(&self, index: usize) -> &str {
+        &self.nodes[index].base_url
+    }
+
+    /// The nodes to try for a request to a route of the given engine, best candidate first.
+    ///
+    /// Nodes that are currently unavailable are kept at the end of the list,
+    /// so a request is still attempted when every node looks unhealthy.
+    pub(crate) fn candidates(&self, engine: Option<Engine>, balancing: Balancing) -> Vec<usize> {
+        let count = self.nodes.len();
+        let start = self.next.fetch_add(1, Ordering::Relaxed) % count.max(1);
+        let mut candidates: Vec<usize> = (0..count).map(|i| (start + i) % count).collect();
+
+        if balancing == Balancing::LeastInFlight {
+            // Stable sort, so ties keep the round-robin order
+            candidates.sort_by_key(|&index| self.nodes[index].in_flight.load(Ordering::Relaxed));
+        }
+        candidates.sort_by_key(|&index| !self.nodes[index].available(engine));
+
+        candidates
+    }
+
+    /// Count a request as in flight on the node until the returned guard is dropped.
+    pub(crate) fn begin(&self, index: usize) -> InFlight<'_> {
+        let in_flight = &self.nodes[index].in_flight;
+        in_flight.fetch_add(1, Ordering::Relaxed);
+        InFlight(in_flight)
+    }
+
+    /// Skip the node until a health probe reaches it again.
+    pub(crate) fn mark_unreachable(&self, index: usize) {
+        self.nodes[index].reachable.store(false, Ordering::Relaxed);
+    }
+
+    /// Record the result of a health probe, `None` meaning the node could not be reached.
+    pub(crate) fn update(&self, index: usize, health: Option<&Health>) {
+        let node = &self.nodes[index];
+        node.reachable.store(health.is_some(), Ordering::Relaxed);
+
+        if let Some(health) = health {
+            let up = |status: &HealthStatus| matches!(status, HealthStatus::Up);
+            node.chromium_up
+                .store(up(&health.details.chromium.status), Ordering::Relaxed);
+            node.libreoffice_up
+                .store(up(&health.details.libreoffice.status), Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.nodes.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cluster(count: usize) -> Cluster {
+        let urls: Vec<String> = (0..count)
+            .map(|i| format!("http://gotenberg-{}:3000/", i))
+            .collect();
+        Cluster::new(&urls)
+    }
+
+    fn health(chromium: &str, libreoffice: &str) -> Health {
+        let status = if chromium == "up" && libreoffice == "up" {
+            "up"
+        } else {
+            "down"
+        };
+        serde_json::from_str(&format!(
+            r#"{{
+                "status": "{}",
+                "details": {{
+                    "chromium": {{ "status": "{}", "timestamp": "2024-01-01T00:00:00Z" }},
+                    "libreoffice": {{ "status": "{}", "timestamp": "2024-01-01T00:00:00Z" }}
+                }}
+            }}"#,
+            status, chromium, libreoffice
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_round_robin() {
+        let cluster = cluster(3);
+        assert_eq!(cluster.base_url(0), "http://gotenberg-0:3000");
+
+        let first: Vec<usize> = (0..3)
+            .map(|_| cluster.candidates(None, Balancing::RoundRobin)[0])
+            .collect();
+        assert_eq!(first, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_least_in_flight() {
+        let cluster = cluster(3);
+        let _a = cluster.begin(0);
+        let _b = cluster.begin(0);
+        let c = cluster.begin(1);
+
+        assert_eq!(cluster.candidates(None, Balancing::LeastInFlight)[0], 2);
+
+        drop(c);
+        let _d = cluster.begin(2);
+        assert_eq!(cluster.candidates(None, Balancing::LeastInFlight)[0], 1);
+    }
+
+    #[test]
+    fn test_health_filtering() {
+        let cluster = cluster(3);
+        cluster.update(0, Some(&health("down", "up")));
+        cluster.update(1, None);
+
+        // Chromium routes avoid the node with a Chromium outage and the unreachable node
+        for _ in 0..3 {
+            let candidates = cluster.candidates(Some(Engine::Chromium), Balancing::RoundRobin);
+            assert_eq!(candidates[0], 2);
+        }
+
+        // The overall status is down as well, but LibreOffice and PDF engines routes still use the node
+        let first: Vec<usize> = (0..3)
+            .map(|_| cluster.candidates(Some(Engine::LibreOffice), Balancing::RoundRobin)[0])
+            .collect();
+        assert!(first.contains(&0) && !first.contains(&1));
+        let first: Vec<usize> = (0..3)
+            .map(|_| cluster.candidates(Some(Engine::PdfEngines), Balancing::RoundRobin)[0])
+            .collect();
+        assert!(first.contains(&0) && !first.contains(&1));
+
+        // A LibreOffice outage only affects LibreOffice routes
+        cluster.update(2, Some(&health("up", "down")));
+        for _ in 0..3 {
+            let candidates = cluster.candidates(Some(Engine::LibreOffice), Balancing::RoundRobin);
+            assert_eq!(candidates[0], 0);
+            let candidates = cluster.candidates(Some(Engine::Chromium), Balancing::RoundRobin);
+            assert_eq!(candidates[0], 2);
+        }
+
+        // Nodes recover with the next successful probe
+        cluster.update(0, Some(&health("up", "up")));
+        cluster.update(1, Some(&health("up", "up")));
+        let first: Vec<usize> = (0..3)
+            .map(|_| cluster.candidates(Some(Engine::Chromium), Balancing::RoundRobin)[0])
+            .collect();
+        assert!(first.contains(&0) && first.contains(&1) && first.contains(&2));
+
+        // Unhealthy nodes are still tried as a last resort
+        cluster.mark_unreachable(0);
+        cluster.mark_unreachable(1);
+        cluster.mark_unreachable(2);
+        assert_eq!(
+            cluster.candidates(None, Balancing::RoundRobin).len(),
+            cluster.len()
+        );
+    }
+
+    #[test]
+    fn test_engine_from_endpoint() {
+        assert_eq!(
+            Engine::from_endpoint("forms/chromium/convert/url"),
+            Some(Engine::Chromium)
+        );
+        assert_eq!(
+            Engine::from_endpoint("forms/libreoffice/convert"),
+            Some(Engine::LibreOffice)
+        );
+        assert_eq!(
+            Engine::from_endpoint("forms/pdfengines/merge"),
+            Some(Engine::PdfEngines)
+        );
+        assert_eq!(Engine::from_endpoint("health"), None);
+    }
+}
@@ -0,0 +1,618 @@
+use super::*;
+#[cfg(not(target_arch = "wasm32"))]
+use std::ops::Range;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::{Path, PathBuf};
+
+/// A file referenced by an HTML document, such as an image, a stylesheet or a font.
+///
+/// Gotenberg stores every uploaded file next to `index.html`, so the HTML should reference assets by filename only,
+/// eg. `<img src="logo.png">`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Asset {
+    /// Filename the HTML refers to, eg. `logo.png`.
+    pub filename: String,
+
+    /// MIME type of the asset, eg. `image/png`.
+    pub mime_type: String,
+
+    /// File contents.
+    pub bytes: Vec<u8>,
+}
+
+impl Asset {
+    /// Create a new asset, guessing the MIME type from the filename extension.
+    pub fn new(filename: &str, bytes: Vec<u8>) -> Self {
+        Asset {
+            filename: filename.to_string(),
+            mime_type: mime_type_for(filename).to_string(),
+            bytes,
+        }
+    }
+
+    /// Create a new asset with an explicit MIME type.
+    pub fn with_mime_type(filename: &str, mime_type: &str, bytes: Vec<u8>) -> Self {
+        Asset {
+            filename: filename.to_string(),
+            mime_type: mime_type.to_string(),
+            bytes,
+        }
+    }
+
+    fn into_attachment(self) -> Attachment {
+        Attachment {
+            filename: self.filename,
+            mime_type: self.mime_type,
+            bytes: self.bytes,
+        }
+    }
+}
+
+/// An HTML document together with the assets it references.
+///
+/// Every method that accepts HTML takes an `impl Into<HtmlBundle>`, so a plain `&str` or `String` works as well.
+///
+/// # Example
+///
+/// ```
+/// use gotenberg_pdf::{Asset, HtmlBundle};
+///
+/// let bundle = HtmlBundle::new(r#"<html><body><img src="logo.png"></body></html>"#)
+///     .asset(Asset::new("logo.png", vec![0x89, b'P', b'N', b'G']));
+///
+/// assert_eq!(bundle.assets[0].mime_type, "image/png");
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HtmlBundle {
+    /// The HTML document, uploaded as `index.html`.
+    pub html: String,
+
+    /// Files referenced by the HTML document.
+    pub assets: Vec<Asset>,
+}
+
+impl HtmlBundle {
+    /// Create a new bundle from an HTML document, without any assets.
+    pub fn new(html: &str) -> Self {
+        HtmlBundle {
+            html: html.to_string(),
+            assets: Vec::new(),
+        }
+    }
+
+    /// Add an asset to the bundle, consuming the current bundle and returning the updated one.
+    pub fn asset(mut self, asset: Asset) -> Self {
+        self.assets.push(asset);
+        self
+    }
+
+    fn check_filenames(&self) -> Result<(), Error> {
+        for asset in &self.assets {
+            if asset.filename == "index.html" {
+                return Err(Error::FilenameError(
+                    "Asset filename cannot be 'index.html'".to_string(),
+                ));
+            }
+            if asset.filename.contains('/') || asset.filename.contains('\\') {
+                return Err(Error::FilenameError(format!(
+                    "Asset filename must not contain a path: {}",
+                    asset.filename
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Add `index.html` and the assets to the request.
+    pub(crate) fn fill_request(self, request: GotenbergRequest) -> Result<GotenbergRequest, Error> {
+        self.check_filenames()?;
+
+        let index = FormFile::new("index.html", "index.html", self.html).content_type("text/html");
+        let mut request = request.file(index);
+
+        for asset in self.assets {
+            let filename = asset.filename.clone();
+            request = request.file(asset.into_attachment().form_file(&filename)?);
+        }
+
+        Ok(request)
+    }
+}
+
+impl From<&str> for HtmlBundle {
+    fn from(html: &str) -> Self {
+        HtmlBundle::new(html)
+    }
+}
+
+impl From<String> for HtmlBundle {
+    fn from(html: String) -> Self {
+        HtmlBundle {
+            html,
+            assets: Vec::new(),
+        }
+    }
+}
+
+impl From<&String> for HtmlBundle {
+    fn from(html: &String) -> Self {
+        HtmlBundle::new(html)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl HtmlBundle {
+    /// Build a bundle from a directory containing an `index.html`.
+    ///
+    /// `index.html` is scanned for `src` attributes, stylesheet `<link href>` and CSS `url(...)` references, and every
+    /// stylesheet it pulls in is scanned for `url(...)` and `@import` references in turn. Each referenced local file is
+    /// attached as an asset and the reference is rewritten to the bare filename, as Gotenberg stores all files next to
+    /// `index.html`. Links to other pages, remote URLs, `data:` URIs and fragment links are left untouched.
+    /// References starting with `/` are resolved from the directory.
+    ///
+    /// A missing file, a file outside of the directory, or two different files sharing the same filename,
+    /// result in an [`Error::FilenameError`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() -> Result<(), gotenberg_pdf::Error> {
+    /// use gotenberg_pdf::{Client, HtmlBundle, WebOptions};
+    ///
+    /// let client = Client::new("http://localhost:3000");
+    /// let bundle = HtmlBundle::from_dir("templates/invoice")?;
+    /// let pdf_bytes = client.pdf_from_html(bundle, WebOptions::default(), None).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_dir(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let dir = path.as_ref();
+        let index = dir.join("index.html");
+        let html = std::fs::read_to_string(&index).map_err(|e| {
+            Error::FilenameError(format!("Failed to read {}: {}", index.display(), e))
+        })?;
+
+        let root = canonical_path(dir)?;
+        let mut bundler = Bundler {
+            assets: Vec::new(),
+            sources: HashMap::new(),
+            root: root.clone(),
+        };
+        bundler
+            .sources
+            .insert("index.html".to_string(), canonical_path(&index)?);
+        let html = bundler.rewrite(&html, &root, html_references)?;
+
+        Ok(HtmlBundle {
+            html,
+            assets: bundler.assets,
+        })
+    }
+}
+
+/// Collects the files referenced by a document, keeping track of where each filename came from.
+#[cfg(not(target_arch = "wasm32"))]
+struct Bundler {
+    assets: Vec<Asset>,
+    sources: HashMap<String, PathBuf>,
+    /// The canonical bundle directory, which every attached file must be in.
+    root: PathBuf,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Bundler {
+    /// Attach every local file referenced in `text` and return `text` with the references rewritten to filenames.
+    fn rewrite(
+        &mut self,
+        text: &str,
+        base_dir: &Path,
+        scan: fn(&str) -> Vec<Range<usize>>,
+    ) -> Result<String, Error> {
+        let mut rewritten = String::with_capacity(text.len());
+        let mut last = 0;
+
+        for range in scan(text) {
+            let reference = &text[range.clone()];
+            let Some((path, suffix)) = local_reference(reference) else {
+                continue;
+            };
+
+            // Root-relative references are resolved from the bundle directory, not from the referencing stylesheet
+            let base_dir = if reference.starts_with('/') {
+                self.root.clone()
+            } else {
+                base_dir.to_path_buf()
+            };
+            self.attach(&base_dir.join(percent_decode(path)))?;
+
+            // Keep the reference encoded the same way it was written
+            let encoded_filename = path.rsplit('/').next().unwrap_or(path);
+            rewritten.push_str(&text[last..range.start]);
+            rewritten.push_str(encoded_filename);
+            rewritten.push_str(suffix);
+            last = range.end;
+        }
+
+        rewritten.push_str(&text[last..]);
+        Ok(rewritten)
+    }
+
+    /// Attach the file at `path`, returning its filename within the bundle.
+    fn attach(&mut self, path: &Path) -> Result<String, Error> {
+        let source = canonical_path(path)?;
+        if !source.starts_with(&self.root) {
+            return Err(Error::FilenameError(format!(
+                "Referenced file is outside of the bundle directory: {}",
+                path.display()
+            )));
+        }
+
+        let filename = source
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| {
+                Error::FilenameError(format!("Invalid referenced filename: {}", path.display()))
+            })?
+            .to_string();
+
+        match self.sources.get(&filename) {
+            Some(existing) if *existing == source => return Ok(filename),
+            Some(existing) => {
+                return Err(Error::FilenameError(format!(
+                    "Referenced files {} and {} share the filename '{}'",
+                    existing.display(),
+                    source.display(),
+                    filename
+                )))
+            }
+            None => {}
+        }
+
+        // Register the file before scanning it, so stylesheets importing each other do not loop forever
+        self.sources.insert(filename.clone(), source.clone());
+
+        let bytes = std::fs::read(&source).map_err(|e| {
+            Error::FilenameError(format!("Failed to read {}: {}", source.display(), e))
+        })?;
+
+        let mut asset = Asset::new(&filename, bytes);
+        if asset.mime_type == "text/css" {
+            if let Ok(css) = std::str::from_utf8(&asset.bytes) {
+                let base_dir = source.parent().unwrap_or(Path::new(""));
+                asset.bytes = self.rewrite(css, base_dir, css_references)?.into_bytes();
+            }
+        }
+        self.assets.push(asset);
+
+        Ok(filename)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn canonical_path(path: &Path) -> Result<PathBuf, Error> {
+    path.canonicalize().map_err(|e| {
+        Error::FilenameError(format!(
+            "Referenced file not found: {} ({})",
+            path.display(),
+            e
+        ))
+    })
+}
+
+/// Split a reference into its local path and query / fragment suffix.
+///
+/// Returns `None` for references that do not point to a local file, such as remote URLs or `data:` URIs.
+#[cfg(not(target_arch = "wasm32"))]
+fn local_reference(reference: &str) -> Option<(&str, &str)> {
+    if reference.starts_with('#') || reference.starts_with("//") {
+        return None;
+    }
+
+    // Anything with a scheme (http:, data:, mailto:, ...) is not a local file
+    if let Some((scheme, _)) = reference.split_once(':') {
+        let mut chars = scheme.chars();
+        if chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+            && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        {
+            return None;
+        }
+    }
+
+    let end = reference.find(['?', '#']).unwrap_or(reference.len());
+    let (path, suffix) = reference.split_at(end);
+    let path = path.trim_start_matches('/');
+    if path.is_empty() {
+        None
+    } else {
+        Some((path, suffix))
+    }
+}
+
+/// Find the byte ranges of `src` attribute values, stylesheet `<link href>` values and CSS references in an HTML document.
+///
+/// Other `href` attributes link to other pages rather than to assets of the document, so they are skipped.
+#[cfg(not(target_arch = "wasm32"))]
+fn html_references(html: &str) -> Vec<Range<usize>> {
+    let lower = html.to_ascii_lowercase();
+    let bytes = lower.as_bytes();
+    let mut references = css_references(html);
+
+    for attribute in ["src", "href"] {
+        let mut start = 0;
+        while let Some(position) = lower[start..].find(attribute) {
+            let at = start + position;
+            start = at + attribute.len();
+            if at == 0 || !bytes[at - 1].is_ascii_whitespace() {
+                continue;
+            }
+            if attribute == "href" && !in_stylesheet_link(&lower, at) {
+                continue;
+            }
+            references.extend(attribute_value(html, start));
+        }
+    }
+
+    references.sort_by_key(|range| range.start);
+    references.dedup_by(|next, previous| next.start < previous.end);
+    references
+}
+
+/// Whether the attribute at `at` belongs to a `<link rel="stylesheet">` tag, `lower` being the lowercased document.
+#[cfg(not(target_arch = "wasm32"))]
+fn in_stylesheet_link(lower: &str, at: usize) -> bool {
+    let Some(open) = lower[..at].rfind('<') else {
+        return false;
+    };
+    if lower[open..at].contains('>') {
+        return false;
+    }
+    let end = lower[at..].find('>').map_or(lower.len(), |end| at + end);
+    let tag = &lower[open + 1..end];
+
+    let is_link = tag
+        .strip_prefix("link")
+        .is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_whitespace()));
+    is_link
+        && tag.find("rel").is_some_and(|rel| {
+            attribute_value(tag, rel + 3)
+                .is_some_and(|value| tag[value].split_whitespace().any(|v| v == "stylesheet"))
+        })
+}
+
+/// Find the byte ranges of `url(...)` and `@import "..."` references in a stylesheet.
+#[cfg(not(target_arch = "wasm32"))]
+fn css_references(css: &str) -> Vec<Range<usize>> {
+    let lower = css.to_ascii_lowercase();
+    let bytes = css.as_bytes();
+    let mut references = Vec::new();
+
+    let mut start = 0;
+    while let Some(position) = lower[start..].find("url(") {
+        let at = skip_whitespace(bytes, start + position + 4);
+        start = at;
+        let range = match bytes.get(at) {
+            Some(b'"' | b'\'') => quoted_value(bytes, at),
+            Some(_) => bytes[at..].iter().position(|&b| b == b')').map(|length| {
+                let value = css[at..at + length].trim_end();
+                at..at + value.len()
+            }),
+            None => None,
+        };
+        references.extend(range.filter(|range| !range.is_empty()));
+    }
+
+    let mut start = 0;
+    while let Some(position) = lower[start..].find("@import") {
+        let at = skip_whitespace(bytes, start + position + 7);
+        start = at;
+        references.extend(quoted_value(bytes, at).filter(|range| !range.is_empty()));
+    }
+
+    references.sort_by_key(|range| range.start);
+    references
+}
+
+/// Parse `= value` starting at `at`, with the value optionally quoted.
+#[cfg(not(target_arch = "wasm32"))]
+fn attribute_value(html: &str, at: usize) -> Option<Range<usize>> {
+    let bytes = html.as_bytes();
+    let at = skip_whitespace(bytes, at);
+    if bytes.get(at) != Some(&b'=') {
+        return None;
+    }
+
+    let at = skip_whitespace(bytes, at + 1);
+    let range = match bytes.get(at)? {
+        b'"' | b'\'' => quoted_value(bytes, at)?,
+        _ => {
+            let length = bytes[at..]
+                .iter()
+                .position(|&b| b.is_ascii_whitespace() || b == b'>')
+                .unwrap_or(bytes.len() - at);
+            at..at + length
+        }
+    };
+    (!range.is_empty()).then_some(range)
+}
+
+/// Range of the value between the quote at `at` and its closing quote.
+#[cfg(not(target_arch = "wasm32"))]
+fn quoted_value(bytes: &[u8], at: usize) -> Option<Range<usize>> {
+    let quote = *bytes.get(at)?;
+    if quote != b'"' && quote != b'\'' {
+        return None;
+    }
+    let length = bytes[at + 1..].iter().position(|&b| b == quote)?;
+    Some(at + 1..at + 1 + length)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn skip_whitespace(bytes: &[u8], mut at: usize) -> usize {
+    while bytes.get(at).is_some_and(|b| b.is_ascii_whitespace()) {
+        at += 1;
+    }
+    at
+}
+
+/// Guess the MIME type of a file from its extension, falling back to `application/octet-stream`.
+fn mime_type_for(filename: &str) -> &'static str {
+    let extension = filename
+        .rsplit_once('.')
+        .map(|(_, extension)| extension.to_lowercase())
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "js" | "mjs" => "text/javascript",
+        "json" => "application/json",
+        "md" => "text/markdown",
+        "txt" => "text/plain",
+        "xml" => "text/xml",
+        "csv" => "text/csv",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        "avif" => "image/avif",
+        "bmp" => "image/bmp",
+        "ico" => "image/x-icon",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        "eot" => "application/vnd.ms-fontobject",
+        "pdf" => "application/pdf",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mime_type_for() {
+        assert_eq!(mime_type_for("logo.PNG"), "image/png");
+        assert_eq!(mime_type_for("style.css"), "text/css");
+        assert_eq!(mime_type_for("font.woff2"), "font/woff2");
+        assert_eq!(mime_type_for("no_extension"), "application/octet-stream");
+    }
+
+    #[test]
+    fn test_check_filenames() {
+        assert!(HtmlBundle::new("<html></html>")
+            .asset(Asset::new("logo.png", vec![]))
+            .check_filenames()
+            .is_ok());
+
+        assert!(matches!(
+            HtmlBundle::new("<html></html>")
+                .asset(Asset::new("index.html", vec![]))
+                .check_filenames(),
+            Err(Error::FilenameError(_))
+        ));
+
+        assert!(matches!(
+            HtmlBundle::new("<html></html>")
+                .asset(Asset::new("img/logo.png", vec![]))
+                .check_filenames(),
+            Err(Error::FilenameError(_))
+        ));
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_references() {
+        let html = r#"<link rel="stylesheet" href="a.css"><img src = 'b.png' alt="x"><a href=c.html>c</a><div style="background: url( d.png )"></div><p data-src="no.png"></p><link href="next.html" rel=next><LINK HREF=e.css REL=STYLESHEET>"#;
+        let found: Vec<_> = html_references(html)
+            .into_iter()
+            .map(|range| &html[range])
+            .collect();
+        assert_eq!(found, vec!["a.css", "b.png", "d.png", "e.css"]);
+
+        let css = r#"@import "print.css"; body { background: url(../img/bg.png) } @font-face { src: url('f.woff2') }"#;
+        let found: Vec<_> = css_references(css)
+            .into_iter()
+            .map(|range| &css[range])
+            .collect();
+        assert_eq!(found, vec!["print.css", "../img/bg.png", "f.woff2"]);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_local_reference() {
+        assert_eq!(local_reference("img/logo.png"), Some(("img/logo.png", "")));
+        assert_eq!(
+            local_reference("/icons.svg#home"),
+            Some(("icons.svg", "#home"))
+        );
+        assert_eq!(local_reference("https://example.com/logo.png"), None);
+        assert_eq!(local_reference("//example.com/logo.png"), None);
+        assert_eq!(local_reference("data:image/png;base64,AAAA"), None);
+        assert_eq!(local_reference("#top"), None);
+        assert_eq!(percent_decode("my%20logo.png"), "my logo.png");
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_from_dir() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("test_files");
+        let bundle = HtmlBundle::from_dir(dir.join("bundle")).unwrap();
+
+        assert!(bundle.html.contains(r#"href="style.css""#));
+        assert!(bundle.html.contains(r#"src="logo.svg""#));
+        assert!(bundle.html.contains("url('background.svg')"));
+        assert!(bundle.html.contains(r#"href="https://gotenberg.dev""#));
+
+        let mut filenames: Vec<_> = bundle.assets.iter().map(|a| a.filename.as_str()).collect();
+        filenames.sort();
+        assert_eq!(
+            filenames,
+            vec![
+                "background.svg",
+                "example.woff2",
+                "logo.svg",
+                "print.css",
+                "style.css"
+            ]
+        );
+
+        let style = bundle
+            .assets
+            .iter()
+            .find(|a| a.filename == "style.css")
+            .unwrap();
+        let style = std::str::from_utf8(&style.bytes).unwrap();
+        assert!(style.contains(r#"@import "print.css";"#));
+        assert!(style.contains(r#"url("example.woff2")"#));
+
+        assert!(matches!(
+            HtmlBundle::from_dir(dir.join("bundle_missing")),
+            Err(Error::FilenameError(_))
+        ));
+        assert!(matches!(
+            HtmlBundle::from_dir(dir.join("does_not_exist")),
+            Err(Error::FilenameError(_))
+        ));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_from_dir_outside() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("test_files");
+
+        // The referenced file exists, but is outside of the bundle directory
+        let error = HtmlBundle::from_dir(dir.join("bundle_traversal")).unwrap_err();
+        assert!(
+            matches!(&error, Error::FilenameError(message) if message.contains("outside of the bundle directory")),
+            "{error}"
+        );
+
+        // Root-relative references resolve from the bundle directory
+        let bundle = HtmlBundle::from_dir(dir.join("bundle_rooted")).unwrap();
+        assert!(bundle.html.contains(r#"src="logo.svg""#));
+        assert_eq!(bundle.assets.len(), 1);
+    }
+}
@@ -0,0 +1,151 @@
+use crate::transport::{
+    BodyStream, StreamedFile, Transport, TransportFuture, TransportRequest, TransportResponse,
+};
+use crate::{Bytes, Error, MultipartBody};
+use futures_util::stream::{self, StreamExt, TryStreamExt};
+use http_body_util::combinators::UnsyncBoxBody;
+use http_body_util::{BodyDataStream, BodyExt, StreamBody};
+use hyper::body::Frame;
+use hyper::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use hyper_util::client::legacy::connect::{Connect, HttpConnector};
+use hyper_util::client::legacy::Client as HyperClient;
+use hyper_util::rt::TokioExecutor;
+use std::fmt::{self, Debug};
+
+/// The request body of a [`HyperTransport`], to build a client with another connector, eg. for HTTPS.
+pub type HyperBody = UnsyncBoxBody<Bytes, Error>;
+
+/// A [`Transport`] sending requests with a hyper client. Available when the `hyper` feature is enabled.
+///
+/// Streamed uploads are sent without loading them into memory.
+///
+/// # Example
+///
+/// ```no_run
+/// use gotenberg_pdf::transport::HyperTransport;
+/// use gotenberg_pdf::{Client, WebOptions};
+///
+/// # async fn example() -> Result<(), gotenberg_pdf::Error> {
+/// let client = Client::new("http://localhost:3000").transport(HyperTransport::new());
+/// let pdf = client
+///     .pdf_from_url("https://example.com", WebOptions::default(), None)
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct HyperTransport<C = HttpConnector> {
+    client: HyperClient<C, HyperBody>,
+}
+
+impl HyperTransport {
+    /// Send requests over plain HTTP with a new client.
+    pub fn new() -> Self {
+        HyperTransport {
+            client: HyperClient::builder(TokioExecutor::new()).build_http(),
+        }
+    }
+}
+
+impl Default for HyperTransport {
+    fn default() -> Self {
+        HyperTransport::new()
+    }
+}
+
+impl<C> HyperTransport<C> {
+    /// Send requests with the given client, eg. to use HTTPS or share its connection pool.
+    pub fn with_client(client: HyperClient<C, HyperBody>) -> Self {
+        HyperTransport { client }
+    }
+}
+
+impl<C> Debug for HyperTransport<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HyperTransport").finish_non_exhaustive()
+    }
+}
+
+impl<C> Transport for HyperTransport<C>
+where
+    C: Connect + Clone + Send + Sync + 'static,
+{
+    fn send(&self, request: TransportRequest) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let multipart = request.request.multipart_body();
+            let content_type = multipart.content_type();
+            let (body, length) = body_stream(multipart, request.uploads);
+
+            let mut builder = hyper::Request::post(&request.url).header(CONTENT_TYPE, content_type);
+            if let Some(length) = length {
+                builder = builder.header(CONTENT_LENGTH, length);
+            }
+            for (name, value) in &request.request.headers {
+                builder = builder.header(name, value);
+            }
+            let body = StreamBody::new(body.map_ok(Frame::data)).boxed_unsync();
+            let req = builder.body(body).map_err(transport_error)?;
+
+            self.request(req).await
+        })
+    }
+
+    fn get(&self, url: String) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let body = http_body_util::Empty::new()
+                .map_err(|never| match never {})
+                .boxed_unsync();
+            let req = hyper::Request::get(&url)
+                .body(body)
+                .map_err(transport_error)?;
+            self.request(req).await
+        })
+    }
+}
+
+impl<C> HyperTransport<C>
+where
+    C: Connect + Clone + Send + Sync + 'static,
+{
+    /// Send the request and stream the body of the response.
+    async fn request(&self, req: hyper::Request<HyperBody>) -> Result<TransportResponse, Error> {
+        let response = self.client.request(req).await.map_err(transport_error)?;
+        let (parts, body) = response.into_parts();
+        let body = BodyDataStream::new(body).map_err(transport_error);
+        Ok(TransportResponse {
+            status: parts.status.as_u16(),
+            headers: parts.headers,
+            body: Box::pin(body),
+        })
+    }
+}
+
+/// Stream the parts of the request followed by the uploads. The length is only known if every upload has one.
+fn body_stream(multipart: MultipartBody, uploads: Vec<StreamedFile>) -> (BodyStream, Option<u64>) {
+    let mut length = Some(multipart.content_length());
+    let mut parts: Vec<BodyStream> = Vec::new();
+
+    let mut headers = Vec::new();
+    for upload in &uploads {
+        headers.push(multipart.file_header(&upload.name, &upload.filename, None));
+    }
+    let (chunks, closing) = multipart.into_chunks();
+    parts.push(Box::pin(stream::iter(chunks.into_iter().map(Ok))));
+
+    for (header, upload) in headers.into_iter().zip(uploads) {
+        length = match (length, upload.length) {
+            (Some(length), Some(upload)) => Some(length + header.len() as u64 + upload + 2),
+            _ => None,
+        };
+        parts.push(Box::pin(stream::iter([Ok(header)])));
+        parts.push(upload.stream);
+        parts.push(Box::pin(stream::iter([Ok(Bytes::from_static(b"\r\n"))])));
+    }
+    parts.push(Box::pin(stream::iter([Ok(closing)])));
+
+    (Box::pin(stream::iter(parts).flatten()), length)
+}
+
+fn transport_error(error: impl std::error::Error + Send + Sync + 'static) -> Error {
+    Error::TransportError(Box::new(error))
+}
@@ -0,0 +1,270 @@
+use super::*;
+
+/// A file to convert or process, either uploaded with the request or downloaded by the Gotenberg server.
+///
+/// Every method that accepts a single PDF takes an `impl Into<InputFile>`, so plain bytes work as well.
+/// Methods that accept several files take [`NamedFile`] or [`EmbeddedFile`] items instead.
+///
+/// # Example
+///
+/// ```no_run
+/// use gotenberg_pdf::{Client, DownloadFrom};
+///
+/// # async fn example() -> Result<(), gotenberg_pdf::Error> {
+/// let client = Client::new("http://localhost:3000");
+///
+/// // Uploaded with the request
+/// let pdf = client.flatten_pdf(std::fs::read("form.pdf").unwrap(), None).await?;
+///
+/// // Downloaded by the server, eg. from a presigned S3 link
+/// let download = DownloadFrom::new("https://bucket.s3.amazonaws.com/form.pdf?X-Amz-Signature=...");
+/// let pdf = client.flatten_pdf(download, None).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputFile {
+    /// The contents of the file, uploaded with the request.
+    Bytes(Bytes),
+
+    /// A URL the server downloads the file from, sent as the `downloadFrom` form field.
+    DownloadFrom(DownloadFrom),
+}
+
+/// A file the Gotenberg server downloads itself instead of it being uploaded with the request.
+///
+/// The server names the file after the `Content-Disposition` header of the response, or after the URL.
+/// Requires Gotenberg 8.10.0 or later, and can be disabled on the server with `--api-disable-download-from`.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadFrom {
+    /// The URL to download the file from.
+    pub url: String,
+
+    /// Extra HTTP headers sent by the server when downloading the file, eg. `Authorization`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extra_http_headers: Option<HashMap<String, String>>,
+}
+
+impl DownloadFrom {
+    /// Download the file at the given URL.
+    pub fn new(url: &str) -> Self {
+        DownloadFrom {
+            url: url.to_string(),
+            extra_http_headers: None,
+        }
+    }
+
+    /// Add an HTTP header sent by the server when downloading the file.
+    pub fn header(self, name: &str, value: &str) -> Self {
+        let mut download = self;
+        download
+            .extra_http_headers
+            .get_or_insert_with(HashMap::new)
+            .insert(name.to_string(), value.to_string());
+
+        download
+    }
+}
+
+impl InputFile {
+    /// Add the file to the request, as a file with the given name and filename, or as the `downloadFrom` field.
+    pub(crate) fn fill_request(
+        self,
+        request: GotenbergRequest,
+        name: &str,
+        filename: &str,
+    ) -> GotenbergRequest {
+        match self {
+            InputFile::Bytes(bytes) => request.file(FormFile::new(name, filename, bytes)),
+            InputFile::DownloadFrom(download) => add_download(request, download, false),
+        }
+    }
+}
+
+impl From<Vec<u8>> for InputFile {
+    fn from(bytes: Vec<u8>) -> Self {
+        InputFile::Bytes(bytes.into())
+    }
+}
+
+impl From<Bytes> for InputFile {
+    fn from(bytes: Bytes) -> Self {
+        InputFile::Bytes(bytes)
+    }
+}
+
+impl From<&[u8]> for InputFile {
+    fn from(bytes: &[u8]) -> Self {
+        InputFile::Bytes(Bytes::copy_from_slice(bytes))
+    }
+}
+
+impl From<DownloadFrom> for InputFile {
+    fn from(download: DownloadFrom) -> Self {
+        InputFile::DownloadFrom(download)
+    }
+}
+
+/// An entry of the `downloadFrom` field, marked when the file is to be embedded rather than processed.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DownloadEntry {
+    #[serde(flatten)]
+    download: DownloadFrom,
+
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    embedded: bool,
+}
+
+/// Add a file downloaded by the server to the `downloadFrom` field of the request.
+///
+/// The server reads a single `downloadFrom` field, so the downloads of a request are kept in one JSON array.
+pub(crate) fn add_download(
+    request: GotenbergRequest,
+    download: DownloadFrom,
+    embedded: bool,
+) -> GotenbergRequest {
+    let mut request = request.require(Some(Feature::DownloadFrom));
+    let entry = DownloadEntry { download, embedded };
+    match request
+        .fields
+        .iter_mut()
+        .find(|(name, _)| name == "downloadFrom")
+    {
+        Some((_, value)) => {
+            // The field is only ever written here, so it always holds a valid array
+            let mut entries: Vec<DownloadEntry> = serde_json::from_str(value).unwrap();
+            entries.push(entry);
+            *value = serde_json::to_string(&entries).unwrap();
+            request
+        }
+        None => request.text("downloadFrom", serde_json::to_string(&[entry]).unwrap()),
+    }
+}
+
+/// One of several files to convert or merge, either uploaded with its filename or downloaded by the Gotenberg server.
+///
+/// Tuples of a filename and bytes convert into uploaded files, eg. `("report.docx", bytes)`.
+/// A downloaded file is named by the server, after the `Content-Disposition` header of the response or after the URL.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NamedFile {
+    /// The contents of the file, uploaded with the request under the given filename.
+    Bytes { filename: String, bytes: Bytes },
+
+    /// A URL the server downloads the file from, sent in the `downloadFrom` form field.
+    DownloadFrom(DownloadFrom),
+}
+
+impl NamedFile {
+    /// The filename of an uploaded file, `None` for a file downloaded by the server.
+    pub fn filename(&self) -> Option<&str> {
+        match self {
+            NamedFile::Bytes { filename, .. } => Some(filename),
+            NamedFile::DownloadFrom(_) => None,
+        }
+    }
+}
+
+impl<F: Into<String>> From<(F, Vec<u8>)> for NamedFile {
+    fn from((filename, bytes): (F, Vec<u8>)) -> Self {
+        NamedFile::Bytes {
+            filename: filename.into(),
+            bytes: bytes.into(),
+        }
+    }
+}
+
+impl<F: Into<String>> From<(F, Bytes)> for NamedFile {
+    fn from((filename, bytes): (F, Bytes)) -> Self {
+        NamedFile::Bytes {
+            filename: filename.into(),
+            bytes,
+        }
+    }
+}
+
+impl From<DownloadFrom> for NamedFile {
+    fn from(download: DownloadFrom) -> Self {
+        NamedFile::DownloadFrom(download)
+    }
+}
+
+/// A file to embed into a PDF, either uploaded as an [`Attachment`] or downloaded by the Gotenberg server.
+#[derive(Debug, Clone)]
+pub enum EmbeddedFile {
+    /// A file uploaded with the request.
+    Attachment(Attachment),
+
+    /// A URL the server downloads the file from, sent in the `downloadFrom` form field and marked as embedded.
+    DownloadFrom(DownloadFrom),
+}
+
+impl EmbeddedFile {
+    /// Add the file to the request, as an `embeds` file or in the `downloadFrom` field.
+    pub(crate) fn fill_request(self, request: GotenbergRequest) -> Result<GotenbergRequest, Error> {
+        match self {
+            EmbeddedFile::Attachment(attachment) => {
+                Ok(request.file(attachment.form_file("embeds")?))
+            }
+            EmbeddedFile::DownloadFrom(download) => Ok(add_download(request, download, true)),
+        }
+    }
+}
+
+impl From<Attachment> for EmbeddedFile {
+    fn from(attachment: Attachment) -> Self {
+        EmbeddedFile::Attachment(attachment)
+    }
+}
+
+impl From<DownloadFrom> for EmbeddedFile {
+    fn from(download: DownloadFrom) -> Self {
+        EmbeddedFile::DownloadFrom(download)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_download_from() {
+        let request = GotenbergRequest::new("forms/pdfengines/flatten");
+        let download = DownloadFrom::new("https://example.com/file.pdf");
+        let request =
+            InputFile::from(download.clone()).fill_request(request, "file.pdf", "file.pdf");
+        assert_eq!(
+            request.fields,
+            vec![(
+                "downloadFrom".to_string(),
+                r#"[{"url":"https://example.com/file.pdf"}]"#.to_string()
+            )]
+        );
+        assert!(request.features.contains(&Feature::DownloadFrom));
+
+        // Every download of a request goes into the same field
+        let download = download.header("Authorization", "Bearer token");
+        let request = EmbeddedFile::from(download).fill_request(request).unwrap();
+        assert_eq!(
+            request.fields[0].1,
+            concat!(
+                r#"[{"url":"https://example.com/file.pdf"},"#,
+                r#"{"url":"https://example.com/file.pdf","extraHttpHeaders":{"Authorization":"Bearer token"},"embedded":true}]"#
+            )
+        );
+
+        let request = GotenbergRequest::new("forms/pdfengines/flatten");
+        let request =
+            InputFile::from(b"%PDF".to_vec()).fill_request(request, "file.pdf", "file.pdf");
+        assert!(request.features.is_empty());
+    }
+
+    #[test]
+    fn test_named_file() {
+        let file = NamedFile::from(("report.docx", b"PK".to_vec()));
+        assert_eq!(file.filename(), Some("report.docx"));
+        let download = NamedFile::from(DownloadFrom::new("https://example.com/report.docx"));
+        assert_eq!(download.filename(), None);
+    }
+}
@@ -3,38 +3,94 @@
 
 /// Gotenberg server health status. See [`Client::health_check`].
 pub mod health;
+pub mod metrics;
+pub mod webhook;
+
+/// Unpacking of the ZIP archives returned by routes that produce several files.
+pub mod archive;
 
 mod client;
+#[cfg(not(target_arch = "wasm32"))]
+mod cluster;
+mod html_bundle;
+mod input_file;
+#[cfg(not(target_arch = "wasm32"))]
+mod limiter;
 mod page_range;
 mod paper_format;
+mod render_output;
+mod request;
+mod retry;
+mod secret;
+mod version;
 
 #[cfg(feature = "stream")]
 mod streaming_client;
 
-#[cfg(feature = "blocking")]
+#[cfg(not(target_arch = "wasm32"))]
+pub mod transport;
+
+#[cfg(all(feature = "mock", not(target_arch = "wasm32")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "mock")))]
+pub mod mock;
+
+#[cfg(all(feature = "hyper", not(target_arch = "wasm32")))]
+mod hyper_transport;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod mock_transport;
+
+#[cfg(all(feature = "ureq", not(target_arch = "wasm32")))]
+mod ureq_transport;
+
+#[cfg(any(feature = "blocking", feature = "ureq"))]
 mod blocking_client;
 
+#[cfg(any(feature = "stream", feature = "blocking", feature = "ureq"))]
+mod sink;
+
+#[cfg(all(feature = "stream", not(target_arch = "wasm32")))]
+mod upload;
+
 #[cfg(feature = "stream")]
 #[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
 pub use crate::streaming_client::StreamingClient;
 
-#[cfg(feature = "blocking")]
-#[cfg_attr(docsrs, doc(cfg(feature = "blocking")))]
+#[cfg(any(feature = "blocking", feature = "ureq"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "blocking", feature = "ureq"))))]
 pub use crate::blocking_client::BlockingClient;
 
+#[cfg(all(feature = "stream", not(target_arch = "wasm32")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+pub use crate::upload::FileUpload;
+
 pub use crate::paper_format::*;
 use crate::WebhookMethods::POST;
 /// Re-exported from the `bytes` crate (See [`bytes::Bytes`]).
 pub use bytes::Bytes;
 pub use client::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub use cluster::Balancing;
+pub use html_bundle::{Asset, HtmlBundle};
+pub use input_file::{DownloadFrom, EmbeddedFile, InputFile, NamedFile};
+#[cfg(not(target_arch = "wasm32"))]
+pub use limiter::{ConcurrencyLimits, LimiterStats};
 pub use page_range::*;
-use reqwest::header::HeaderMap;
+#[cfg(feature = "stream")]
+#[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+pub use render_output::StreamingOutput;
+pub use render_output::{OutputKind, RenderOutput};
+pub use request::{FormFile, GotenbergRequest, MultipartBody};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use reqwest::multipart;
 use reqwest::Error as ReqwestError;
+pub use retry::RetryPolicy;
+pub use secret::Secret;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt::{self, Debug};
 use std::str::FromStr;
+pub use version::{Feature, GotenbergVersion};
 
 #[cfg(all(test, not(target_arch = "wasm32")))]
 mod tests;
@@ -42,7 +98,11 @@ mod tests;
 #[cfg(all(test, feature = "stream", not(target_arch = "wasm32")))]
 mod streaming_tests;
 
-#[cfg(all(test, feature = "blocking", not(target_arch = "wasm32")))]
+#[cfg(all(
+    test,
+    any(feature = "blocking", feature = "ureq"),
+    not(target_arch = "wasm32")
+))]
 mod blocking_tests;
 
 #[cfg(all(test, target_arch = "wasm32"))]
@@ -60,12 +120,24 @@ pub enum Error {
     /// Error communicating with the gotenberg server.
     CommunicationError(ReqwestError),
 
-    /// PDF rendering error.
-    RenderingError(String),
+    /// The Gotenberg server rejected the request or failed to render the result.
+    RenderingError(ApiError),
 
     /// Error parsing a string into a type
     // (Type, Subject, Message)
     ParseError(String, String, String),
+
+    /// The request uses a feature the Gotenberg server does not support, see [`Client::server_version`].
+    UnsupportedFeature(Feature, GotenbergVersion),
+
+    /// The webhook options are invalid.
+    WebhookError(WebhookError),
+
+    /// Error reading a file to upload.
+    IoError(std::io::Error),
+
+    /// Error communicating with the gotenberg server through a [`transport::Transport`] other than reqwest.
+    TransportError(Box<dyn std::error::Error + Send + Sync>),
 }
 
 impl Into<Error> for ReqwestError {
@@ -89,6 +161,20 @@ impl fmt::Display for Error {
             Error::ParseError(t, s, e) => {
                 write!(f, "gotenberg_pdf: Error Parsing {} from `{}`: {}", t, s, e)
             }
+            Error::UnsupportedFeature(feature, version) => write!(
+                f,
+                "gotenberg_pdf: {} requires Gotenberg {} or later, but the server runs {}",
+                feature,
+                feature.since(),
+                version
+            ),
+            Error::WebhookError(e) => write!(f, "gotenberg_pdf: Invalid webhook options: {}", e),
+            Error::IoError(e) => write!(f, "gotenberg_pdf: IO Error: {}", e),
+            Error::TransportError(e) => write!(
+                f,
+                "gotenberg_pdf: Error communicating with the gotenberg server: {}",
+                e
+            ),
         }
     }
 }
@@ -97,11 +183,87 @@ impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             Error::CommunicationError(e) => Some(e),
+            Error::IoError(e) => Some(e),
+            Error::TransportError(e) => Some(e.as_ref()),
             _ => None,
         }
     }
 }
 
+/// The kind of failure reported by the Gotenberg server, based on the HTTP status code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// 400: the form data is invalid, eg. a missing file or an out of range option.
+    InvalidFormData,
+    /// 401: basic auth is enabled on the server and the credentials are missing or wrong.
+    Unauthorized,
+    /// 403: the URL to convert or download is not allowed by the server's allow / deny lists.
+    Forbidden,
+    /// 409: Chromium received an unacceptable HTTP status code from the main page.
+    Conflict,
+    /// 413: the request is larger than the server accepts.
+    PayloadTooLarge,
+    /// 503: the conversion timed out or the server's queue is full.
+    Unavailable,
+    /// Any other status code.
+    Other,
+}
+
+impl ErrorKind {
+    /// Map an HTTP status code to an error kind.
+    pub fn from_status(status: u16) -> Self {
+        match status {
+            400 => ErrorKind::InvalidFormData,
+            401 => ErrorKind::Unauthorized,
+            403 => ErrorKind::Forbidden,
+            409 => ErrorKind::Conflict,
+            413 => ErrorKind::PayloadTooLarge,
+            503 => ErrorKind::Unavailable,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+/// An unsuccessful response from the Gotenberg server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiError {
+    /// The kind of failure, derived from `status`.
+    pub kind: ErrorKind,
+
+    /// The HTTP status code.
+    pub status: u16,
+
+    /// The response body, usually a short explanation of the failure.
+    pub message: String,
+
+    /// The trace of the request, echoed back in the `Gotenberg-Trace` header.
+    pub trace: Option<String>,
+}
+
+impl ApiError {
+    pub(crate) fn new(status: u16, message: String, headers: &HeaderMap) -> Self {
+        ApiError {
+            kind: ErrorKind::from_status(status),
+            status,
+            message,
+            trace: headers
+                .get("Gotenberg-Trace")
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} - {}", self.status, self.message)?;
+        if let Some(trace) = &self.trace {
+            write!(f, " (trace: {})", trace)?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum WebhookMethods {
     POST,
@@ -137,43 +299,150 @@ pub struct WebhookOptions {
 }
 
 impl WebhookOptions {
-    pub fn to_headers(&self) -> HeaderMap {
+    /// Build the `Gotenberg-Webhook-*` request headers.
+    ///
+    /// Fails if `url` or `error_url` is not an absolute http(s) URL,
+    /// or if one of the extra HTTP headers cannot be sent as a header.
+    pub fn to_headers(&self) -> Result<HeaderMap, Error> {
         let mut header_map = HeaderMap::new();
-        if let Some(extra_headers) = self.extra_http_headers.clone() {
-            let extra_headers_json = serde_json::to_string(&extra_headers).unwrap();
-            header_map.insert(
-                "GOTENBERG-WEBHOOK-EXTRA-HTTP-HEADERS",
-                extra_headers_json.parse().unwrap(),
-            );
+        if let Some(extra_headers) = &self.extra_http_headers {
+            for (name, value) in extra_headers {
+                if HeaderName::from_bytes(name.as_bytes()).is_err()
+                    || HeaderValue::from_str(value).is_err()
+                {
+                    return Err(Error::WebhookError(WebhookError::InvalidHeader(
+                        name.clone(),
+                    )));
+                }
+            }
+
+            let extra_headers_json = serde_json::to_string(extra_headers).map_err(|e| {
+                Error::ParseError("Webhook headers".to_string(), "".to_string(), e.to_string())
+            })?;
+            let value = HeaderValue::from_str(&extra_headers_json).map_err(|_| {
+                Error::WebhookError(WebhookError::InvalidHeader(
+                    "Gotenberg-Webhook-Extra-Http-Headers".to_string(),
+                ))
+            })?;
+            header_map.insert("GOTENBERG-WEBHOOK-EXTRA-HTTP-HEADERS", value);
         }
 
-        header_map.insert("GOTENBERG-WEBHOOK-URL", self.url.parse().unwrap());
+        header_map.insert("GOTENBERG-WEBHOOK-URL", webhook_url("url", &self.url)?);
         header_map.insert(
             "GOTENBERG-WEBHOOK-ERROR-URL",
-            self.error_url.parse().unwrap(),
+            webhook_url("error_url", &self.error_url)?,
         );
         header_map.insert(
             "GOTENBERG-WEBHOOK-METHOD",
-            self.method
-                .clone()
-                .unwrap_or(POST)
-                .as_str()
-                .parse()
-                .unwrap(),
+            HeaderValue::from_static(self.method.clone().unwrap_or(POST).as_str()),
         );
         header_map.insert(
             "GOTENBERG-WEBHOOK-ERROR-METHOD",
-            self.error_method
-                .clone()
-                .unwrap_or(POST)
-                .as_str()
-                .parse()
-                .unwrap(),
+            HeaderValue::from_static(self.error_method.clone().unwrap_or(POST).as_str()),
         );
-        header_map
+        Ok(header_map)
+    }
+}
+
+/// Check that a webhook URL is an absolute http(s) URL that can be sent as a header.
+fn webhook_url(field: &'static str, url: &str) -> Result<HeaderValue, Error> {
+    let invalid = || Error::WebhookError(WebhookError::InvalidUrl(field, url.to_string()));
+
+    // The URL parser silently strips whitespace and control characters, so check the raw value first
+    if url.contains(|c: char| c.is_whitespace() || c.is_control()) {
+        return Err(invalid());
+    }
+
+    match reqwest::Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") && parsed.has_host() => {
+            HeaderValue::from_str(url).map_err(|_| invalid())
+        }
+        _ => Err(invalid()),
     }
 }
 
+/// Why [`WebhookOptions`] could not be turned into request headers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebhookError {
+    /// The field (`url` or `error_url`) is not an absolute http(s) URL.
+    // (Field, URL)
+    InvalidUrl(&'static str, String),
+
+    /// The extra HTTP header with the given name has an invalid name or value.
+    InvalidHeader(String),
+}
+
+impl fmt::Display for WebhookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WebhookError::InvalidUrl(field, url) => write!(
+                f,
+                "`{}` must be an absolute http(s) URL, got `{}`",
+                field,
+                url.escape_debug()
+            ),
+            WebhookError::InvalidHeader(name) => {
+                write!(f, "invalid extra HTTP header `{}`", name.escape_debug())
+            }
+        }
+    }
+}
+
+/// The Gotenberg module handling a family of routes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Engine {
+    /// `forms/chromium/*`: URL, HTML and Markdown conversions and screenshots.
+    Chromium,
+    /// `forms/libreoffice/*`: document conversions.
+    LibreOffice,
+    /// `forms/pdfengines/*`: merging, splitting and otherwise manipulating PDF files.
+    PdfEngines,
+}
+
+impl Engine {
+    /// The engine handling the given route, eg. `forms/chromium/convert/url`.
+    pub(crate) fn from_endpoint(endpoint: &str) -> Option<Self> {
+        match endpoint.trim_start_matches('/').split('/').nth(1)? {
+            "chromium" => Some(Engine::Chromium),
+            "libreoffice" => Some(Engine::LibreOffice),
+            "pdfengines" => Some(Engine::PdfEngines),
+            _ => None,
+        }
+    }
+}
+
+/// Per-request headers taken from the conversion options, along with the features the options need.
+#[derive(Debug, Clone, Default)]
+struct RequestHeaders {
+    trace: Option<String>,
+    output_filename: Option<String>,
+    features: Vec<Feature>,
+}
+
+impl RequestHeaders {
+    /// Add the headers to the request, along with the features they need.
+    fn fill_request(self, request: GotenbergRequest) -> GotenbergRequest {
+        let mut request = request;
+        if let Some(trace) = self.trace {
+            request = request.header("Gotenberg-Trace", trace);
+        }
+        if let Some(output_filename) = self.output_filename {
+            request = request.header("Gotenberg-Output-Filename", output_filename);
+        }
+        request.features.extend(self.features);
+        request
+    }
+}
+
+/// The features whose condition is true.
+fn used_features(features: &[(bool, Feature)]) -> Vec<Feature> {
+    features
+        .iter()
+        .filter(|(used, _)| *used)
+        .map(|(_, feature)| *feature)
+        .collect()
+}
+
 /// Configuration for rendering PDF from web content using the Chromium engine.
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -182,6 +451,10 @@ pub struct WebOptions {
     /// This trace will show up on the end server as a `Gotenberg-Trace` header.
     pub trace_id: Option<String>,
 
+    /// The filename of the resulting file, without extension. Gotenberg adds the extension itself.
+    /// Sent as the `Gotenberg-Output-Filename` header.
+    pub output_filename: Option<String>,
+
     /// Define whether to print the entire content on one single page.
     /// Default: `false`
     pub single_page: Option<bool>,
@@ -218,6 +491,10 @@ pub struct WebOptions {
     /// Default: `false`
     pub generate_document_outline: Option<bool>,
 
+    /// Define whether to generate a tagged (accessible) PDF.
+    /// Default: `false`
+    pub generate_tagged_pdf: Option<bool>,
+
     /// Print the background graphics.
     /// Default: `false`
     pub print_background: Option<bool>,
@@ -325,6 +602,27 @@ pub struct WebOptions {
 
     /// Fail a response if there are exceptions in the Chromium console.
     pub fail_on_console_exceptions: Option<bool>,
+
+    /// Split the resulting PDF, either every N pages or by page ranges.
+    /// When set, the server responds with a ZIP archive, see [`archive::unpack`].
+    pub split_mode: Option<SplitMode>,
+
+    /// Unify the pages selected by [`SplitMode::Pages`] into a single PDF instead of one PDF per range. default: false
+    pub split_unify: Option<bool>,
+
+    /// Flatten the resulting PDF, turning form fields and annotations into static content. default: false
+    pub flatten: Option<bool>,
+
+    /// Set the password for opening the resulting PDF, encrypting it.
+    pub user_password: Option<Secret>,
+
+    /// Set the password for full access (editing, printing, copying) to the resulting PDF.
+    /// Requires `user_password`: setting it alone fails the request with an [`Error::ParseError`].
+    pub owner_password: Option<Secret>,
+
+    /// Files to embed into the resulting PDF as attachments, eg. the XML invoice of a ZUGFeRD / Factur-X document.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub embeds: Vec<Attachment>,
 }
 
 impl WebOptions {
@@ -334,322 +632,197 @@ impl WebOptions {
         self.paper_height = Some(format.height());
     }
 
-    fn fill_form(self, form: reqwest::multipart::Form) -> reqwest::multipart::Form {
-        let mut form = form;
+    fn request_headers(&self) -> RequestHeaders {
+        RequestHeaders {
+            trace: self.trace_id.clone(),
+            output_filename: self.output_filename.clone(),
+            features: used_features(&[
+                (self.pdfua == Some(true), Feature::PdfUa),
+                (self.split_mode.is_some(), Feature::Split),
+                (self.flatten == Some(true), Feature::Flatten),
+                (
+                    self.generate_tagged_pdf == Some(true),
+                    Feature::GenerateTaggedPdf,
+                ),
+                (self.user_password.is_some(), Feature::Encryption),
+                (!self.embeds.is_empty(), Feature::EmbedFiles),
+            ]),
+        }
+    }
+
+    fn fill_request(self, request: GotenbergRequest) -> Result<GotenbergRequest, Error> {
+        let mut request = self.request_headers().fill_request(request);
 
         if let Some(single_page) = self.single_page {
-            form = form.text("singlePage", single_page.to_string());
+            request = request.text("singlePage", single_page.to_string());
         }
 
         if let Some(paper_width) = self.paper_width {
-            form = form.text("paperWidth", format!("{}", paper_width));
+            request = request.text("paperWidth", format!("{}", paper_width));
         }
 
         if let Some(paper_height) = self.paper_height {
-            form = form.text("paperHeight", format!("{}", paper_height));
+            request = request.text("paperHeight", format!("{}", paper_height));
         }
 
         if let Some(margin_top) = self.margin_top {
-            form = form.text("marginTop", margin_top.to_string());
+            request = request.text("marginTop", margin_top.to_string());
         }
 
         if let Some(margin_bottom) = self.margin_bottom {
-            form = form.text("marginBottom", margin_bottom.to_string());
+            request = request.text("marginBottom", margin_bottom.to_string());
         }
 
         if let Some(margin_left) = self.margin_left {
-            form = form.text("marginLeft", margin_left.to_string());
+            request = request.text("marginLeft", margin_left.to_string());
         }
 
         if let Some(margin_right) = self.margin_right {
-            form = form.text("marginRight", margin_right.to_string());
+            request = request.text("marginRight", margin_right.to_string());
         }
 
         if let Some(prefer_css_page_size) = self.prefer_css_page_size {
-            form = form.text("preferCssPageSize", prefer_css_page_size.to_string());
+            request = request.text("preferCssPageSize", prefer_css_page_size.to_string());
         }
 
         if let Some(generate_document_outline) = self.generate_document_outline {
-            form = form.text(
+            request = request.text(
                 "generateDocumentOutline",
                 generate_document_outline.to_string(),
             );
         }
 
+        if let Some(generate_tagged_pdf) = self.generate_tagged_pdf {
+            request = request.text("generateTaggedPdf", generate_tagged_pdf.to_string());
+        }
+
         if let Some(print_background) = self.print_background {
-            form = form.text("printBackground", print_background.to_string());
+            request = request.text("printBackground", print_background.to_string());
         }
 
         if let Some(omit_background) = self.omit_background {
-            form = form.text("omitBackground", omit_background.to_string());
+            request = request.text("omitBackground", omit_background.to_string());
         }
 
         if let Some(landscape) = self.landscape {
-            form = form.text("landscape", landscape.to_string());
+            request = request.text("landscape", landscape.to_string());
         }
 
         if let Some(scale) = self.scale {
-            form = form.text("scale", scale.to_string());
+            request = request.text("scale", scale.to_string());
         }
 
         if let Some(native_page_ranges) = self.native_page_ranges {
-            form = form.text("nativePageRanges", native_page_ranges.to_string());
+            request = request.text("nativePageRanges", native_page_ranges.to_string());
         }
 
         if let Some(header_html) = self.header_html {
-            let file_bytes = header_html.into_bytes();
-            let part = multipart::Part::bytes(file_bytes)
-                .file_name("header.html")
-                .mime_str("text/html")
-                .unwrap();
-            form = form.part("header.html", part);
+            let file =
+                FormFile::new("header.html", "header.html", header_html).content_type("text/html");
+            request = request.file(file);
         }
 
         if let Some(footer_html) = self.footer_html {
-            let file_bytes = footer_html.into_bytes();
-            let part = multipart::Part::bytes(file_bytes)
-                .file_name("footer.html")
-                .mime_str("text/html")
-                .unwrap();
-            form = form.part("footer.html", part);
+            let file =
+                FormFile::new("footer.html", "footer.html", footer_html).content_type("text/html");
+            request = request.file(file);
         }
 
         if let Some(wait_delay) = self.wait_delay {
-            form = form.text("waitDelay", format!("{}ms", wait_delay.as_millis()));
+            request = request.text("waitDelay", format!("{}ms", wait_delay.as_millis()));
         }
 
         if let Some(wait_for_expression) = self.wait_for_expression {
-            form = form.text("waitForExpression", wait_for_expression);
+            request = request.text("waitForExpression", wait_for_expression);
         }
 
         if let Some(emulated_media_type) = self.emulated_media_type {
-            form = form.text("emulatedMediaType", emulated_media_type.to_string());
+            request = request.text("emulatedMediaType", emulated_media_type.to_string());
         }
 
         if let Some(cookies) = self.cookies {
-            form = form.text("cookies", serde_json::to_string(&cookies).unwrap());
+            request = request.text("cookies", serde_json::to_string(&cookies).unwrap());
         }
 
         if let Some(skip_network_idle_events) = self.skip_network_idle_events {
-            form = form.text(
+            request = request.text(
                 "skipNetworkIdleEvents",
                 skip_network_idle_events.to_string(),
             );
         }
 
         if let Some(user_agent) = self.user_agent {
-            form = form.text("userAgent", user_agent);
+            request = request.text("userAgent", user_agent);
         }
 
         if let Some(extra_http_headers) = self.extra_http_headers {
-            form = form.text(
+            request = request.text(
                 "extraHttpHeaders",
                 serde_json::to_string(&extra_http_headers).unwrap(),
             );
         }
 
         if let Some(pdfa) = self.pdfa {
-            form = form.text("pdfa", pdfa.to_string());
+            request = request.text("pdfa", pdfa.to_string());
         }
 
         if let Some(pdfua) = self.pdfua {
-            form = form.text("pdfua", pdfua.to_string());
+            request = request.text("pdfua", pdfua.to_string());
         }
 
         if let Some(metadata) = self.metadata {
-            form = form.text("metadata", serde_json::to_string(&metadata).unwrap());
+            request = request.text("metadata", serde_json::to_string(&metadata).unwrap());
         }
 
         if let Some(fail_on_http_status_codes) = self.fail_on_http_status_codes {
-            form = form.text(
+            request = request.text(
                 "failOnHttpStatusCodes",
                 serde_json::to_string(&fail_on_http_status_codes).unwrap(),
             );
         }
 
         if let Some(fail_on_resource_http_status_codes) = self.fail_on_resource_http_status_codes {
-            form = form.text(
+            request = request.text(
                 "failOnResourceHttpStatusCodes",
                 serde_json::to_string(&fail_on_resource_http_status_codes).unwrap(),
             );
         }
 
         if let Some(fail_on_resource_loading_failed) = self.fail_on_resource_loading_failed {
-            form = form.text(
+            request = request.text(
                 "failOnResourceLoadingFailed",
                 fail_on_resource_loading_failed.to_string(),
             );
         }
 
         if let Some(fail_on_console_exceptions) = self.fail_on_console_exceptions {
-            form = form.text(
+            request = request.text(
                 "failOnConsoleExceptions",
                 fail_on_console_exceptions.to_string(),
             );
         }
 
-        form
-    }
-
-    #[cfg(feature = "blocking")]
-    fn fill_form_blocking(
-        self,
-        form: reqwest::blocking::multipart::Form,
-    ) -> reqwest::blocking::multipart::Form {
-        let mut form = form;
-
-        if let Some(single_page) = self.single_page {
-            form = form.text("singlePage", single_page.to_string());
-        }
-
-        if let Some(paper_width) = self.paper_width {
-            form = form.text("paperWidth", format!("{}", paper_width));
-        }
-
-        if let Some(paper_height) = self.paper_height {
-            form = form.text("paperHeight", format!("{}", paper_height));
-        }
-
-        if let Some(margin_top) = self.margin_top {
-            form = form.text("marginTop", margin_top.to_string());
-        }
-
-        if let Some(margin_bottom) = self.margin_bottom {
-            form = form.text("marginBottom", margin_bottom.to_string());
-        }
-
-        if let Some(margin_left) = self.margin_left {
-            form = form.text("marginLeft", margin_left.to_string());
-        }
-
-        if let Some(margin_right) = self.margin_right {
-            form = form.text("marginRight", margin_right.to_string());
-        }
-
-        if let Some(prefer_css_page_size) = self.prefer_css_page_size {
-            form = form.text("preferCssPageSize", prefer_css_page_size.to_string());
-        }
-
-        if let Some(generate_document_outline) = self.generate_document_outline {
-            form = form.text(
-                "generateDocumentOutline",
-                generate_document_outline.to_string(),
-            );
-        }
-
-        if let Some(print_background) = self.print_background {
-            form = form.text("printBackground", print_background.to_string());
-        }
-
-        if let Some(omit_background) = self.omit_background {
-            form = form.text("omitBackground", omit_background.to_string());
-        }
-
-        if let Some(landscape) = self.landscape {
-            form = form.text("landscape", landscape.to_string());
-        }
-
-        if let Some(scale) = self.scale {
-            form = form.text("scale", scale.to_string());
-        }
-
-        if let Some(native_page_ranges) = self.native_page_ranges {
-            form = form.text("nativePageRanges", native_page_ranges.to_string());
-        }
-
-        if let Some(header_html) = self.header_html {
-            let file_bytes = header_html.into_bytes();
-            let part = reqwest::blocking::multipart::Part::bytes(file_bytes)
-                .file_name("header.html")
-                .mime_str("text/html")
-                .unwrap();
-            form = form.part("header.html", part);
-        }
-
-        if let Some(footer_html) = self.footer_html {
-            let file_bytes = footer_html.into_bytes();
-            let part = reqwest::blocking::multipart::Part::bytes(file_bytes)
-                .file_name("footer.html")
-                .mime_str("text/html")
-                .unwrap();
-            form = form.part("footer.html", part);
-        }
-
-        if let Some(wait_delay) = self.wait_delay {
-            form = form.text("waitDelay", format!("{}ms", wait_delay.as_millis()));
-        }
-
-        if let Some(wait_for_expression) = self.wait_for_expression {
-            form = form.text("waitForExpression", wait_for_expression);
-        }
-
-        if let Some(emulated_media_type) = self.emulated_media_type {
-            form = form.text("emulatedMediaType", emulated_media_type.to_string());
-        }
-
-        if let Some(cookies) = self.cookies {
-            form = form.text("cookies", serde_json::to_string(&cookies).unwrap());
-        }
-
-        if let Some(skip_network_idle_events) = self.skip_network_idle_events {
-            form = form.text(
-                "skipNetworkIdleEvents",
-                skip_network_idle_events.to_string(),
-            );
-        }
-
-        if let Some(user_agent) = self.user_agent {
-            form = form.text("userAgent", user_agent);
-        }
-
-        if let Some(extra_http_headers) = self.extra_http_headers {
-            form = form.text(
-                "extraHttpHeaders",
-                serde_json::to_string(&extra_http_headers).unwrap(),
-            );
-        }
-
-        if let Some(pdfa) = self.pdfa {
-            form = form.text("pdfa", pdfa.to_string());
-        }
-
-        if let Some(pdfua) = self.pdfua {
-            form = form.text("pdfua", pdfua.to_string());
+        if let Some(split_mode) = self.split_mode {
+            for (name, value) in split_mode.form_fields() {
+                request = request.text(name, value);
+            }
         }
 
-        if let Some(metadata) = self.metadata {
-            form = form.text("metadata", serde_json::to_string(&metadata).unwrap());
+        if let Some(split_unify) = self.split_unify {
+            request = request.text("splitUnify", split_unify.to_string());
         }
 
-        if let Some(fail_on_http_status_codes) = self.fail_on_http_status_codes {
-            form = form.text(
-                "failOnHttpStatusCodes",
-                serde_json::to_string(&fail_on_http_status_codes).unwrap(),
-            );
+        if let Some(flatten) = self.flatten {
+            request = request.text("flatten", flatten.to_string());
         }
 
-        if let Some(fail_on_resource_http_status_codes) = self.fail_on_resource_http_status_codes {
-            form = form.text(
-                "failOnResourceHttpStatusCodes",
-                serde_json::to_string(&fail_on_resource_http_status_codes).unwrap(),
-            );
-        }
+        request = request::password_fields(request, self.user_password, self.owner_password)?;
 
-        if let Some(fail_on_resource_loading_failed) = self.fail_on_resource_loading_failed {
-            form = form.text(
-                "failOnResourceLoadingFailed",
-                fail_on_resource_loading_failed.to_string(),
-            );
+        for attachment in self.embeds {
+            request = request.file(attachment.form_file("embeds")?);
         }
 
-        if let Some(fail_on_console_exceptions) = self.fail_on_console_exceptions {
-            form = form.text(
-                "failOnConsoleExceptions",
-                fail_on_console_exceptions.to_string(),
-            );
-        }
-
-        form
+        Ok(request)
     }
 }
 
@@ -660,6 +833,10 @@ pub struct ScreenshotOptions {
     /// This trace will show up on the end server as a `Gotenberg-Trace` header.
     pub trace_id: Option<String>,
 
+    /// The filename of the resulting file, without extension. Gotenberg adds the extension itself.
+    /// Sent as the `Gotenberg-Output-Filename` header.
+    pub output_filename: Option<String>,
+
     /// The device screen width in pixels. Default: 800.
     pub width: Option<u32>,
 
@@ -736,200 +913,108 @@ pub struct ScreenshotOptions {
 }
 
 impl ScreenshotOptions {
-    fn fill_form(self, form: reqwest::multipart::Form) -> reqwest::multipart::Form {
-        let mut form = form;
-
-        if let Some(width) = self.width {
-            form = form.text("width", width.to_string());
-        }
-
-        if let Some(height) = self.height {
-            form = form.text("height", height.to_string());
-        }
-
-        if let Some(clip) = self.clip {
-            form = form.text("clip", clip.to_string());
-        }
-
-        if let Some(format) = self.format {
-            form = form.text("format", format.to_string());
-        }
-
-        if let Some(quality) = self.quality {
-            form = form.text("quality", quality.to_string());
-        }
-
-        if let Some(omit_background) = self.omit_background {
-            form = form.text("omitBackground", omit_background.to_string());
-        }
-
-        if let Some(optimize_for_speed) = self.optimize_for_speed {
-            form = form.text("optimizeForSpeed", optimize_for_speed.to_string());
-        }
-
-        if let Some(wait_delay) = self.wait_delay {
-            form = form.text("waitDelay", format!("{}ms", wait_delay.as_millis()));
-        }
-
-        if let Some(wait_for_expression) = self.wait_for_expression {
-            form = form.text("waitForExpression", wait_for_expression);
-        }
-
-        if let Some(emulated_media_type) = self.emulated_media_type {
-            form = form.text("emulatedMediaType", emulated_media_type.to_string());
-        }
-
-        if let Some(cookies) = self.cookies {
-            form = form.text("cookies", serde_json::to_string(&cookies).unwrap());
-        }
-
-        if let Some(skip_network_idle_events) = self.skip_network_idle_events {
-            form = form.text(
-                "skipNetworkIdleEvents",
-                skip_network_idle_events.to_string(),
-            );
-        }
-
-        if let Some(user_agent) = self.user_agent {
-            form = form.text("userAgent", user_agent);
-        }
-
-        if let Some(extra_http_headers) = self.extra_http_headers {
-            form = form.text(
-                "extraHttpHeaders",
-                serde_json::to_string(&extra_http_headers).unwrap(),
-            );
-        }
-
-        if let Some(fail_on_http_status_codes) = self.fail_on_http_status_codes {
-            form = form.text(
-                "failOnHttpStatusCodes",
-                serde_json::to_string(&fail_on_http_status_codes).unwrap(),
-            );
-        }
-
-        if let Some(fail_on_resource_http_status_codes) = self.fail_on_resource_http_status_codes {
-            form = form.text(
-                "failOnResourceHttpStatusCodes",
-                serde_json::to_string(&fail_on_resource_http_status_codes).unwrap(),
-            );
-        }
-
-        if let Some(fail_on_resource_loading_failed) = self.fail_on_resource_loading_failed {
-            form = form.text(
-                "failOnResourceLoadingFailed",
-                fail_on_resource_loading_failed.to_string(),
-            );
-        }
-
-        if let Some(fail_on_console_exceptions) = self.fail_on_console_exceptions {
-            form = form.text(
-                "failOnConsoleExceptions",
-                fail_on_console_exceptions.to_string(),
-            );
+    fn request_headers(&self) -> RequestHeaders {
+        RequestHeaders {
+            trace: self.trace_id.clone(),
+            output_filename: self.output_filename.clone(),
+            ..Default::default()
         }
-
-        form
     }
 
-    #[cfg(feature = "blocking")]
-    fn fill_form_blocking(
-        self,
-        form: reqwest::blocking::multipart::Form,
-    ) -> reqwest::blocking::multipart::Form {
-        let mut form = form;
+    fn fill_request(self, request: GotenbergRequest) -> GotenbergRequest {
+        let mut request = self.request_headers().fill_request(request);
 
         if let Some(width) = self.width {
-            form = form.text("width", width.to_string());
+            request = request.text("width", width.to_string());
         }
 
         if let Some(height) = self.height {
-            form = form.text("height", height.to_string());
+            request = request.text("height", height.to_string());
         }
 
         if let Some(clip) = self.clip {
-            form = form.text("clip", clip.to_string());
+            request = request.text("clip", clip.to_string());
         }
 
         if let Some(format) = self.format {
-            form = form.text("format", format.to_string());
+            request = request.text("format", format.to_string());
         }
 
         if let Some(quality) = self.quality {
-            form = form.text("quality", quality.to_string());
+            request = request.text("quality", quality.to_string());
         }
 
         if let Some(omit_background) = self.omit_background {
-            form = form.text("omitBackground", omit_background.to_string());
+            request = request.text("omitBackground", omit_background.to_string());
         }
 
         if let Some(optimize_for_speed) = self.optimize_for_speed {
-            form = form.text("optimizeForSpeed", optimize_for_speed.to_string());
+            request = request.text("optimizeForSpeed", optimize_for_speed.to_string());
         }
 
         if let Some(wait_delay) = self.wait_delay {
-            form = form.text("waitDelay", format!("{}ms", wait_delay.as_millis()));
+            request = request.text("waitDelay", format!("{}ms", wait_delay.as_millis()));
         }
 
         if let Some(wait_for_expression) = self.wait_for_expression {
-            form = form.text("waitForExpression", wait_for_expression);
+            request = request.text("waitForExpression", wait_for_expression);
         }
 
         if let Some(emulated_media_type) = self.emulated_media_type {
-            form = form.text("emulatedMediaType", emulated_media_type.to_string());
+            request = request.text("emulatedMediaType", emulated_media_type.to_string());
         }
 
         if let Some(cookies) = self.cookies {
-            form = form.text("cookies", serde_json::to_string(&cookies).unwrap());
+            request = request.text("cookies", serde_json::to_string(&cookies).unwrap());
         }
 
         if let Some(skip_network_idle_events) = self.skip_network_idle_events {
-            form = form.text(
+            request = request.text(
                 "skipNetworkIdleEvents",
                 skip_network_idle_events.to_string(),
             );
         }
 
         if let Some(user_agent) = self.user_agent {
-            form = form.text("userAgent", user_agent);
+            request = request.text("userAgent", user_agent);
         }
 
         if let Some(extra_http_headers) = self.extra_http_headers {
-            form = form.text(
+            request = request.text(
                 "extraHttpHeaders",
                 serde_json::to_string(&extra_http_headers).unwrap(),
             );
         }
 
         if let Some(fail_on_http_status_codes) = self.fail_on_http_status_codes {
-            form = form.text(
+            request = request.text(
                 "failOnHttpStatusCodes",
                 serde_json::to_string(&fail_on_http_status_codes).unwrap(),
             );
         }
 
         if let Some(fail_on_resource_http_status_codes) = self.fail_on_resource_http_status_codes {
-            form = form.text(
+            request = request.text(
                 "failOnResourceHttpStatusCodes",
                 serde_json::to_string(&fail_on_resource_http_status_codes).unwrap(),
             );
         }
 
         if let Some(fail_on_resource_loading_failed) = self.fail_on_resource_loading_failed {
-            form = form.text(
+            request = request.text(
                 "failOnResourceLoadingFailed",
                 fail_on_resource_loading_failed.to_string(),
             );
         }
 
         if let Some(fail_on_console_exceptions) = self.fail_on_console_exceptions {
-            form = form.text(
+            request = request.text(
                 "failOnConsoleExceptions",
                 fail_on_console_exceptions.to_string(),
             );
         }
 
-        form
+        request
     }
 }
 
@@ -940,6 +1025,10 @@ pub struct DocumentOptions {
     /// This trace will show up on the end server as a `Gotenberg-Trace` header.
     pub trace_id: Option<String>,
 
+    /// The filename of the resulting file, without extension. Gotenberg adds the extension itself.
+    /// Sent as the `Gotenberg-Output-Filename` header.
+    pub output_filename: Option<String>,
+
     /// Set the password for opening the source file.
     pub password: Option<String>,
 
@@ -1011,254 +1100,556 @@ pub struct DocumentOptions {
 
     /// Enable PDF for Universal Access for optimal accessibility.
     pub pdfua: Option<bool>,
+
+    /// Merge the resulting PDFs into a single PDF when converting several documents. default: false
+    /// See [`Client::pdfs_from_docs`].
+    pub merge: Option<bool>,
+
+    /// Split the resulting PDF, either every N pages or by page ranges.
+    /// When set, the server responds with a ZIP archive, see [`archive::unpack`].
+    pub split_mode: Option<SplitMode>,
+
+    /// Unify the pages selected by [`SplitMode::Pages`] into a single PDF instead of one PDF per range. default: false
+    pub split_unify: Option<bool>,
+
+    /// Flatten the resulting PDF, turning form fields and annotations into static content. default: false
+    pub flatten: Option<bool>,
+
+    /// Set the password for opening the resulting PDF, encrypting it.
+    pub user_password: Option<Secret>,
+
+    /// Set the password for full access (editing, printing, copying) to the resulting PDF.
+    /// Requires `user_password`: setting it alone fails the request with an [`Error::ParseError`].
+    pub owner_password: Option<Secret>,
+
+    /// Files to embed into the resulting PDF as attachments, eg. the XML invoice of a ZUGFeRD / Factur-X document.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub embeds: Vec<Attachment>,
 }
 
 /// Options for converting a document to a PDF using the LibreOffice engine.
 impl DocumentOptions {
-    fn fill_form(self, form: reqwest::multipart::Form) -> reqwest::multipart::Form {
-        let mut form = form;
+    fn request_headers(&self) -> RequestHeaders {
+        RequestHeaders {
+            trace: self.trace_id.clone(),
+            output_filename: self.output_filename.clone(),
+            features: used_features(&[
+                (self.pdfua == Some(true), Feature::PdfUa),
+                (self.split_mode.is_some(), Feature::Split),
+                (self.flatten == Some(true), Feature::Flatten),
+                (self.user_password.is_some(), Feature::Encryption),
+                (!self.embeds.is_empty(), Feature::EmbedFiles),
+            ]),
+        }
+    }
+
+    fn fill_request(self, request: GotenbergRequest) -> Result<GotenbergRequest, Error> {
+        let mut request = self.request_headers().fill_request(request);
 
         if let Some(password) = self.password {
-            form = form.text("password", password);
+            request = request.secret("password", password);
         }
 
         if let Some(landscape) = self.landscape {
-            form = form.text("landscape", landscape.to_string());
+            request = request.text("landscape", landscape.to_string());
         }
 
         if let Some(native_page_ranges) = self.native_page_ranges {
-            form = form.text("nativePageRanges", native_page_ranges.to_string());
+            request = request.text("nativePageRanges", native_page_ranges.to_string());
         }
 
         if let Some(export_form_fields) = self.export_form_fields {
-            form = form.text("exportFormFields", export_form_fields.to_string());
+            request = request.text("exportFormFields", export_form_fields.to_string());
         }
 
         if let Some(allow_duplicate_field_names) = self.allow_duplicate_field_names {
-            form = form.text(
+            request = request.text(
                 "allowDuplicateFieldNames",
                 allow_duplicate_field_names.to_string(),
             );
         }
 
         if let Some(export_bookmarks) = self.export_bookmarks {
-            form = form.text("exportBookmarks", export_bookmarks.to_string());
+            request = request.text("exportBookmarks", export_bookmarks.to_string());
         }
 
         if let Some(export_bookmarks_to_pdf_destination) = self.export_bookmarks_to_pdf_destination
         {
-            form = form.text(
+            request = request.text(
                 "exportBookmarksToPdfDestination",
                 export_bookmarks_to_pdf_destination.to_string(),
             );
         }
 
         if let Some(export_placeholders) = self.export_placeholders {
-            form = form.text("exportPlaceholders", export_placeholders.to_string());
+            request = request.text("exportPlaceholders", export_placeholders.to_string());
         }
 
         if let Some(export_notes) = self.export_notes {
-            form = form.text("exportNotes", export_notes.to_string());
+            request = request.text("exportNotes", export_notes.to_string());
         }
 
         if let Some(export_notes_pages) = self.export_notes_pages {
-            form = form.text("exportNotesPages", export_notes_pages.to_string());
+            request = request.text("exportNotesPages", export_notes_pages.to_string());
         }
 
         if let Some(export_only_notes_pages) = self.export_only_notes_pages {
-            form = form.text("exportOnlyNotesPages", export_only_notes_pages.to_string());
+            request = request.text("exportOnlyNotesPages", export_only_notes_pages.to_string());
         }
 
         if let Some(export_notes_in_margin) = self.export_notes_in_margin {
-            form = form.text("exportNotesInMargin", export_notes_in_margin.to_string());
+            request = request.text("exportNotesInMargin", export_notes_in_margin.to_string());
         }
 
         if let Some(convert_ooo_target_to_pdf_target) = self.convert_ooo_target_to_pdf_target {
-            form = form.text(
+            request = request.text(
                 "convertOooTargetToPdfTarget",
                 convert_ooo_target_to_pdf_target.to_string(),
             );
         }
 
         if let Some(export_links_relative_fsys) = self.export_links_relative_fsys {
-            form = form.text(
+            request = request.text(
                 "exportLinksRelativeFsys",
                 export_links_relative_fsys.to_string(),
             );
         }
 
         if let Some(export_hidden_slides) = self.export_hidden_slides {
-            form = form.text("exportHiddenSlides", export_hidden_slides.to_string());
+            request = request.text("exportHiddenSlides", export_hidden_slides.to_string());
         }
 
         if let Some(skip_empty_pages) = self.skip_empty_pages {
-            form = form.text("skipEmptyPages", skip_empty_pages.to_string());
+            request = request.text("skipEmptyPages", skip_empty_pages.to_string());
         }
 
         if let Some(add_original_document_as_stream) = self.add_original_document_as_stream {
-            form = form.text(
+            request = request.text(
                 "addOriginalDocumentAsStream",
                 add_original_document_as_stream.to_string(),
             );
         }
 
         if let Some(single_page_sheets) = self.single_page_sheets {
-            form = form.text("singlePageSheets", single_page_sheets.to_string());
+            request = request.text("singlePageSheets", single_page_sheets.to_string());
         }
 
         if let Some(lossless_image_compression) = self.lossless_image_compression {
-            form = form.text(
+            request = request.text(
                 "losslessImageCompression",
                 lossless_image_compression.to_string(),
             );
         }
 
         if let Some(quality) = self.quality {
-            form = form.text("quality", quality.to_string());
+            request = request.text("quality", quality.to_string());
         }
 
         if let Some(reduce_image_resolution) = self.reduce_image_resolution {
-            form = form.text("reduceImageResolution", reduce_image_resolution.to_string());
+            request = request.text("reduceImageResolution", reduce_image_resolution.to_string());
         }
 
         if let Some(max_image_resolution) = self.max_image_resolution {
-            form = form.text("maxImageResolution", max_image_resolution.to_string());
+            request = request.text("maxImageResolution", max_image_resolution.to_string());
         }
 
         if let Some(pdfa) = self.pdfa {
-            form = form.text("pdfa", pdfa.to_string());
+            request = request.text("pdfa", pdfa.to_string());
         }
 
         if let Some(pdfua) = self.pdfua {
-            form = form.text("pdfua", pdfua.to_string());
+            request = request.text("pdfua", pdfua.to_string());
         }
 
-        form
-    }
-
-    #[cfg(feature = "blocking")]
-    fn fill_form_blocking(
-        self,
-        form: reqwest::blocking::multipart::Form,
-    ) -> reqwest::blocking::multipart::Form {
-        let mut form = form;
-
-        if let Some(password) = self.password {
-            form = form.text("password", password);
+        if let Some(merge) = self.merge {
+            request = request.text("merge", merge.to_string());
         }
 
-        if let Some(landscape) = self.landscape {
-            form = form.text("landscape", landscape.to_string());
+        if let Some(split_mode) = self.split_mode {
+            for (name, value) in split_mode.form_fields() {
+                request = request.text(name, value);
+            }
         }
 
-        if let Some(native_page_ranges) = self.native_page_ranges {
-            form = form.text("nativePageRanges", native_page_ranges.to_string());
+        if let Some(split_unify) = self.split_unify {
+            request = request.text("splitUnify", split_unify.to_string());
         }
 
-        if let Some(export_form_fields) = self.export_form_fields {
-            form = form.text("exportFormFields", export_form_fields.to_string());
+        if let Some(flatten) = self.flatten {
+            request = request.text("flatten", flatten.to_string());
         }
 
-        if let Some(allow_duplicate_field_names) = self.allow_duplicate_field_names {
-            form = form.text(
-                "allowDuplicateFieldNames",
-                allow_duplicate_field_names.to_string(),
-            );
+        request = request::password_fields(request, self.user_password, self.owner_password)?;
+
+        for attachment in self.embeds {
+            request = request.file(attachment.form_file("embeds")?);
         }
 
-        if let Some(export_bookmarks) = self.export_bookmarks {
-            form = form.text("exportBookmarks", export_bookmarks.to_string());
+        Ok(request)
+    }
+}
+
+/// Options for merging PDF files using the PDF engines.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MergeOptions {
+    /// By default, the API assigns a unique UUID trace to every request. However, you also have the option to specify the trace for each request.
+    /// This trace will show up on the end server as a `Gotenberg-Trace` header.
+    pub trace_id: Option<String>,
+
+    /// The filename of the resulting file, without extension. Gotenberg adds the extension itself.
+    /// Sent as the `Gotenberg-Output-Filename` header.
+    pub output_filename: Option<String>,
+
+    /// Convert the resulting PDF into the given PDF/A format
+    pub pdfa: Option<PDFFormat>,
+
+    /// Enable PDF for Universal Access for optimal accessibility.
+    pub pdfua: Option<bool>,
+
+    /// Write PDF metadata.
+    /// Not all metadata are writable. Consider taking a look at <https://exiftool.org/TagNames/XMP.html#pdf> for an (exhaustive?) list of available metadata.
+    /// Caution: Writing metadata may compromise PDF/A compliance.
+    pub metadata: Option<HashMap<String, serde_json::Value>>,
+
+    /// Flatten the resulting PDF, turning form fields and annotations into static content. default: false
+    pub flatten: Option<bool>,
+
+    /// Set the password for opening the resulting PDF, encrypting it.
+    pub user_password: Option<Secret>,
+
+    /// Set the password for full access (editing, printing, copying) to the resulting PDF.
+    /// Requires `user_password`: setting it alone fails the request with an [`Error::ParseError`].
+    pub owner_password: Option<Secret>,
+
+    /// Files to embed into the resulting PDF as attachments, eg. the XML invoice of a ZUGFeRD / Factur-X document.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub embeds: Vec<Attachment>,
+}
+
+impl MergeOptions {
+    fn request_headers(&self) -> RequestHeaders {
+        RequestHeaders {
+            trace: self.trace_id.clone(),
+            output_filename: self.output_filename.clone(),
+            features: used_features(&[
+                (self.pdfua == Some(true), Feature::PdfUa),
+                (self.flatten == Some(true), Feature::Flatten),
+                (self.user_password.is_some(), Feature::Encryption),
+                (!self.embeds.is_empty(), Feature::EmbedFiles),
+            ]),
         }
+    }
 
-        if let Some(export_bookmarks_to_pdf_destination) = self.export_bookmarks_to_pdf_destination
-        {
-            form = form.text(
-                "exportBookmarksToPdfDestination",
-                export_bookmarks_to_pdf_destination.to_string(),
-            );
+    fn fill_request(self, request: GotenbergRequest) -> Result<GotenbergRequest, Error> {
+        let mut request = self.request_headers().fill_request(request);
+
+        if let Some(pdfa) = self.pdfa {
+            request = request.text("pdfa", pdfa.to_string());
         }
 
-        if let Some(export_placeholders) = self.export_placeholders {
-            form = form.text("exportPlaceholders", export_placeholders.to_string());
+        if let Some(pdfua) = self.pdfua {
+            request = request.text("pdfua", pdfua.to_string());
         }
 
-        if let Some(export_notes) = self.export_notes {
-            form = form.text("exportNotes", export_notes.to_string());
+        if let Some(metadata) = self.metadata {
+            request = request.text("metadata", serde_json::to_string(&metadata).unwrap());
         }
 
-        if let Some(export_notes_pages) = self.export_notes_pages {
-            form = form.text("exportNotesPages", export_notes_pages.to_string());
+        if let Some(flatten) = self.flatten {
+            request = request.text("flatten", flatten.to_string());
         }
 
-        if let Some(export_only_notes_pages) = self.export_only_notes_pages {
-            form = form.text("exportOnlyNotesPages", export_only_notes_pages.to_string());
+        request = request::password_fields(request, self.user_password, self.owner_password)?;
+
+        for attachment in self.embeds {
+            request = request.file(attachment.form_file("embeds")?);
         }
 
-        if let Some(export_notes_in_margin) = self.export_notes_in_margin {
-            form = form.text("exportNotesInMargin", export_notes_in_margin.to_string());
+        Ok(request)
+    }
+}
+
+/// Parse the response of the metadata route, which maps the filename of the PDF to its metadata.
+/// A downloaded PDF keeps its own filename, so the single entry is taken whatever its name.
+fn parse_metadata(bytes: &[u8]) -> Result<HashMap<String, serde_json::Value>, Error> {
+    let error = |message: String| {
+        Error::ParseError(
+            "Metadata".to_string(),
+            String::from_utf8_lossy(bytes).to_string(),
+            message,
+        )
+    };
+
+    let files: HashMap<String, HashMap<String, serde_json::Value>> =
+        serde_json::from_slice(bytes).map_err(|e| error(e.to_string()))?;
+    files
+        .into_values()
+        .next()
+        .ok_or_else(|| error("missing the metadata of the PDF".to_string()))
+}
+
+/// Decode `%XX` escapes, as found in URL paths and `Content-Disposition` filenames.
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
         }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
 
-        if let Some(convert_ooo_target_to_pdf_target) = self.convert_ooo_target_to_pdf_target {
-            form = form.text(
-                "convertOooTargetToPdfTarget",
-                convert_ooo_target_to_pdf_target.to_string(),
-            );
+/// A random number, good enough for traces and retry jitter but not for secrets.
+///
+/// Every `RandomState` is seeded differently, so hashing nothing with a new one yields a new number.
+pub(crate) fn random_u64() -> u64 {
+    use std::hash::{BuildHasher, Hasher};
+
+    std::collections::hash_map::RandomState::new()
+        .build_hasher()
+        .finish()
+}
+
+/// Gotenberg merges files in the alphanumerical order of their filenames.
+/// Prefix the filename with its position so the caller's ordering is kept.
+fn ordered_filename(index: usize, filename: &str) -> String {
+    format!("{:05}_{}", index, filename)
+}
+
+/// The filename of a file of the merge route, checked for a `.pdf` extension and prefixed to keep its order.
+fn merge_filename(index: usize, filename: &str) -> Result<String, Error> {
+    if !filename.to_lowercase().ends_with(".pdf") {
+        return Err(Error::FilenameError(format!(
+            "Merge filename must end with '.pdf': {}",
+            filename
+        )));
+    }
+    Ok(ordered_filename(index, filename))
+}
+
+/// The PDFs produced by converting several documents at once. See [`Client::pdfs_from_docs`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConvertedDocuments {
+    /// A single PDF containing every document, in the order they were given. Returned when `merge` is set.
+    Merged(Bytes),
+
+    /// One `(filename, bytes)` PDF per document, unpacked from the ZIP archive returned by the server.
+    /// Empty when the result is delivered to a webhook.
+    Files(Vec<(String, Bytes)>),
+}
+
+impl ConvertedDocuments {
+    fn from_response(bytes: Bytes, merged: bool, filenames: &[String]) -> Result<Self, Error> {
+        if merged {
+            return Ok(ConvertedDocuments::Merged(bytes));
         }
 
-        if let Some(export_links_relative_fsys) = self.export_links_relative_fsys {
-            form = form.text(
-                "exportLinksRelativeFsys",
-                export_links_relative_fsys.to_string(),
-            );
+        // A single document is returned as a bare PDF, named after its source file.
+        let filename = match filenames {
+            [filename] => pdf_filename(filename),
+            _ => "file.pdf".to_string(),
+        };
+        archive::files_from_response(bytes, &filename).map(ConvertedDocuments::Files)
+    }
+}
+
+/// The name Gotenberg gives to the PDF converted from `filename`, eg. `report.docx` becomes `report.pdf`.
+fn pdf_filename(filename: &str) -> String {
+    let stem = match filename.rsplit_once('.') {
+        Some((stem, _)) if !stem.is_empty() => stem,
+        _ => filename,
+    };
+    format!("{}.pdf", stem)
+}
+
+/// How to split a PDF into several files.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SplitMode {
+    /// Split the PDF every N pages, eg `Intervals(1)` produces one file per page.
+    Intervals(u32),
+
+    /// Extract the given page ranges, eg `"1-3,5"`. Each range becomes its own file unless `split_unify` is set.
+    Pages(PageRange),
+}
+
+impl Default for SplitMode {
+    fn default() -> Self {
+        SplitMode::Intervals(1)
+    }
+}
+
+impl SplitMode {
+    /// The `splitMode` and `splitSpan` form fields for this mode.
+    fn form_fields(&self) -> [(&'static str, String); 2] {
+        match self {
+            SplitMode::Intervals(span) => [
+                ("splitMode", "intervals".into()),
+                ("splitSpan", span.to_string()),
+            ],
+            SplitMode::Pages(pages) => [
+                ("splitMode", "pages".into()),
+                ("splitSpan", pages.to_string()),
+            ],
         }
+    }
+}
 
-        if let Some(export_hidden_slides) = self.export_hidden_slides {
-            form = form.text("exportHiddenSlides", export_hidden_slides.to_string());
+/// Options for splitting a PDF file using the PDF engines.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SplitOptions {
+    /// By default, the API assigns a unique UUID trace to every request. However, you also have the option to specify the trace for each request.
+    /// This trace will show up on the end server as a `Gotenberg-Trace` header.
+    pub trace_id: Option<String>,
+
+    /// The filename of the resulting file, without extension. Gotenberg adds the extension itself.
+    /// Sent as the `Gotenberg-Output-Filename` header.
+    pub output_filename: Option<String>,
+
+    /// How to split the PDF.
+    /// Default: `Intervals(1)` (one file per page)
+    pub split_mode: SplitMode,
+
+    /// Unify the pages selected by [`SplitMode::Pages`] into a single PDF instead of one PDF per range. default: false
+    pub split_unify: Option<bool>,
+
+    /// Convert the resulting PDFs into the given PDF/A format
+    pub pdfa: Option<PDFFormat>,
+
+    /// Enable PDF for Universal Access for optimal accessibility.
+    pub pdfua: Option<bool>,
+
+    /// Write PDF metadata.
+    /// Not all metadata are writable. Consider taking a look at <https://exiftool.org/TagNames/XMP.html#pdf> for an (exhaustive?) list of available metadata.
+    /// Caution: Writing metadata may compromise PDF/A compliance.
+    pub metadata: Option<HashMap<String, serde_json::Value>>,
+
+    /// Flatten the resulting PDF, turning form fields and annotations into static content. default: false
+    pub flatten: Option<bool>,
+
+    /// Set the password for opening the resulting PDF, encrypting it.
+    pub user_password: Option<Secret>,
+
+    /// Set the password for full access (editing, printing, copying) to the resulting PDF.
+    /// Requires `user_password`: setting it alone fails the request with an [`Error::ParseError`].
+    pub owner_password: Option<Secret>,
+
+    /// Files to embed into the resulting PDF as attachments, eg. the XML invoice of a ZUGFeRD / Factur-X document.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub embeds: Vec<Attachment>,
+}
+
+impl SplitOptions {
+    /// Create split options for the given split mode.
+    pub fn new(split_mode: SplitMode) -> Self {
+        SplitOptions {
+            split_mode,
+            ..Default::default()
         }
+    }
 
-        if let Some(skip_empty_pages) = self.skip_empty_pages {
-            form = form.text("skipEmptyPages", skip_empty_pages.to_string());
+    fn request_headers(&self) -> RequestHeaders {
+        RequestHeaders {
+            trace: self.trace_id.clone(),
+            output_filename: self.output_filename.clone(),
+            features: used_features(&[
+                (self.pdfua == Some(true), Feature::PdfUa),
+                (self.flatten == Some(true), Feature::Flatten),
+                (self.user_password.is_some(), Feature::Encryption),
+                (!self.embeds.is_empty(), Feature::EmbedFiles),
+            ]),
         }
+    }
 
-        if let Some(add_original_document_as_stream) = self.add_original_document_as_stream {
-            form = form.text(
-                "addOriginalDocumentAsStream",
-                add_original_document_as_stream.to_string(),
-            );
+    fn fill_request(self, request: GotenbergRequest) -> Result<GotenbergRequest, Error> {
+        let mut request = self.request_headers().fill_request(request);
+
+        for (name, value) in self.split_mode.form_fields() {
+            request = request.text(name, value);
         }
 
-        if let Some(single_page_sheets) = self.single_page_sheets {
-            form = form.text("singlePageSheets", single_page_sheets.to_string());
+        if let Some(split_unify) = self.split_unify {
+            request = request.text("splitUnify", split_unify.to_string());
         }
 
-        if let Some(lossless_image_compression) = self.lossless_image_compression {
-            form = form.text(
-                "losslessImageCompression",
-                lossless_image_compression.to_string(),
-            );
+        if let Some(pdfa) = self.pdfa {
+            request = request.text("pdfa", pdfa.to_string());
         }
 
-        if let Some(quality) = self.quality {
-            form = form.text("quality", quality.to_string());
+        if let Some(pdfua) = self.pdfua {
+            request = request.text("pdfua", pdfua.to_string());
         }
 
-        if let Some(reduce_image_resolution) = self.reduce_image_resolution {
-            form = form.text("reduceImageResolution", reduce_image_resolution.to_string());
+        if let Some(metadata) = self.metadata {
+            request = request.text("metadata", serde_json::to_string(&metadata).unwrap());
         }
 
-        if let Some(max_image_resolution) = self.max_image_resolution {
-            form = form.text("maxImageResolution", max_image_resolution.to_string());
+        if let Some(flatten) = self.flatten {
+            request = request.text("flatten", flatten.to_string());
         }
 
-        if let Some(pdfa) = self.pdfa {
-            form = form.text("pdfa", pdfa.to_string());
+        request = request::password_fields(request, self.user_password, self.owner_password)?;
+
+        for attachment in self.embeds {
+            request = request.file(attachment.form_file("embeds")?);
         }
 
-        if let Some(pdfua) = self.pdfua {
-            form = form.text("pdfua", pdfua.to_string());
+        Ok(request)
+    }
+}
+
+/// A file to embed into a PDF, eg. the XML invoice of a ZUGFeRD / Factur-X document.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Attachment {
+    /// Name of the embedded file, eg. `factur-x.xml`.
+    pub filename: String,
+
+    /// MIME type of the embedded file, eg. `text/xml`.
+    /// An invalid MIME type fails the request with an [`Error::ParseError`].
+    pub mime_type: String,
+
+    /// File contents.
+    pub bytes: Vec<u8>,
+}
+
+impl Attachment {
+    /// Create a new attachment.
+    pub fn new(filename: &str, mime_type: &str, bytes: Vec<u8>) -> Self {
+        Attachment {
+            filename: filename.to_string(),
+            mime_type: mime_type.to_string(),
+            bytes,
         }
+    }
+
+    /// The MIME type to send, checked to be a valid one.
+    fn valid_mime_type(&self) -> Result<mime::Mime, Error> {
+        self.mime_type.parse().map_err(|e: mime::FromStrError| {
+            Error::ParseError(
+                "MIME type".to_string(),
+                self.mime_type.clone(),
+                e.to_string(),
+            )
+        })
+    }
 
-        form
+    fn form_file(self, name: &str) -> Result<FormFile, Error> {
+        let mime_type = self.valid_mime_type()?;
+        Ok(FormFile::new(name, &self.filename, self.bytes).content_type(mime_type.as_ref()))
     }
 }
 
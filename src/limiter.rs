@@ -0,0 +1,208 @@
+use super::*;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+/// Maximum number of concurrent requests per engine.
+///
+/// Chromium and LibreOffice only process a limited number of requests at once and answer `503 Service Unavailable`
+/// or restart when flooded. Limiting the requests on the client side queues them locally instead, so batch jobs
+/// apply backpressure rather than overloading the server. Match the limits to the server configuration,
+/// eg. `--libreoffice-max-queue-size` and `--chromium-max-queue-size`.
+///
+/// Routes of an engine without a limit are not queued. Limits are kept between 1 and [`tokio::sync::Semaphore::MAX_PERMITS`].
+///
+/// # Example
+///
+/// ```
+/// use gotenberg_pdf::{Client, ConcurrencyLimits};
+///
+/// let client = Client::new("http://localhost:3000").concurrency_limits(ConcurrencyLimits {
+///     chromium: Some(4),
+///     libreoffice: Some(1),
+///     ..Default::default()
+/// });
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ConcurrencyLimits {
+    /// Limit for the Chromium routes: URL, HTML and Markdown conversions and screenshots.
+    /// Default: None
+    pub chromium: Option<usize>,
+
+    /// Limit for the LibreOffice routes: document conversions.
+    /// Default: None
+    pub libreoffice: Option<usize>,
+
+    /// Limit for the PDF engines routes: merging, splitting, encrypting, flattening, etc.
+    /// Default: None
+    pub pdf_engines: Option<usize>,
+}
+
+/// A snapshot of the requests queued by the limiter of one engine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LimiterStats {
+    /// The configured maximum number of concurrent requests.
+    pub limit: usize,
+
+    /// Number of requests currently being sent.
+    pub in_flight: usize,
+
+    /// Number of requests currently waiting for a free slot.
+    pub queued: usize,
+
+    /// Number of requests that got a slot since the client was created.
+    pub acquired: u64,
+
+    /// Total time requests spent waiting for a slot.
+    pub total_wait: Duration,
+
+    /// Longest time a single request waited for a slot.
+    pub max_wait: Duration,
+}
+
+impl LimiterStats {
+    /// The average time a request waited for a slot.
+    pub fn average_wait(&self) -> Duration {
+        match self.acquired {
+            0 => Duration::ZERO,
+            acquired => self.total_wait / acquired.min(u32::MAX as u64) as u32,
+        }
+    }
+}
+
+/// The limiters of all engines, shared by the clones of a client.
+#[derive(Debug, Default)]
+pub(crate) struct Limiters {
+    chromium: Option<Arc<Limiter>>,
+    libreoffice: Option<Arc<Limiter>>,
+    pdf_engines: Option<Arc<Limiter>>,
+}
+
+impl Limiters {
+    pub(crate) fn new(limits: ConcurrencyLimits) -> Self {
+        let limiter = |limit: Option<usize>| limit.map(|limit| Arc::new(Limiter::new(limit)));
+        Limiters {
+            chromium: limiter(limits.chromium),
+            libreoffice: limiter(limits.libreoffice),
+            pdf_engines: limiter(limits.pdf_engines),
+        }
+    }
+
+    pub(crate) fn get(&self, engine: Engine) -> Option<&Limiter> {
+        match engine {
+            Engine::Chromium => self.chromium.as_deref(),
+            Engine::LibreOffice => self.libreoffice.as_deref(),
+            Engine::PdfEngines => self.pdf_engines.as_deref(),
+        }
+    }
+}
+
+/// A semaphore that keeps track of how long requests wait for it.
+#[derive(Debug)]
+pub(crate) struct Limiter {
+    limit: usize,
+    semaphore: Semaphore,
+    queued: AtomicUsize,
+    acquired: AtomicU64,
+    total_wait_nanos: AtomicU64,
+    max_wait_nanos: AtomicU64,
+}
+
+/// Removes a request from the queue when dropped, including when the waiting future is cancelled.
+struct Queued<'a>(&'a AtomicUsize);
+
+impl Drop for Queued<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl Limiter {
+    fn new(limit: usize) -> Self {
+        // A limit of zero would block every request forever, and the semaphore cannot hold more than `MAX_PERMITS`
+        let limit = limit.clamp(1, Semaphore::MAX_PERMITS);
+        Limiter {
+            limit,
+            semaphore: Semaphore::new(limit),
+            queued: AtomicUsize::new(0),
+            acquired: AtomicU64::new(0),
+            total_wait_nanos: AtomicU64::new(0),
+            max_wait_nanos: AtomicU64::new(0),
+        }
+    }
+
+    /// Wait for a free slot, which is released when the returned permit is dropped.
+    pub(crate) async fn acquire(&self) -> SemaphorePermit<'_> {
+        let started = Instant::now();
+        let permit = {
+            self.queued.fetch_add(1, Ordering::Relaxed);
+            let _queued = Queued(&self.queued);
+            self.semaphore
+                .acquire()
+                .await
+                .expect("the semaphore is never closed")
+        };
+
+        let waited = started.elapsed().as_nanos().min(u64::MAX as u128) as u64;
+        self.acquired.fetch_add(1, Ordering::Relaxed);
+        self.total_wait_nanos.fetch_add(waited, Ordering::Relaxed);
+        self.max_wait_nanos.fetch_max(waited, Ordering::Relaxed);
+
+        permit
+    }
+
+    pub(crate) fn stats(&self) -> LimiterStats {
+        LimiterStats {
+            limit: self.limit,
+            in_flight: self.limit - self.semaphore.available_permits(),
+            queued: self.queued.load(Ordering::Relaxed),
+            acquired: self.acquired.load(Ordering::Relaxed),
+            total_wait: Duration::from_nanos(self.total_wait_nanos.load(Ordering::Relaxed)),
+            max_wait: Duration::from_nanos(self.max_wait_nanos.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_limiter() {
+        let limiters = Limiters::new(ConcurrencyLimits {
+            libreoffice: Some(1),
+            ..Default::default()
+        });
+        assert!(limiters.get(Engine::Chromium).is_none());
+
+        let limiter = limiters.get(Engine::LibreOffice).unwrap();
+        let permit = limiter.acquire().await;
+        assert_eq!(limiter.stats().in_flight, 1);
+
+        let waiting = async {
+            let _permit = limiter.acquire().await;
+            limiter.stats()
+        };
+        let release = async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            assert_eq!(limiter.stats().queued, 1);
+            drop(permit);
+        };
+        let (stats, _) = tokio::join!(waiting, release);
+
+        assert_eq!(stats.queued, 0);
+        assert_eq!(stats.acquired, 2);
+        assert!(stats.max_wait >= Duration::from_millis(20));
+        assert_eq!(limiter.stats().in_flight, 0);
+    }
+
+    #[test]
+    fn test_limiter_out_of_range() {
+        assert_eq!(Limiter::new(0).stats().limit, 1);
+        assert_eq!(
+            Limiter::new(usize::MAX).stats().limit,
+            Semaphore::MAX_PERMITS
+        );
+    }
+}
@@ -0,0 +1,360 @@
+//! Parsing of the Prometheus metrics exposed by Gotenberg.
+
+use crate::Error;
+use std::collections::BTreeMap;
+
+/// The namespace of the metrics, unless changed with `--prometheus-namespace`.
+pub const DEFAULT_NAMESPACE: &str = "gotenberg";
+
+const CHROMIUM_REQUESTS_QUEUE_SIZE: &str = "chromium_requests_queue_size";
+const CHROMIUM_RESTARTS_COUNT: &str = "chromium_restarts_count";
+const LIBREOFFICE_REQUESTS_QUEUE_SIZE: &str = "libreoffice_requests_queue_size";
+const LIBREOFFICE_RESTARTS_COUNT: &str = "libreoffice_restarts_count";
+
+const KNOWN_METRICS: [&str; 4] = [
+    CHROMIUM_REQUESTS_QUEUE_SIZE,
+    CHROMIUM_RESTARTS_COUNT,
+    LIBREOFFICE_REQUESTS_QUEUE_SIZE,
+    LIBREOFFICE_RESTARTS_COUNT,
+];
+
+/// The metrics of a Gotenberg server.
+///
+/// The gauges are `None` if the server did not report them, eg. because the module is disabled.
+///
+/// # Example
+///
+/// ```
+/// use gotenberg_pdf::metrics::GotenbergMetrics;
+///
+/// let payload = "pdf_chromium_requests_queue_size 3\ngo_goroutines 42\n";
+/// let metrics = GotenbergMetrics::parse(payload).unwrap();
+///
+/// assert_eq!(metrics.namespace, "pdf");
+/// assert_eq!(metrics.chromium_requests_queue_size, Some(3));
+/// assert_eq!(metrics.other.get("go_goroutines"), Some(&42.0));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GotenbergMetrics {
+    /// The namespace of the Gotenberg metrics, as set with `--prometheus-namespace`.
+    pub namespace: String,
+
+    /// Current number of Chromium conversion requests waiting to be treated.
+    pub chromium_requests_queue_size: Option<u64>,
+
+    /// Current number of Chromium restarts.
+    pub chromium_restarts_count: Option<u64>,
+
+    /// Current number of LibreOffice conversion requests waiting to be treated.
+    pub libreoffice_requests_queue_size: Option<u64>,
+
+    /// Current number of LibreOffice restarts.
+    pub libreoffice_restarts_count: Option<u64>,
+
+    /// All other series, keyed by their name and labels as they appear in the payload, eg. `http_requests_total{code="200"}`.
+    pub other: BTreeMap<String, f64>,
+}
+
+impl GotenbergMetrics {
+    /// Parse the metrics in the Prometheus text exposition format, detecting the namespace.
+    ///
+    /// Falls back to [`DEFAULT_NAMESPACE`] if none of the Gotenberg gauges are present.
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let samples = parse_samples(text)?;
+        let namespace = samples
+            .iter()
+            .filter(|sample| sample.labels.is_empty())
+            .find_map(|sample| {
+                KNOWN_METRICS.iter().find_map(|metric| {
+                    sample
+                        .name
+                        .strip_suffix(metric)
+                        .and_then(|prefix| prefix.strip_suffix('_'))
+                })
+            })
+            .unwrap_or(DEFAULT_NAMESPACE)
+            .to_string();
+
+        Ok(Self::from_samples(samples, &namespace))
+    }
+
+    /// Parse the metrics in the Prometheus text exposition format, with the given namespace.
+    pub fn parse_with_namespace(text: &str, namespace: &str) -> Result<Self, Error> {
+        Ok(Self::from_samples(parse_samples(text)?, namespace))
+    }
+
+    fn from_samples(samples: Vec<Sample>, namespace: &str) -> Self {
+        let mut metrics = GotenbergMetrics {
+            namespace: namespace.to_string(),
+            ..Default::default()
+        };
+
+        for sample in samples {
+            let known = match sample.name.strip_prefix(namespace) {
+                Some(name) if sample.labels.is_empty() => name.strip_prefix('_'),
+                _ => None,
+            };
+            let gauge = match known {
+                Some(CHROMIUM_REQUESTS_QUEUE_SIZE) => &mut metrics.chromium_requests_queue_size,
+                Some(CHROMIUM_RESTARTS_COUNT) => &mut metrics.chromium_restarts_count,
+                Some(LIBREOFFICE_REQUESTS_QUEUE_SIZE) => {
+                    &mut metrics.libreoffice_requests_queue_size
+                }
+                Some(LIBREOFFICE_RESTARTS_COUNT) => &mut metrics.libreoffice_restarts_count,
+                _ => {
+                    metrics.other.insert(sample.series(), sample.value);
+                    continue;
+                }
+            };
+
+            // The gauges count requests and restarts, so they are whole numbers
+            *gauge = Some(sample.value.max(0.0) as u64);
+        }
+
+        metrics
+    }
+}
+
+/// A single sample of the Prometheus text exposition format.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    /// The metric name, eg. `gotenberg_chromium_restarts_count`.
+    pub name: String,
+
+    /// The labels as `(name, value)` pairs, in the order they appear in.
+    pub labels: Vec<(String, String)>,
+
+    /// The sample value.
+    pub value: f64,
+
+    /// The optional timestamp, in milliseconds since the epoch.
+    pub timestamp: Option<i64>,
+}
+
+impl Sample {
+    /// The name and labels of the series, eg. `http_requests_total{code="200",method="post"}`.
+    pub fn series(&self) -> String {
+        if self.labels.is_empty() {
+            return self.name.clone();
+        }
+
+        let labels: Vec<String> = self
+            .labels
+            .iter()
+            .map(|(name, value)| {
+                let value = value
+                    .replace('\\', "\\\\")
+                    .replace('"', "\\\"")
+                    .replace('\n', "\\n");
+                format!("{}=\"{}\"", name, value)
+            })
+            .collect();
+        format!("{}{{{}}}", self.name, labels.join(","))
+    }
+}
+
+/// Parse all samples of a payload in the Prometheus text exposition format, skipping comments.
+pub fn parse_samples(text: &str) -> Result<Vec<Sample>, Error> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            parse_sample(line)
+                .map_err(|error| Error::ParseError("Metrics".to_string(), line.to_string(), error))
+        })
+        .collect()
+}
+
+fn parse_sample(line: &str) -> Result<Sample, String> {
+    let name_end = line
+        .find(|c: char| c == '{' || c.is_whitespace())
+        .ok_or("missing value")?;
+    let (name, mut rest) = line.split_at(name_end);
+    if name.is_empty() {
+        return Err("missing metric name".to_string());
+    }
+
+    let mut labels = Vec::new();
+    if let Some(after_brace) = rest.strip_prefix('{') {
+        let (parsed, after_labels) = parse_labels(after_brace)?;
+        labels = parsed;
+        rest = after_labels;
+    }
+
+    let mut fields = rest.split_whitespace();
+    let value = fields.next().ok_or("missing value")?;
+    let value = parse_value(value).ok_or_else(|| format!("invalid value `{}`", value))?;
+    let timestamp = fields
+        .next()
+        .map(|timestamp| {
+            timestamp
+                .parse()
+                .map_err(|_| format!("invalid timestamp `{}`", timestamp))
+        })
+        .transpose()?;
+
+    Ok(Sample {
+        name: name.to_string(),
+        labels,
+        value,
+        timestamp,
+    })
+}
+
+type Labels = Vec<(String, String)>;
+
+/// Parse the labels after the opening brace, returning them with the rest of the line.
+fn parse_labels(input: &str) -> Result<(Labels, &str), String> {
+    let mut labels = Vec::new();
+    let mut rest = input.trim_start();
+
+    loop {
+        if let Some(after) = rest.strip_prefix('}') {
+            return Ok((labels, after));
+        }
+
+        let (name, after_name) = rest.split_once('=').ok_or("missing `=` in label")?;
+        let after_quote = after_name
+            .trim_start()
+            .strip_prefix('"')
+            .ok_or("label value must be quoted")?;
+
+        let mut value = String::new();
+        let mut chars = after_quote.char_indices();
+        let end = loop {
+            match chars.next().ok_or("unterminated label value")? {
+                (index, '"') => break index,
+                (_, '\\') => match chars.next().ok_or("unterminated label value")?.1 {
+                    'n' => value.push('\n'),
+                    other => value.push(other),
+                },
+                (_, c) => value.push(c),
+            }
+        };
+
+        labels.push((name.trim().to_string(), value));
+        rest = after_quote[end + 1..].trim_start();
+        rest = rest.strip_prefix(',').unwrap_or(rest).trim_start();
+    }
+}
+
+fn parse_value(value: &str) -> Option<f64> {
+    match value {
+        "+Inf" => Some(f64::INFINITY),
+        "-Inf" => Some(f64::NEG_INFINITY),
+        "NaN" => Some(f64::NAN),
+        _ => value.parse().ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_PAYLOAD: &str = r#"# HELP gotenberg_chromium_requests_queue_size Current number of Chromium conversion requests waiting to be treated.
+# TYPE gotenberg_chromium_requests_queue_size gauge
+gotenberg_chromium_requests_queue_size 2
+# HELP gotenberg_chromium_restarts_count Current number of Chromium restarts.
+# TYPE gotenberg_chromium_restarts_count gauge
+gotenberg_chromium_restarts_count 1
+# HELP gotenberg_libreoffice_requests_queue_size Current number of LibreOffice conversion requests waiting to be treated.
+# TYPE gotenberg_libreoffice_requests_queue_size gauge
+gotenberg_libreoffice_requests_queue_size 0
+# HELP gotenberg_libreoffice_restarts_count Current number of LibreOffice restarts.
+# TYPE gotenberg_libreoffice_restarts_count gauge
+gotenberg_libreoffice_restarts_count 4
+# HELP go_gc_duration_seconds A summary of the pause duration of garbage collection cycles.
+# TYPE go_gc_duration_seconds summary
+go_gc_duration_seconds{quantile="0"} 2.4e-05
+go_gc_duration_seconds{quantile="1"} 0.000187
+go_gc_duration_seconds_sum 0.001
+go_gc_duration_seconds_count 12
+promhttp_metric_handler_requests_total{code="200"} 5 1700000000000
+"#;
+
+    #[test]
+    fn test_parse() {
+        let metrics = GotenbergMetrics::parse(SAMPLE_PAYLOAD).unwrap();
+        assert_eq!(metrics.namespace, "gotenberg");
+        assert_eq!(metrics.chromium_requests_queue_size, Some(2));
+        assert_eq!(metrics.chromium_restarts_count, Some(1));
+        assert_eq!(metrics.libreoffice_requests_queue_size, Some(0));
+        assert_eq!(metrics.libreoffice_restarts_count, Some(4));
+
+        assert_eq!(metrics.other.len(), 5);
+        assert_eq!(
+            metrics.other.get("go_gc_duration_seconds{quantile=\"0\"}"),
+            Some(&2.4e-05)
+        );
+        assert_eq!(
+            metrics
+                .other
+                .get("promhttp_metric_handler_requests_total{code=\"200\"}"),
+            Some(&5.0)
+        );
+    }
+
+    #[test]
+    fn test_parse_custom_namespace() {
+        let payload = SAMPLE_PAYLOAD.replace("gotenberg_", "pdf_service_");
+        let metrics = GotenbergMetrics::parse(&payload).unwrap();
+        assert_eq!(metrics.namespace, "pdf_service");
+        assert_eq!(metrics.libreoffice_restarts_count, Some(4));
+
+        // With the wrong namespace, the gauges are kept as unknown series
+        let metrics = GotenbergMetrics::parse_with_namespace(&payload, "gotenberg").unwrap();
+        assert_eq!(metrics.libreoffice_restarts_count, None);
+        assert_eq!(
+            metrics.other.get("pdf_service_libreoffice_restarts_count"),
+            Some(&4.0)
+        );
+
+        // Without any Gotenberg gauges, the default namespace is assumed
+        let metrics = GotenbergMetrics::parse("go_goroutines 8").unwrap();
+        assert_eq!(metrics.namespace, DEFAULT_NAMESPACE);
+        assert_eq!(metrics.chromium_restarts_count, None);
+    }
+
+    #[test]
+    fn test_parse_samples() {
+        let samples = parse_samples(
+            "http_requests_total{method=\"post\", path=\"a \\\"quoted\\\" \\\\ path\\n\",} +Inf\n\
+             temperature -3.5e2 -1\n\
+             ratio NaN\n",
+        )
+        .unwrap();
+
+        assert_eq!(samples[0].name, "http_requests_total");
+        assert_eq!(
+            samples[0].labels,
+            vec![
+                ("method".to_string(), "post".to_string()),
+                ("path".to_string(), "a \"quoted\" \\ path\n".to_string()),
+            ]
+        );
+        assert_eq!(samples[0].value, f64::INFINITY);
+        assert_eq!(
+            samples[0].series(),
+            "http_requests_total{method=\"post\",path=\"a \\\"quoted\\\" \\\\ path\\n\"}"
+        );
+        assert_eq!(samples[1].value, -350.0);
+        assert_eq!(samples[1].timestamp, Some(-1));
+        assert!(samples[2].value.is_nan());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse_samples("metric_without_value").is_err());
+        assert!(parse_samples("metric{label=\"unterminated} 1").is_err());
+        assert!(parse_samples("metric{label=unquoted} 1").is_err());
+        assert!(parse_samples("metric one").is_err());
+
+        match parse_samples("ok 1\nbroken") {
+            Err(Error::ParseError(kind, line, _)) => {
+                assert_eq!(kind, "Metrics");
+                assert_eq!(line, "broken");
+            }
+            other => panic!("Expected a parse error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,867 @@
+//! A local stand-in for Gotenberg, to test code using the clients without Docker. Available when the `mock` feature is enabled.
+//!
+//! [`MockServer`] answers the Gotenberg routes over HTTP with canned files: a one page PDF, a one pixel image
+//! in the requested format, or a ZIP archive wherever Gotenberg returns several files. It checks the form the
+//! way Gotenberg does, records every request it receives, and fails on demand, see [`Fault`].
+//!
+//! Use [`transport::MockTransport`](crate::transport::MockTransport) instead to test without any network access.
+//!
+//! # Example
+//!
+//! ```
+//! use gotenberg_pdf::mock::{Fault, MockServer};
+//! use gotenberg_pdf::{Client, Error, RetryPolicy, WebOptions};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Error> {
+//! let server = MockServer::start()?;
+//! let client = Client::new(server.url()).retry_policy(RetryPolicy::new(2));
+//!
+//! server.fail_next(Fault::Status(503));
+//! let pdf = client
+//!     .pdf_from_url("https://example.com", WebOptions::default(), None)
+//!     .await?;
+//! assert!(pdf.starts_with(b"%PDF"));
+//!
+//! let requests = server.requests();
+//! assert_eq!(requests.len(), 2);
+//! assert_eq!(requests[1].endpoint, "forms/chromium/convert/url");
+//! assert!(requests[1].fields.contains(&("url".to_string(), "https://example.com".to_string())));
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::request::SECRET_FIELDS;
+use crate::{pdf_filename, Error, FormFile, GotenbergRequest};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::{BufRead, BufReader, Cursor, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// The PDF returned by the conversion routes, a single empty A4 page.
+pub const PDF: &[u8] = b"%PDF-1.7\n1 0 obj\n<< /Type /Catalog /Pages 2 0 R >>\nendobj\n2 0 obj\n<< /Type /Pages /Kids [3 0 R] /Count 1 >>\nendobj\n3 0 obj\n<< /Type /Page /Parent 2 0 R /MediaBox [0 0 595 842] >>\nendobj\nxref\n0 4\n0000000000 65535 f \n0000000009 00000 n \n0000000058 00000 n \n0000000115 00000 n \ntrailer\n<< /Size 4 /Root 1 0 R >>\nstartxref\n186\n%%EOF\n";
+
+/// The screenshot returned in the `png` format, a single white pixel.
+pub const PNG: &[u8] = b"\x89\x50\x4e\x47\x0d\x0a\x1a\x0a\x00\x00\x00\x0d\x49\x48\x44\x52\x00\x00\x00\x01\x00\x00\x00\x01\x08\x02\x00\x00\x00\x90\x77\x53\xde\x00\x00\x00\x0c\x49\x44\x41\x54\x78\x9c\x63\xf8\xff\xff\x3f\x00\x05\xfe\x02\xfe\x0d\xef\x46\xb8\x00\x00\x00\x00\x49\x45\x4e\x44\xae\x42\x60\x82";
+
+/// The screenshot returned in the `jpeg` format, a single grey pixel.
+pub const JPEG: &[u8] = b"\xff\xd8\xff\xdb\x00\x43\x00\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\x01\xff\xc0\x00\x0b\x08\x00\x01\x00\x01\x01\x01\x11\x00\xff\xc4\x00\x14\x00\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\xff\xc4\x00\x14\x10\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\xff\xda\x00\x08\x01\x01\x00\x00\x3f\x00\x3f\xff\xd9";
+
+/// The screenshot returned in the `webp` format, a single black pixel.
+pub const WEBP: &[u8] = b"\x52\x49\x46\x46\x16\x00\x00\x00\x57\x45\x42\x50\x56\x50\x38\x4c\x09\x00\x00\x00\x2f\x00\x00\x00\x00\x88\x88\xfe\x07\x00";
+
+/// The version reported by `/version`, which supports every [`Feature`](crate::Feature).
+pub const VERSION: &str = "8.20.0";
+
+/// A failure injected with [`MockServer::fail_next`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fault {
+    /// Answer with the given status code and a plain text message, like Gotenberg.
+    Status(u16),
+
+    /// Wait before answering as usual, eg. to trigger a timeout of the client.
+    Delay(Duration),
+
+    /// Close the connection without answering, like a server restarting in the middle of a request.
+    Reset,
+}
+
+/// A local HTTP server answering the Gotenberg routes, see the [module documentation](self).
+///
+/// The server listens on a free port of `127.0.0.1` until it is dropped. Every connection is handled on its own thread,
+/// so the server can be used from async and blocking tests alike.
+#[derive(Debug)]
+pub struct MockServer {
+    url: String,
+    address: SocketAddr,
+    state: Arc<Mutex<State>>,
+    shutdown: Arc<AtomicBool>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    requests: Vec<GotenbergRequest>,
+    faults: VecDeque<Fault>,
+}
+
+impl MockServer {
+    /// Start a server on a free port.
+    pub fn start() -> Result<Self, Error> {
+        let listener = TcpListener::bind("127.0.0.1:0").map_err(Error::IoError)?;
+        let address = listener.local_addr().map_err(Error::IoError)?;
+        let state = Arc::new(Mutex::new(State::default()));
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let (server_state, server_shutdown) = (state.clone(), shutdown.clone());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if server_shutdown.load(Ordering::SeqCst) {
+                    break;
+                }
+                let Ok(stream) = stream else { continue };
+                let state = server_state.clone();
+                thread::spawn(move || handle_connection(stream, &state));
+            }
+        });
+
+        Ok(MockServer {
+            url: format!("http://{}", address),
+            address,
+            state,
+            shutdown,
+        })
+    }
+
+    /// The base URL of the server, eg. `http://127.0.0.1:12345`, to create a client with.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// The requests received so far, in order, including the ones that failed.
+    ///
+    /// The endpoint of a request is its path, eg. `forms/chromium/convert/url` or `health`. Its headers are the
+    /// HTTP headers as received, with lowercase names.
+    pub fn requests(&self) -> Vec<GotenbergRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// Fail the next request with the given fault. Faults queued this way apply to the following requests, in order.
+    pub fn fail_next(&self, fault: Fault) {
+        self.state.lock().unwrap().faults.push_back(fault);
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        // Wake up the accept loop so it notices the shutdown
+        let _ = TcpStream::connect(self.address);
+    }
+}
+
+/// An HTTP request as read from the connection.
+struct HttpRequest {
+    method: String,
+    path: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl HttpRequest {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// An HTTP response to write back.
+struct Reply {
+    status: u16,
+    content_type: &'static str,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Reply {
+    fn new(status: u16, content_type: &'static str, body: impl Into<Vec<u8>>) -> Self {
+        Reply {
+            status,
+            content_type,
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    /// A failure with the plain text message Gotenberg sends.
+    fn error(status: u16, message: &str) -> Self {
+        Reply::new(status, "text/plain; charset=UTF-8", message)
+    }
+
+    /// A rendered file, named after the `Gotenberg-Output-Filename` header like Gotenberg does.
+    fn file(
+        request: &GotenbergRequest,
+        content_type: &'static str,
+        extension: &str,
+        body: Vec<u8>,
+    ) -> Self {
+        let name = header(request, "Gotenberg-Output-Filename")
+            .map(str::to_string)
+            .unwrap_or_else(crate::webhook::generate_trace_id);
+        let mut reply = Reply::new(200, content_type, body);
+        reply.headers.push((
+            "Content-Disposition".to_string(),
+            format!("attachment; filename=\"{}.{}\"", name, extension),
+        ));
+        reply
+    }
+
+    fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+fn handle_connection(stream: TcpStream, state: &Mutex<State>) {
+    let mut reader = BufReader::new(&stream);
+    let Ok(http) = read_request(&mut reader) else {
+        return;
+    };
+    let request = gotenberg_request(&http);
+
+    let fault = {
+        let mut state = state.lock().unwrap();
+        state.requests.push(request.clone());
+        state.faults.pop_front()
+    };
+    let mut reply = match fault {
+        Some(Fault::Reset) => return,
+        Some(Fault::Status(status)) => Reply::error(status, reason(status)),
+        Some(Fault::Delay(delay)) => {
+            thread::sleep(delay);
+            route(&http.method, &request)
+        }
+        None => route(&http.method, &request),
+    };
+
+    // Gotenberg echoes the trace, or generates one
+    let trace = request
+        .trace()
+        .map(str::to_string)
+        .unwrap_or_else(crate::webhook::generate_trace_id);
+    reply
+        .headers
+        .push(("Gotenberg-Trace".to_string(), trace.clone()));
+
+    // In webhook mode, Gotenberg answers right away and sends the result to the webhook
+    if header(&request, "Gotenberg-Webhook-Url").is_some() && fault.is_none() {
+        let mut accepted = Reply::new(204, "text/plain; charset=UTF-8", Vec::new());
+        accepted
+            .headers
+            .push(("Gotenberg-Trace".to_string(), trace));
+        let _ = write_reply(&stream, &accepted);
+        send_callback(&request, reply);
+        return;
+    }
+
+    let _ = write_reply(&stream, &reply);
+}
+
+/// Answer a request the way Gotenberg would, with canned files.
+fn route(method: &str, request: &GotenbergRequest) -> Reply {
+    let endpoint = request.endpoint.as_str();
+    match (method, endpoint) {
+        ("GET", "health") => Reply::new(200, "application/json", HEALTH),
+        ("GET", "version") => Reply::new(200, "text/plain; charset=UTF-8", VERSION),
+        ("GET", "prometheus/metrics") => Reply::new(200, "text/plain; version=0.0.4", METRICS),
+        ("POST", _) => convert(request),
+        _ => Reply::error(404, "Not Found"),
+    }
+}
+
+fn convert(request: &GotenbergRequest) -> Reply {
+    let endpoint = request.endpoint.as_str();
+    let pdf = |request| Reply::file(request, "application/pdf", "pdf", PDF.to_vec());
+
+    match endpoint {
+        "forms/chromium/convert/url" | "forms/chromium/screenshot/url" => {
+            if field(request, "url").is_none() {
+                return invalid("form field 'url' is required");
+            }
+        }
+        "forms/chromium/convert/html" | "forms/chromium/screenshot/html" => {
+            if file(request, "index.html").is_none() {
+                return invalid("form file 'index.html' is required");
+            }
+        }
+        "forms/chromium/convert/markdown" | "forms/chromium/screenshot/markdown" => {
+            if file(request, "index.html").is_none() {
+                return invalid("form file 'index.html' is required");
+            }
+            if !request
+                .files
+                .iter()
+                .any(|file| file.filename.ends_with(".md"))
+            {
+                return invalid("no form file found for extensions: [.md]");
+            }
+        }
+        "forms/libreoffice/convert"
+        | "forms/pdfengines/merge"
+        | "forms/pdfengines/split"
+        | "forms/pdfengines/convert"
+        | "forms/pdfengines/flatten"
+        | "forms/pdfengines/encrypt"
+        | "forms/pdfengines/embed"
+        | "forms/pdfengines/metadata/read"
+        | "forms/pdfengines/metadata/write" => {
+            if request.files.is_empty() && field(request, "downloadFrom").is_none() {
+                return invalid("no form file found");
+            }
+        }
+        _ => return Reply::error(404, "Not Found"),
+    }
+
+    match endpoint {
+        "forms/chromium/screenshot/url"
+        | "forms/chromium/screenshot/html"
+        | "forms/chromium/screenshot/markdown" => match field(request, "format").unwrap_or("png") {
+            "png" => Reply::file(request, "image/png", "png", PNG.to_vec()),
+            "jpeg" => Reply::file(request, "image/jpeg", "jpeg", JPEG.to_vec()),
+            "webp" => Reply::file(request, "image/webp", "webp", WEBP.to_vec()),
+            format => invalid(&format!(
+                "form field 'format' is invalid (got '{}')",
+                format
+            )),
+        },
+        "forms/libreoffice/convert" => {
+            let documents: Vec<&FormFile> = request
+                .files
+                .iter()
+                .filter(|file| file.name == "files")
+                .collect();
+            if documents.len() < 2 || field(request, "merge") == Some("true") {
+                return pdf(request);
+            }
+            let files = documents
+                .iter()
+                .map(|document| pdf_filename(&document.filename))
+                .collect();
+            Reply::file(request, "application/zip", "zip", zip(files))
+        }
+        "forms/pdfengines/split" => {
+            if field(request, "splitUnify") == Some("true") {
+                return pdf(request);
+            }
+            // Without parsing the PDF, intervals produce two files and page ranges one file per range
+            let count = match field(request, "splitMode") {
+                Some("pages") => field(request, "splitSpan")
+                    .unwrap_or_default()
+                    .split(',')
+                    .count(),
+                _ => 2,
+            };
+            let stem = request
+                .files
+                .first()
+                .map(|file| file.filename.trim_end_matches(".pdf").to_string())
+                .unwrap_or_else(|| "file".to_string());
+            let files = (0..count)
+                .map(|index| format!("{}_{}.pdf", stem, index))
+                .collect();
+            Reply::file(request, "application/zip", "zip", zip(files))
+        }
+        "forms/pdfengines/encrypt" if field(request, "userPassword").is_none() => {
+            invalid("form field 'userPassword' is required")
+        }
+        "forms/pdfengines/metadata/read" => {
+            let filename = request
+                .files
+                .first()
+                .map(|file| file.filename.as_str())
+                .unwrap_or("file.pdf");
+            let metadata = serde_json::json!({
+                filename: { "PageCount": 1, "Producer": "gotenberg_pdf mock" }
+            });
+            Reply::new(200, "application/json", metadata.to_string())
+        }
+        "forms/pdfengines/metadata/write" if field(request, "metadata").is_none() => {
+            invalid("form field 'metadata' is required")
+        }
+        _ => pdf(request),
+    }
+}
+
+fn invalid(message: &str) -> Reply {
+    Reply::error(400, &format!("Invalid form data: {}", message))
+}
+
+fn field<'a>(request: &'a GotenbergRequest, name: &str) -> Option<&'a str> {
+    let secrets = request.secrets.iter().map(|(n, v)| (n, v.expose()));
+    request
+        .fields
+        .iter()
+        .map(|(n, v)| (n, v.as_str()))
+        .chain(secrets)
+        .find(|(field, _)| *field == name)
+        .map(|(_, value)| value)
+}
+
+fn file<'a>(request: &'a GotenbergRequest, filename: &str) -> Option<&'a FormFile> {
+    request.files.iter().find(|file| file.filename == filename)
+}
+
+fn header<'a>(request: &'a GotenbergRequest, name: &str) -> Option<&'a str> {
+    request
+        .headers
+        .iter()
+        .find(|(header, _)| header.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+/// A ZIP archive holding the canned PDF under every filename.
+///
+/// A ZIP archive cannot hold two files with the same name, eg. `a.pdf` for `a.docx` and `a.odt`,
+/// so repeated names get a numbered suffix: `a.pdf`, `a_1.pdf`, `a_2.pdf`...
+fn zip(filenames: Vec<String>) -> Vec<u8> {
+    let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let mut used = HashSet::new();
+    for filename in filenames {
+        let filename = unique_filename(&mut used, filename);
+        let options = zip::write::SimpleFileOptions::default();
+        writer.start_file(filename, options).unwrap();
+        writer.write_all(PDF).unwrap();
+    }
+    writer.finish().unwrap().into_inner()
+}
+
+/// The filename, with a numbered suffix before its extension if it was already used.
+fn unique_filename(used: &mut HashSet<String>, filename: String) -> String {
+    let (stem, extension) = match filename.rfind('.') {
+        Some(dot) => filename.split_at(dot),
+        None => (filename.as_str(), ""),
+    };
+    let mut candidate = filename.clone();
+    let mut counter = 0;
+    while used.contains(&candidate) {
+        counter += 1;
+        candidate = format!("{}_{}{}", stem, counter, extension);
+    }
+    used.insert(candidate.clone());
+    candidate
+}
+
+/// Send the result to the webhook, or the failure to the error webhook, like Gotenberg does after answering.
+fn send_callback(request: &GotenbergRequest, reply: Reply) {
+    let (url, method) = match reply.is_success() {
+        true => ("Gotenberg-Webhook-Url", "Gotenberg-Webhook-Method"),
+        false => (
+            "Gotenberg-Webhook-Error-Url",
+            "Gotenberg-Webhook-Error-Method",
+        ),
+    };
+    let Some(url) = header(request, url).map(str::to_string) else {
+        return;
+    };
+    let method = header(request, method).unwrap_or("POST").to_string();
+
+    let mut headers = reply.headers.clone();
+    let extra_headers: HashMap<String, String> =
+        header(request, "Gotenberg-Webhook-Extra-Http-Headers")
+            .and_then(|value| serde_json::from_str(value).ok())
+            .unwrap_or_default();
+    headers.extend(extra_headers);
+
+    let (content_type, body) = match reply.is_success() {
+        true => (reply.content_type.to_string(), reply.body),
+        false => {
+            let message = String::from_utf8_lossy(&reply.body).to_string();
+            let payload = serde_json::json!({ "status": reply.status, "message": message });
+            (
+                "application/json".to_string(),
+                payload.to_string().into_bytes(),
+            )
+        }
+    };
+    headers.push(("Content-Type".to_string(), content_type));
+
+    thread::spawn(move || {
+        let _ = post(&url, &method, &headers, &body);
+    });
+}
+
+/// Send a request over plain HTTP, ignoring the response.
+fn post(url: &str, method: &str, headers: &[(String, String)], body: &[u8]) -> std::io::Result<()> {
+    let invalid_url = || std::io::Error::other(format!("unsupported webhook URL `{}`", url));
+    let rest = url.strip_prefix("http://").ok_or_else(invalid_url)?;
+    let (host, path) = match rest.find('/') {
+        Some(index) => rest.split_at(index),
+        None => (rest, "/"),
+    };
+    let address = match host.contains(':') {
+        true => host.to_string(),
+        false => format!("{}:80", host),
+    };
+
+    let mut stream = TcpStream::connect(address)?;
+    let mut head = format!(
+        "{} {} HTTP/1.1\r\nHost: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        method,
+        path,
+        host,
+        body.len()
+    );
+    for (name, value) in headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(body)?;
+    stream.read_to_end(&mut Vec::new())?;
+    Ok(())
+}
+
+/// Read the request line, the headers and the body, with a `Content-Length` or chunked.
+fn read_request(reader: &mut impl BufRead) -> std::io::Result<HttpRequest> {
+    let malformed = || std::io::Error::new(std::io::ErrorKind::InvalidData, "malformed request");
+
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next().ok_or_else(malformed)?.to_string();
+    let target = parts.next().ok_or_else(malformed)?;
+    let path = target
+        .split('?')
+        .next()
+        .unwrap_or_default()
+        .trim_start_matches('/')
+        .to_string();
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(malformed());
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        let (name, value) = line.split_once(':').ok_or_else(malformed)?;
+        headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+    }
+    let mut request = HttpRequest {
+        method,
+        path,
+        headers,
+        body: Vec::new(),
+    };
+
+    if request
+        .header("Transfer-Encoding")
+        .is_some_and(|value| value.eq_ignore_ascii_case("chunked"))
+    {
+        loop {
+            let mut size = String::new();
+            reader.read_line(&mut size)?;
+            let size = size.trim().split(';').next().unwrap_or_default();
+            let size = usize::from_str_radix(size, 16).map_err(|_| malformed())?;
+            let mut chunk = vec![0; size + 2];
+            reader.read_exact(&mut chunk)?;
+            if size == 0 {
+                break;
+            }
+            request.body.extend_from_slice(&chunk[..size]);
+        }
+    } else if let Some(length) = request.header("Content-Length") {
+        let length = length.parse().map_err(|_| malformed())?;
+        request.body = vec![0; length];
+        reader.read_exact(&mut request.body)?;
+    }
+
+    Ok(request)
+}
+
+/// Turn an HTTP request into the fields and files of its multipart form.
+fn gotenberg_request(http: &HttpRequest) -> GotenbergRequest {
+    let mut request = GotenbergRequest::new(&http.path);
+    request.headers = http.headers.clone();
+
+    let boundary = http
+        .header("Content-Type")
+        .and_then(|value| {
+            value
+                .split(';')
+                .find_map(|param| param.trim().strip_prefix("boundary="))
+        })
+        .map(|boundary| format!("--{}", boundary.trim_matches('"')));
+    let Some(boundary) = boundary else {
+        return request;
+    };
+
+    // Every part sits between two delimiters, the last one being followed by `--`
+    let mut parts = split(&http.body, boundary.as_bytes()).into_iter().skip(1);
+    while let Some(part) = parts.next().filter(|part| !part.starts_with(b"--")) {
+        let part = part.strip_prefix(b"\r\n").unwrap_or(part);
+        let part = part.strip_suffix(b"\r\n").unwrap_or(part);
+        let Some(end) = find(part, b"\r\n\r\n") else {
+            continue;
+        };
+        let (head, content) = (String::from_utf8_lossy(&part[..end]), &part[end + 4..]);
+
+        let mut name = None;
+        let mut filename = None;
+        let mut content_type = None;
+        for line in head.lines() {
+            let Some((header, value)) = line.split_once(':') else {
+                continue;
+            };
+            if header.trim().eq_ignore_ascii_case("Content-Type") {
+                content_type = Some(value.trim().to_string());
+            } else if header.trim().eq_ignore_ascii_case("Content-Disposition") {
+                name = parameter(value, "name");
+                filename = parameter(value, "filename");
+            }
+        }
+
+        match (name, filename) {
+            (Some(name), Some(filename)) => request.files.push(FormFile {
+                name,
+                filename,
+                content_type,
+                bytes: content.to_vec().into(),
+            }),
+            (Some(name), None) => {
+                let value = String::from_utf8_lossy(content).to_string();
+                match SECRET_FIELDS.contains(&name.as_str()) {
+                    true => request.secrets.push((name, value.into())),
+                    false => request.fields.push((name, value)),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    request
+}
+
+/// Read a quoted parameter of a `Content-Disposition` header.
+fn parameter(value: &str, name: &str) -> Option<String> {
+    value.split(';').find_map(|param| {
+        let (key, value) = param.trim().split_once('=')?;
+        (key == name).then(|| value.trim_matches('"').to_string())
+    })
+}
+
+fn split<'a>(bytes: &'a [u8], delimiter: &[u8]) -> Vec<&'a [u8]> {
+    let mut parts = Vec::new();
+    let mut rest = bytes;
+    while let Some(index) = find(rest, delimiter) {
+        parts.push(&rest[..index]);
+        rest = &rest[index + delimiter.len()..];
+    }
+    parts.push(rest);
+    parts
+}
+
+fn find(bytes: &[u8], needle: &[u8]) -> Option<usize> {
+    bytes
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+fn write_reply(mut stream: &TcpStream, reply: &Reply) -> std::io::Result<()> {
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        reply.status,
+        reason(reply.status),
+        reply.content_type,
+        reply.body.len()
+    );
+    for (name, value) in &reply.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(&reply.body)?;
+    stream.flush()
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        204 => "No Content",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        500 => "Internal Server Error",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        _ => "Error",
+    }
+}
+
+const HEALTH: &str = r#"{"status":"up","details":{"chromium":{"status":"up","timestamp":"2025-01-01T00:00:00Z"},"libreoffice":{"status":"up","timestamp":"2025-01-01T00:00:00Z"}}}"#;
+
+const METRICS: &str = "# HELP gotenberg_chromium_requests_queue_size Current number of Chromium conversion requests waiting to be treated.
+# TYPE gotenberg_chromium_requests_queue_size gauge
+gotenberg_chromium_requests_queue_size 0
+# HELP gotenberg_chromium_restarts_count Current number of Chromium restarts.
+# TYPE gotenberg_chromium_restarts_count gauge
+gotenberg_chromium_restarts_count 0
+# HELP gotenberg_libreoffice_requests_queue_size Current number of LibreOffice conversion requests waiting to be treated.
+# TYPE gotenberg_libreoffice_requests_queue_size gauge
+gotenberg_libreoffice_requests_queue_size 0
+# HELP gotenberg_libreoffice_restarts_count Current number of LibreOffice restarts.
+# TYPE gotenberg_libreoffice_restarts_count gauge
+gotenberg_libreoffice_restarts_count 0
+";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Client, ErrorKind, OutputKind, ScreenshotOptions, WebOptions};
+
+    #[tokio::test]
+    async fn test_routes() {
+        let server = MockServer::start().unwrap();
+        let client = Client::new(server.url());
+
+        let options = WebOptions {
+            output_filename: Some("invoice".to_string()),
+            trace_id: Some("abc".to_string()),
+            ..Default::default()
+        };
+        let output = client
+            .pdf_from_html_with_response("<html></html>", options, None)
+            .await
+            .unwrap();
+        assert_eq!(&output.bytes[..], PDF);
+        assert_eq!(output.filename.as_deref(), Some("invoice.pdf"));
+        assert_eq!(output.trace.as_deref(), Some("abc"));
+
+        let options = ScreenshotOptions {
+            format: Some(crate::ImageFormat::Jpeg),
+            ..Default::default()
+        };
+        let output = client
+            .screenshot_url_with_response("https://example.com", options, None)
+            .await
+            .unwrap();
+        assert_eq!(output.kind(), OutputKind::Image);
+        assert_eq!(&output.bytes[..], JPEG);
+
+        // Several documents come back as a ZIP archive
+        let documents = vec![
+            ("a.docx".to_string(), b"a".to_vec()),
+            ("b.odt".to_string(), b"b".to_vec()),
+        ];
+        let converted = client
+            .pdfs_from_docs(documents, Default::default(), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            converted,
+            crate::ConvertedDocuments::Files(vec![
+                ("a.pdf".to_string(), PDF.into()),
+                ("b.pdf".to_string(), PDF.into()),
+            ])
+        );
+
+        // Documents converted to the same filename do not collide in the archive
+        let documents = vec![
+            ("a.docx".to_string(), b"a".to_vec()),
+            ("a.odt".to_string(), b"a".to_vec()),
+        ];
+        let converted = client
+            .pdfs_from_docs(documents, Default::default(), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            converted,
+            crate::ConvertedDocuments::Files(vec![
+                ("a.pdf".to_string(), PDF.into()),
+                ("a_1.pdf".to_string(), PDF.into()),
+            ])
+        );
+
+        let metadata = client.read_metadata(PDF.to_vec()).await.unwrap();
+        assert_eq!(metadata["PageCount"], 1);
+        assert_eq!(client.version_typed().await.unwrap().to_string(), VERSION);
+        assert!(client.health_check().await.is_ok());
+        assert!(client.metrics_typed().await.is_ok());
+
+        // The form is checked like Gotenberg does
+        let result = client
+            .pdf_from_markdown("<html></html>", HashMap::new(), WebOptions::default(), None)
+            .await;
+        match result {
+            Err(Error::RenderingError(error)) => assert_eq!(error.kind, ErrorKind::InvalidFormData),
+            result => panic!("Expected invalid form data, got {:?}", result),
+        }
+
+        let requests = server.requests();
+        assert_eq!(requests[0].endpoint, "forms/chromium/convert/html");
+        assert_eq!(requests[0].files[0].filename, "index.html");
+        assert_eq!(&requests[0].files[0].bytes[..], b"<html></html>");
+        assert_eq!(requests[0].trace(), Some("abc"));
+        assert!(requests[1]
+            .fields
+            .contains(&("format".to_string(), "jpeg".to_string())));
+    }
+
+    #[tokio::test]
+    async fn test_faults() {
+        let server = MockServer::start().unwrap();
+        let reqwest_client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(200))
+            .build()
+            .unwrap();
+        let client = Client::new_with_client(server.url(), reqwest_client);
+
+        server.fail_next(Fault::Status(503));
+        server.fail_next(Fault::Reset);
+        server.fail_next(Fault::Delay(Duration::from_secs(1)));
+
+        let result = client.flatten_pdf(PDF.to_vec(), None).await;
+        assert!(
+            matches!(result, Err(Error::RenderingError(error)) if error.kind == ErrorKind::Unavailable)
+        );
+
+        let result = client.flatten_pdf(PDF.to_vec(), None).await;
+        assert!(matches!(result, Err(Error::CommunicationError(_))));
+
+        let result = client.flatten_pdf(PDF.to_vec(), None).await;
+        assert!(matches!(result, Err(Error::CommunicationError(error)) if error.is_timeout()));
+
+        // Faults only apply once
+        let pdf = client.flatten_pdf(PDF.to_vec(), None).await.unwrap();
+        assert_eq!(&pdf[..], PDF);
+        assert_eq!(server.requests().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_webhook() {
+        let server = MockServer::start().unwrap();
+        let receiver = MockServer::start().unwrap();
+        let client = Client::new(server.url());
+        let webhook = crate::WebhookOptions {
+            url: format!("{}/webhook/success", receiver.url()),
+            error_url: format!("{}/webhook/failure", receiver.url()),
+            ..Default::default()
+        };
+
+        let output = client
+            .pdf_from_url_with_response(
+                "https://example.com",
+                WebOptions::default(),
+                Some(&webhook),
+            )
+            .await
+            .unwrap();
+        assert!(output.is_empty());
+
+        // The result is sent to the webhook once the request was answered
+        let mut callbacks = receiver.requests();
+        for _ in 0..50 {
+            if !callbacks.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            callbacks = receiver.requests();
+        }
+        assert_eq!(callbacks[0].endpoint, "webhook/success");
+        assert_eq!(callbacks[0].trace(), output.trace.as_deref());
+        assert_eq!(
+            header(&callbacks[0], "Content-Type"),
+            Some("application/pdf")
+        );
+    }
+}
@@ -0,0 +1,166 @@
+use crate::transport::{
+    BlockingTransport, BlockingTransportResponse, Transport, TransportFuture, TransportRequest,
+    TransportResponse,
+};
+use crate::{Bytes, Error, FormFile, GotenbergRequest};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// A [`Transport`] and [`BlockingTransport`] answering from memory, for tests.
+///
+/// Responses queued with [`MockTransport::respond_with`] are returned in order, and a minimal PDF
+/// once the queue is empty. Every request is recorded, see [`MockTransport::requests`]. GET requests, eg. health checks,
+/// take their response from the same queue and are recorded with an empty request.
+/// Clones share their queue and recorded requests, so keep a clone to inspect the requests sent by a client.
+#[derive(Debug, Clone, Default)]
+pub struct MockTransport {
+    state: Arc<Mutex<MockState>>,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    responses: VecDeque<MockResponse>,
+    requests: Vec<RecordedRequest>,
+}
+
+/// A canned response of a [`MockTransport`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockResponse {
+    /// The HTTP status code.
+    pub status: u16,
+
+    /// The response headers.
+    pub headers: HeaderMap,
+
+    /// The response body.
+    pub body: Bytes,
+}
+
+/// A request received by a [`MockTransport`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedRequest {
+    /// The URL the request was sent to.
+    pub url: String,
+
+    /// The request. Streamed uploads are read into memory and appended to its files.
+    pub request: GotenbergRequest,
+}
+
+impl MockResponse {
+    /// Create a response with the given status code and body.
+    pub fn new(status: u16, body: impl Into<Bytes>) -> Self {
+        MockResponse {
+            status,
+            headers: HeaderMap::new(),
+            body: body.into(),
+        }
+    }
+
+    /// A successful response with a PDF.
+    pub fn pdf(bytes: impl Into<Bytes>) -> Self {
+        MockResponse::new(200, bytes).header("Content-Type", "application/pdf")
+    }
+
+    /// Add a header, consuming the current response and returning the updated one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name or the value is not a valid header.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.append(
+            HeaderName::from_bytes(name.as_bytes()).expect("invalid header name"),
+            HeaderValue::from_str(value).expect("invalid header value"),
+        );
+        self
+    }
+}
+
+impl MockResponse {
+    /// The response of a [`Transport`].
+    fn into_stream(self) -> TransportResponse {
+        TransportResponse {
+            status: self.status,
+            headers: self.headers,
+            body: Box::pin(futures_util::stream::iter([Ok(self.body)])),
+        }
+    }
+
+    /// The response of a [`BlockingTransport`].
+    fn into_reader(self) -> BlockingTransportResponse {
+        BlockingTransportResponse {
+            status: self.status,
+            headers: self.headers,
+            body: Box::new(std::io::Cursor::new(self.body)),
+        }
+    }
+}
+
+impl Default for MockResponse {
+    fn default() -> Self {
+        MockResponse::pdf("%PDF-1.7\n%%EOF\n")
+    }
+}
+
+impl MockTransport {
+    /// Create a transport answering every request with a minimal PDF.
+    pub fn new() -> Self {
+        MockTransport::default()
+    }
+
+    /// Queue a response, consuming the current transport and returning the updated one.
+    pub fn respond_with(self, response: MockResponse) -> Self {
+        self.state.lock().unwrap().responses.push_back(response);
+        self
+    }
+
+    /// The requests received so far, in order.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// Record the request and pick its response.
+    fn answer(&self, url: &str, request: GotenbergRequest) -> MockResponse {
+        let mut state = self.state.lock().unwrap();
+        state.requests.push(RecordedRequest {
+            url: url.to_string(),
+            request,
+        });
+        state.responses.pop_front().unwrap_or_default()
+    }
+}
+
+impl Transport for MockTransport {
+    fn send(&self, request: TransportRequest) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let mut recorded = request.request;
+            for upload in request.uploads {
+                let file = FormFile::new(&upload.name, &upload.filename, Bytes::new());
+                recorded = recorded.file(FormFile {
+                    bytes: upload.bytes().await?,
+                    ..file
+                });
+            }
+
+            Ok(self.answer(&request.url, recorded).into_stream())
+        })
+    }
+
+    fn get(&self, url: String) -> TransportFuture<'_> {
+        Box::pin(async move { Ok(self.answer(&url, GotenbergRequest::default()).into_stream()) })
+    }
+}
+
+impl BlockingTransport for MockTransport {
+    fn send(
+        &self,
+        url: &str,
+        request: &GotenbergRequest,
+    ) -> Result<BlockingTransportResponse, Error> {
+        Ok(self.answer(url, request.clone()).into_reader())
+    }
+
+    fn get(&self, url: &str) -> Result<BlockingTransportResponse, Error> {
+        Ok(self.answer(url, GotenbergRequest::default()).into_reader())
+    }
+}
@@ -0,0 +1,307 @@
+use super::*;
+use reqwest::header::{CONTENT_DISPOSITION, CONTENT_TYPE};
+
+/// The kind of file returned by Gotenberg.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputKind {
+    /// A single PDF file.
+    Pdf,
+    /// A ZIP archive containing several files.
+    Zip,
+    /// A screenshot image.
+    Image,
+    /// No content, as returned in webhook mode.
+    Empty,
+    /// Any other content type.
+    Other,
+}
+
+impl OutputKind {
+    /// The kind of file announced by a `Content-Type`, if it is one Gotenberg returns.
+    pub(crate) fn from_content_type(content_type: Option<&str>) -> Option<OutputKind> {
+        let content_type = content_type?.split(';').next()?.trim().to_ascii_lowercase();
+        match content_type.as_str() {
+            "application/pdf" => Some(OutputKind::Pdf),
+            "application/zip" => Some(OutputKind::Zip),
+            content_type if content_type.starts_with("image/") => Some(OutputKind::Image),
+            _ => None,
+        }
+    }
+}
+
+/// A rendered file together with the details Gotenberg sent in the response headers.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() -> Result<(), gotenberg_pdf::Error> {
+/// use gotenberg_pdf::{Client, OutputKind, WebOptions};
+///
+/// let client = Client::new("http://localhost:3000");
+/// let options = WebOptions {
+///     output_filename: Some("invoice".to_string()),
+///     ..Default::default()
+/// };
+///
+/// let output = client
+///     .pdf_from_url_with_response("https://example.com", options, None)
+///     .await?;
+/// assert_eq!(output.kind(), OutputKind::Pdf);
+/// assert_eq!(output.filename.as_deref(), Some("invoice.pdf"));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RenderOutput {
+    /// The response body.
+    pub bytes: Bytes,
+
+    /// The `Content-Type` of the response, eg. `application/pdf`.
+    pub content_type: Option<String>,
+
+    /// The filename from the `Content-Disposition` header, as set with `output_filename`.
+    pub filename: Option<String>,
+
+    /// The trace of the request, echoed back in the `Gotenberg-Trace` header.
+    pub trace: Option<String>,
+}
+
+impl RenderOutput {
+    pub(crate) fn from_parts(headers: &HeaderMap, bytes: Bytes) -> Self {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string())
+        };
+
+        RenderOutput {
+            bytes,
+            content_type: header(CONTENT_TYPE.as_str()),
+            filename: header(CONTENT_DISPOSITION.as_str())
+                .and_then(|value| content_disposition_filename(&value)),
+            trace: header("Gotenberg-Trace"),
+        }
+    }
+
+    /// The number of bytes in the response body.
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Check if the response body is empty, which is the case in webhook mode.
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// The kind of file returned, based on the content type and falling back to the file signature.
+    pub fn kind(&self) -> OutputKind {
+        match OutputKind::from_content_type(self.content_type.as_deref()) {
+            _ if self.bytes.is_empty() => OutputKind::Empty,
+            Some(kind) => kind,
+            None if self.bytes.starts_with(b"%PDF") => OutputKind::Pdf,
+            None if archive::is_zip(&self.bytes) => OutputKind::Zip,
+            None => OutputKind::Other,
+        }
+    }
+
+    /// The ticket to match the webhook callback of the request with, if the request was sent in webhook mode.
+    ///
+    /// Requests with webhook options always carry a trace, which is generated if `trace_id` is not set.
+    pub fn webhook_ticket(&self) -> Option<webhook::WebhookTicket> {
+        match (self.kind(), &self.trace) {
+            (OutputKind::Empty, Some(trace)) => Some(webhook::WebhookTicket {
+                trace_id: trace.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// The returned files as `(filename, bytes)` tuples, unpacking a ZIP archive if needed.
+    ///
+    /// A single file is named after the `Content-Disposition` filename, or `output` if there is none.
+    pub fn files(&self) -> Result<Vec<(String, Bytes)>, Error> {
+        let filename = self.filename.as_deref().unwrap_or("output");
+        archive::files_from_response(self.bytes.clone(), filename)
+    }
+}
+
+/// A streamed file together with the details Gotenberg sent in the response headers, see [`RenderOutput`].
+/// Available with the `stream` feature enabled.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() -> Result<(), gotenberg_pdf::Error> {
+/// use futures::StreamExt;
+/// use gotenberg_pdf::{StreamingClient, WebOptions};
+///
+/// let client = StreamingClient::new("http://localhost:3000");
+/// let options = WebOptions {
+///     output_filename: Some("invoice".to_string()),
+///     ..Default::default()
+/// };
+///
+/// let mut output = client
+///     .pdf_from_url_with_response("https://example.com", options, None)
+///     .await?;
+/// assert_eq!(output.filename.as_deref(), Some("invoice.pdf"));
+/// while let Some(chunk) = output.stream.next().await {
+///     let chunk = chunk.map_err(gotenberg_pdf::Error::CommunicationError)?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "stream")]
+#[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+pub struct StreamingOutput {
+    /// The response body.
+    pub stream: std::pin::Pin<Box<dyn futures::Stream<Item = Result<Bytes, ReqwestError>> + Send>>,
+
+    /// The `Content-Type` of the response, eg. `application/pdf`.
+    pub content_type: Option<String>,
+
+    /// The filename from the `Content-Disposition` header, as set with `output_filename`.
+    pub filename: Option<String>,
+
+    /// The trace of the request, echoed back in the `Gotenberg-Trace` header.
+    pub trace: Option<String>,
+
+    /// Whether the server answered with 204 No Content, as it does in webhook mode.
+    no_content: bool,
+}
+
+#[cfg(feature = "stream")]
+impl StreamingOutput {
+    /// Keep the headers of the response and stream its body.
+    ///
+    /// `trace` is the trace the request was sent with, used when the response does not echo it.
+    pub(crate) fn from_response(response: reqwest::Response, trace: Option<String>) -> Self {
+        let output = RenderOutput::from_parts(response.headers(), Bytes::new());
+        StreamingOutput {
+            content_type: output.content_type,
+            filename: output.filename,
+            trace: output.trace.or(trace),
+            no_content: response.status() == reqwest::StatusCode::NO_CONTENT,
+            stream: Box::pin(response.bytes_stream()),
+        }
+    }
+
+    /// The ticket to match the webhook callback of the request with, if the request was sent in webhook mode.
+    ///
+    /// See [`RenderOutput::webhook_ticket`].
+    pub fn webhook_ticket(&self) -> Option<webhook::WebhookTicket> {
+        match (self.no_content, &self.trace) {
+            (true, Some(trace)) => Some(webhook::WebhookTicket {
+                trace_id: trace.clone(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "stream")]
+impl Debug for StreamingOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StreamingOutput")
+            .field("content_type", &self.content_type)
+            .field("filename", &self.filename)
+            .field("trace", &self.trace)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Extract the filename from a `Content-Disposition` header value.
+fn content_disposition_filename(value: &str) -> Option<String> {
+    let mut filename = None;
+
+    for parameter in value.split(';').map(str::trim) {
+        let Some((name, value)) = parameter.split_once('=') else {
+            continue;
+        };
+
+        match name.trim().to_ascii_lowercase().as_str() {
+            // RFC 5987 encoded filename, eg. `filename*=UTF-8''my%20file.pdf`, takes precedence
+            "filename*" => {
+                if let Some((_, encoded)) = value.split_once("''") {
+                    return Some(percent_decode(encoded));
+                }
+            }
+            "filename" => filename = Some(value.trim().trim_matches('"').to_string()),
+            _ => {}
+        }
+    }
+
+    filename.filter(|filename| !filename.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, value.parse().unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn test_from_parts() {
+        let output = RenderOutput::from_parts(
+            &headers(&[
+                ("content-type", "application/pdf"),
+                (
+                    "content-disposition",
+                    "attachment; filename=\"invoice.pdf\"",
+                ),
+                ("gotenberg-trace", "abc"),
+            ]),
+            Bytes::from_static(b"%PDF-1.7"),
+        );
+
+        assert_eq!(output.kind(), OutputKind::Pdf);
+        assert_eq!(output.filename.as_deref(), Some("invoice.pdf"));
+        assert_eq!(output.trace.as_deref(), Some("abc"));
+        assert_eq!(output.len(), 8);
+        assert_eq!(
+            output.files().unwrap(),
+            vec![("invoice.pdf".to_string(), Bytes::from_static(b"%PDF-1.7"))]
+        );
+    }
+
+    #[test]
+    fn test_kind() {
+        let output = |content_type: &'static str, bytes: &'static [u8]| {
+            RenderOutput::from_parts(
+                &headers(&[("content-type", content_type)]),
+                Bytes::from_static(bytes),
+            )
+        };
+
+        assert_eq!(output("image/png", b"\x89PNG").kind(), OutputKind::Image);
+        assert_eq!(output("application/zip", b"PK").kind(), OutputKind::Zip);
+        assert_eq!(output("text/plain", b"").kind(), OutputKind::Empty);
+        assert_eq!(
+            output("application/octet-stream", b"%PDF").kind(),
+            OutputKind::Pdf
+        );
+        assert_eq!(output("text/plain", b"hello").kind(), OutputKind::Other);
+    }
+
+    #[test]
+    fn test_content_disposition_filename() {
+        assert_eq!(
+            content_disposition_filename("attachment; filename=report.pdf"),
+            Some("report.pdf".to_string())
+        );
+        assert_eq!(
+            content_disposition_filename(
+                "attachment; filename=\"fallback.pdf\"; filename*=UTF-8''my%20report.pdf"
+            ),
+            Some("my report.pdf".to_string())
+        );
+        assert_eq!(content_disposition_filename("inline"), None);
+    }
+}
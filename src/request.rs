@@ -0,0 +1,761 @@
+use super::*;
+
+/// A request to Gotenberg, independent of the HTTP client sending it.
+///
+/// Every client builds its requests with the constructors below, then turns them into its own multipart form,
+/// so all clients send the exact same fields for the same options. Use them to send requests through your own HTTP stack:
+/// POST a `multipart/form-data` body with the `fields`, `secrets` and `files` to `{base_url}/{endpoint}`, along with the `headers`.
+///
+/// # Example
+///
+/// ```
+/// use gotenberg_pdf::{GotenbergRequest, WebOptions};
+///
+/// let options = WebOptions {
+///     trace_id: Some("my-trace".to_string()),
+///     landscape: Some(true),
+///     ..Default::default()
+/// };
+/// let request = GotenbergRequest::pdf_from_url("https://example.com", options)?;
+///
+/// assert_eq!(request.endpoint, "forms/chromium/convert/url");
+/// assert!(request.fields.contains(&("landscape".to_string(), "true".to_string())));
+/// assert_eq!(request.trace(), Some("my-trace"));
+/// # Ok::<(), gotenberg_pdf::Error>(())
+/// ```
+#[derive(Clone, Default, PartialEq)]
+pub struct GotenbergRequest {
+    /// The route, relative to the base URL of the server, eg. `forms/chromium/convert/url`.
+    pub endpoint: String,
+
+    /// The text fields of the form, in order.
+    pub fields: Vec<(String, String)>,
+
+    /// The text fields holding passwords, sent after the other fields.
+    ///
+    /// They are kept as [`Secret`] until the body is encoded, so they are redacted from `Debug` output
+    /// and, with the `zeroize` feature enabled, zeroized when the request is dropped.
+    pub secrets: Vec<(String, Secret)>,
+
+    /// The files of the form, in order.
+    pub files: Vec<FormFile>,
+
+    /// The HTTP headers, eg. `Gotenberg-Trace`.
+    pub headers: Vec<(String, String)>,
+
+    /// The features the server needs to support, checked against [`Client::server_version`].
+    pub(crate) features: Vec<Feature>,
+}
+
+/// A file of a [`GotenbergRequest`].
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct FormFile {
+    /// The name of the form field, eg. `files`.
+    pub name: String,
+
+    /// The filename sent to Gotenberg, eg. `document.docx`.
+    pub filename: String,
+
+    /// The MIME type of the file, if any.
+    pub content_type: Option<String>,
+
+    /// File contents.
+    pub bytes: Bytes,
+}
+
+impl FormFile {
+    /// Create a new file without a MIME type.
+    pub fn new(name: &str, filename: &str, bytes: impl Into<Bytes>) -> Self {
+        FormFile {
+            name: name.to_string(),
+            filename: filename.to_string(),
+            content_type: None,
+            bytes: bytes.into(),
+        }
+    }
+
+    /// Set the MIME type of the file.
+    pub fn content_type(self, content_type: &str) -> Self {
+        FormFile {
+            content_type: Some(content_type.to_string()),
+            ..self
+        }
+    }
+
+    fn part(&self) -> Result<multipart::Part, Error> {
+        #[cfg(not(target_arch = "wasm32"))]
+        let part = multipart::Part::stream_with_length(self.bytes.clone(), self.bytes.len() as u64);
+        #[cfg(target_arch = "wasm32")]
+        let part = multipart::Part::bytes(self.bytes.to_vec());
+
+        let part = part.file_name(self.filename.clone());
+        match &self.content_type {
+            Some(content_type) => part.mime_str(content_type).map_err(Into::into),
+            None => Ok(part),
+        }
+    }
+
+    #[cfg(feature = "blocking")]
+    fn blocking_part(&self) -> Result<reqwest::blocking::multipart::Part, Error> {
+        let part = reqwest::blocking::multipart::Part::reader_with_length(
+            std::io::Cursor::new(self.bytes.clone()),
+            self.bytes.len() as u64,
+        )
+        .file_name(self.filename.clone());
+        match &self.content_type {
+            Some(content_type) => part.mime_str(content_type).map_err(Into::into),
+            None => Ok(part),
+        }
+    }
+}
+
+/// Form fields holding passwords, stored in [`GotenbergRequest::secrets`].
+#[cfg(all(feature = "mock", not(target_arch = "wasm32")))]
+pub(crate) const SECRET_FIELDS: [&str; 3] = ["password", "userPassword", "ownerPassword"];
+
+impl Debug for GotenbergRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The `Authorization` header is redacted like the secrets
+        let headers: Vec<(&str, &str)> = self
+            .headers
+            .iter()
+            .map(
+                |(name, value)| match name.eq_ignore_ascii_case("Authorization") {
+                    true => (name.as_str(), "***"),
+                    false => (name.as_str(), value.as_str()),
+                },
+            )
+            .collect();
+        f.debug_struct("GotenbergRequest")
+            .field("endpoint", &self.endpoint)
+            .field("fields", &self.fields)
+            .field("secrets", &self.secrets)
+            .field("files", &self.files)
+            .field("headers", &headers)
+            .field("features", &self.features)
+            .finish()
+    }
+}
+
+impl Debug for FormFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FormFile")
+            .field("name", &self.name)
+            .field("filename", &self.filename)
+            .field("content_type", &self.content_type)
+            .field("length", &self.bytes.len())
+            .finish()
+    }
+}
+
+impl GotenbergRequest {
+    /// Create an empty request to the given route.
+    pub fn new(endpoint: &str) -> Self {
+        GotenbergRequest {
+            endpoint: endpoint.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Add a text field, consuming the current request and returning the updated one.
+    pub fn text(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.fields.push((name.into(), value.into()));
+        self
+    }
+
+    /// Add a text field holding a password, consuming the current request and returning the updated one.
+    pub fn secret(mut self, name: impl Into<String>, value: impl Into<Secret>) -> Self {
+        self.secrets.push((name.into(), value.into()));
+        self
+    }
+
+    /// Add a file, consuming the current request and returning the updated one.
+    pub fn file(mut self, file: FormFile) -> Self {
+        self.files.push(file);
+        self
+    }
+
+    /// Add an HTTP header, consuming the current request and returning the updated one.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// The trace of the request, sent as the `Gotenberg-Trace` header.
+    pub fn trace(&self) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("Gotenberg-Trace"))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The features the server needs to support to handle the request.
+    pub fn features(&self) -> &[Feature] {
+        &self.features
+    }
+
+    /// Add the headers of the webhook options, so the server uploads the result instead of answering with it.
+    ///
+    /// A trace is generated if the request has none, so the callback can be matched with the request.
+    pub fn webhook(self, webhook: Option<&WebhookOptions>) -> Result<Self, Error> {
+        let Some(webhook) = webhook else {
+            return Ok(self);
+        };
+
+        let mut request = self;
+        for (name, value) in webhook.to_headers()?.iter() {
+            let value = String::from_utf8_lossy(value.as_bytes()).to_string();
+            request = request.header(name.as_str(), value);
+        }
+        if request.trace().is_none() {
+            request = request.header("Gotenberg-Trace", webhook::generate_trace_id());
+        }
+
+        Ok(request)
+    }
+
+    pub(crate) fn require(mut self, feature: Option<Feature>) -> Self {
+        self.features.extend(feature);
+        self
+    }
+
+    /// Build the multipart form of the async clients.
+    pub(crate) fn form(&self) -> Result<multipart::Form, Error> {
+        let mut form = multipart::Form::new();
+        for (name, value) in &self.fields {
+            form = form.text(name.clone(), value.clone());
+        }
+        for (name, value) in &self.secrets {
+            form = form.text(name.clone(), value.expose().to_string());
+        }
+        for file in &self.files {
+            form = form.part(file.name.clone(), file.part()?);
+        }
+        Ok(form)
+    }
+
+    /// Build the multipart form of the blocking client.
+    #[cfg(feature = "blocking")]
+    pub(crate) fn blocking_form(&self) -> Result<reqwest::blocking::multipart::Form, Error> {
+        let mut form = reqwest::blocking::multipart::Form::new();
+        for (name, value) in &self.fields {
+            form = form.text(name.clone(), value.clone());
+        }
+        for (name, value) in &self.secrets {
+            form = form.text(name.clone(), value.expose().to_string());
+        }
+        for file in &self.files {
+            form = form.part(file.name.clone(), file.blocking_part()?);
+        }
+        Ok(form)
+    }
+
+    /// Encode the fields and files into a `multipart/form-data` body, for HTTP clients without a multipart encoder.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gotenberg_pdf::GotenbergRequest;
+    ///
+    /// let request = GotenbergRequest::flatten_pdf(b"%PDF-1.7".to_vec());
+    /// let body = request.multipart_body();
+    ///
+    /// assert!(body.content_type().starts_with("multipart/form-data; boundary="));
+    /// assert_eq!(body.content_length(), body.to_bytes().len() as u64);
+    /// ```
+    pub fn multipart_body(&self) -> MultipartBody {
+        let mut body = MultipartBody {
+            boundary: format!("gotenberg-pdf-{}", webhook::generate_trace_id()),
+            chunks: Vec::new(),
+        };
+        let secrets = self
+            .secrets
+            .iter()
+            .map(|(name, value)| (name, value.expose()));
+        for (name, value) in self
+            .fields
+            .iter()
+            .map(|(n, v)| (n, v.as_str()))
+            .chain(secrets)
+        {
+            let header = format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n",
+                body.boundary,
+                escape_quoted(name)
+            );
+            body.chunks.push(Bytes::from(header));
+            body.chunks.push(Bytes::from(format!("{}\r\n", value)));
+        }
+        for file in &self.files {
+            let content_type = file.content_type.as_deref();
+            body.chunks
+                .push(body.file_header(&file.name, &file.filename, content_type));
+            body.chunks.push(file.bytes.clone());
+            body.chunks.push(Bytes::from_static(b"\r\n"));
+        }
+        body
+    }
+}
+
+/// A `multipart/form-data` encoding of a [`GotenbergRequest`], see [`GotenbergRequest::multipart_body`].
+///
+/// The files are not copied until the body is turned into bytes.
+#[derive(Clone)]
+pub struct MultipartBody {
+    boundary: String,
+    /// Every part, without the closing delimiter.
+    chunks: Vec<Bytes>,
+}
+
+impl MultipartBody {
+    /// The boundary between the parts.
+    pub fn boundary(&self) -> &str {
+        &self.boundary
+    }
+
+    /// The `Content-Type` header to send with the body.
+    pub fn content_type(&self) -> String {
+        format!("multipart/form-data; boundary={}", self.boundary)
+    }
+
+    /// The length of the body in bytes, sent as the `Content-Length` header.
+    pub fn content_length(&self) -> u64 {
+        let parts: usize = self.chunks.iter().map(Bytes::len).sum();
+        (parts + self.closing().len()) as u64
+    }
+
+    /// Concatenate the parts into a single buffer.
+    pub fn to_bytes(&self) -> Bytes {
+        let mut bytes = bytes::BytesMut::with_capacity(self.content_length() as usize);
+        for chunk in &self.chunks {
+            bytes.extend_from_slice(chunk);
+        }
+        bytes.extend_from_slice(&self.closing());
+        bytes.freeze()
+    }
+
+    /// The parts and the closing delimiter, so streamed files can be sent in between.
+    #[cfg(all(feature = "hyper", not(target_arch = "wasm32")))]
+    pub(crate) fn into_chunks(self) -> (Vec<Bytes>, Bytes) {
+        let closing = self.closing();
+        (self.chunks, closing)
+    }
+
+    /// The headers of a file part.
+    pub(crate) fn file_header(
+        &self,
+        name: &str,
+        filename: &str,
+        content_type: Option<&str>,
+    ) -> Bytes {
+        Bytes::from(format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\nContent-Type: {}\r\n\r\n",
+            self.boundary,
+            escape_quoted(name),
+            escape_quoted(filename),
+            content_type.unwrap_or("application/octet-stream")
+        ))
+    }
+
+    fn closing(&self) -> Bytes {
+        Bytes::from(format!("--{}--\r\n", self.boundary))
+    }
+}
+
+impl Debug for MultipartBody {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MultipartBody")
+            .field("boundary", &self.boundary)
+            .field("content_length", &self.content_length())
+            .finish()
+    }
+}
+
+/// Escape a name or filename for a quoted header parameter, the way browsers do.
+fn escape_quoted(value: &str) -> String {
+    value
+        .replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// The error for a downloaded file among files merged in order, as the server picks its filename.
+fn merge_download_error(download: &DownloadFrom) -> Error {
+    Error::FilenameError(format!(
+        "Merged files must be uploaded to keep their order: {}",
+        download.url
+    ))
+}
+
+/// The requests of every route.
+impl GotenbergRequest {
+    /// Convert a URL to a PDF using the Chromium engine.
+    pub fn pdf_from_url(url: &str, options: WebOptions) -> Result<Self, Error> {
+        let request = GotenbergRequest::new("forms/chromium/convert/url").text("url", url);
+        options.fill_request(request)
+    }
+
+    /// Convert HTML to a PDF using the Chromium engine.
+    pub fn pdf_from_html(html: impl Into<HtmlBundle>, options: WebOptions) -> Result<Self, Error> {
+        let request = html
+            .into()
+            .fill_request(GotenbergRequest::new("forms/chromium/convert/html"))?;
+        options.fill_request(request)
+    }
+
+    /// Convert Markdown to a PDF using the Chromium engine. Every markdown filename must end with `.md`.
+    pub fn pdf_from_markdown(
+        html_template: impl Into<HtmlBundle>,
+        markdown: HashMap<&str, &str>,
+        options: WebOptions,
+    ) -> Result<Self, Error> {
+        let request = GotenbergRequest::new("forms/chromium/convert/markdown");
+        let request = markdown_files(html_template.into().fill_request(request)?, markdown)?;
+        options.fill_request(request)
+    }
+
+    /// Take a screenshot of a webpage using the Chromium engine.
+    pub fn screenshot_url(url: &str, options: ScreenshotOptions) -> Self {
+        let request = GotenbergRequest::new("forms/chromium/screenshot/url").text("url", url);
+        options.fill_request(request)
+    }
+
+    /// Take a screenshot of an HTML page using the Chromium engine.
+    pub fn screenshot_html(
+        html: impl Into<HtmlBundle>,
+        options: ScreenshotOptions,
+    ) -> Result<Self, Error> {
+        let request = html
+            .into()
+            .fill_request(GotenbergRequest::new("forms/chromium/screenshot/html"))?;
+        Ok(options.fill_request(request))
+    }
+
+    /// Take a screenshot of a set of markdown files using the Chromium engine.
+    pub fn screenshot_markdown(
+        html_template: impl Into<HtmlBundle>,
+        markdown: HashMap<&str, &str>,
+        options: ScreenshotOptions,
+    ) -> Result<Self, Error> {
+        let request = GotenbergRequest::new("forms/chromium/screenshot/markdown");
+        let request = markdown_files(html_template.into().fill_request(request)?, markdown)?;
+        Ok(options.fill_request(request))
+    }
+
+    /// Convert a document to a PDF using the LibreOffice engine.
+    ///
+    /// The document is either uploaded with its filename, eg. `("report.docx", bytes)`, or a [`DownloadFrom`] the server downloads.
+    pub fn pdf_from_doc(
+        file: impl Into<NamedFile>,
+        options: DocumentOptions,
+    ) -> Result<Self, Error> {
+        let request = Self::pdf_from_doc_fields(options)?;
+        Ok(match file.into() {
+            NamedFile::Bytes { filename, bytes } => {
+                request.file(FormFile::new("files", &filename, bytes))
+            }
+            NamedFile::DownloadFrom(download) => input_file::add_download(request, download, false),
+        })
+    }
+
+    /// The request of [`GotenbergRequest::pdf_from_doc`] without the document, for streamed uploads.
+    pub(crate) fn pdf_from_doc_fields(options: DocumentOptions) -> Result<Self, Error> {
+        options.fill_request(GotenbergRequest::new("forms/libreoffice/convert"))
+    }
+
+    /// Convert several documents to PDF in a single request using the LibreOffice engine.
+    ///
+    /// With `options.merge` set, the filenames are prefixed with their position, so the documents are merged in order.
+    /// Downloaded documents are named by the server, so merging them fails with an [`Error::FilenameError`].
+    pub fn pdfs_from_docs(
+        files: Vec<impl Into<NamedFile>>,
+        options: DocumentOptions,
+    ) -> Result<Self, Error> {
+        let merge = options.merge.unwrap_or(false);
+        let mut request = GotenbergRequest::new("forms/libreoffice/convert");
+        for (index, file) in files.into_iter().enumerate() {
+            request = match file.into() {
+                NamedFile::Bytes { filename, bytes } => {
+                    let filename = match merge {
+                        true => ordered_filename(index, &filename),
+                        false => filename,
+                    };
+                    request.file(FormFile::new("files", &filename, bytes))
+                }
+                NamedFile::DownloadFrom(download) => {
+                    if merge {
+                        return Err(merge_download_error(&download));
+                    }
+                    input_file::add_download(request, download, false)
+                }
+            };
+        }
+        options.fill_request(request)
+    }
+
+    /// Transform a PDF file into the requested PDF/A format and/or PDF/UA.
+    pub fn convert_pdf(pdf: impl Into<InputFile>, pdfa: Option<PDFFormat>, pdfua: bool) -> Self {
+        let request = Self::convert_pdf_fields(pdfa, pdfua);
+        pdf.into().fill_request(request, "file.pdf", "file.pdf")
+    }
+
+    /// The request of [`GotenbergRequest::convert_pdf`] without the PDF, for streamed uploads.
+    pub(crate) fn convert_pdf_fields(pdfa: Option<PDFFormat>, pdfua: bool) -> Self {
+        let mut request = GotenbergRequest::new("forms/pdfengines/convert");
+        if let Some(pdfa) = pdfa {
+            request = request.text("pdfa", pdfa.to_string());
+        }
+        request.text("pdfua", pdfua.to_string())
+    }
+
+    /// Merge several PDF files into a single PDF. Every filename must end with `.pdf`.
+    ///
+    /// A [`DownloadFrom`] fails the request with an [`Error::FilenameError`]: the server names downloaded
+    /// files itself, so their place in the merge could not be kept.
+    pub fn merge_pdfs(
+        files: Vec<impl Into<NamedFile>>,
+        options: MergeOptions,
+    ) -> Result<Self, Error> {
+        let mut request = GotenbergRequest::new("forms/pdfengines/merge");
+        for (index, file) in files.into_iter().enumerate() {
+            request = match file.into() {
+                NamedFile::Bytes { filename, bytes } => {
+                    let filename = merge_filename(index, &filename)?;
+                    let file =
+                        FormFile::new("files", &filename, bytes).content_type("application/pdf");
+                    request.file(file)
+                }
+                NamedFile::DownloadFrom(download) => {
+                    return Err(merge_download_error(&download));
+                }
+            };
+        }
+        options.fill_request(request)
+    }
+
+    /// Split a PDF file into several PDF files.
+    pub fn split_pdf(pdf: impl Into<InputFile>, options: SplitOptions) -> Result<Self, Error> {
+        let request = GotenbergRequest::new("forms/pdfengines/split");
+        let request = pdf.into().fill_request(request, "file.pdf", "file.pdf");
+        options.fill_request(request)
+    }
+
+    /// Encrypt a PDF file with a user password and an optional owner password.
+    pub fn encrypt_pdf(
+        pdf: impl Into<InputFile>,
+        user_password: impl Into<Secret>,
+        owner_password: Option<Secret>,
+    ) -> Self {
+        let request = GotenbergRequest::new("forms/pdfengines/encrypt");
+        let request = pdf.into().fill_request(request, "file.pdf", "file.pdf");
+        let request = request.secret("userPassword", user_password.into());
+        match owner_password {
+            Some(owner_password) => request.secret("ownerPassword", owner_password),
+            None => request,
+        }
+    }
+
+    /// Flatten a PDF file, merging its form fields and annotations into the page content.
+    pub fn flatten_pdf(pdf: impl Into<InputFile>) -> Self {
+        let request = GotenbergRequest::new("forms/pdfengines/flatten");
+        pdf.into().fill_request(request, "file.pdf", "file.pdf")
+    }
+
+    /// Embed files into a PDF as attachments.
+    pub fn embed_files(
+        pdf: impl Into<InputFile>,
+        attachments: Vec<impl Into<EmbeddedFile>>,
+    ) -> Result<Self, Error> {
+        let request = GotenbergRequest::new("forms/pdfengines/embed");
+        let mut request = pdf.into().fill_request(request, "file.pdf", "file.pdf");
+        for attachment in attachments {
+            request = attachment.into().fill_request(request)?;
+        }
+        Ok(request)
+    }
+
+    /// Read the metadata of a PDF file.
+    pub fn read_metadata(pdf: impl Into<InputFile>) -> Self {
+        let request = GotenbergRequest::new("forms/pdfengines/metadata/read");
+        pdf.into().fill_request(request, "file.pdf", "file.pdf")
+    }
+
+    /// Write metadata to a PDF file.
+    pub fn write_metadata(
+        pdf: impl Into<InputFile>,
+        metadata: HashMap<String, serde_json::Value>,
+    ) -> Result<Self, Error> {
+        let request = Self::write_metadata_fields(metadata)?;
+        Ok(pdf.into().fill_request(request, "file.pdf", "file.pdf"))
+    }
+
+    /// The request of [`GotenbergRequest::write_metadata`] without the PDF, for streamed uploads.
+    pub(crate) fn write_metadata_fields(
+        metadata: HashMap<String, serde_json::Value>,
+    ) -> Result<Self, Error> {
+        let metadata = serde_json::to_string(&metadata).map_err(|e| {
+            Error::ParseError("Metadata".to_string(), "".to_string(), e.to_string())
+        })?;
+        Ok(GotenbergRequest::new("forms/pdfengines/metadata/write").text("metadata", metadata))
+    }
+}
+
+/// Add the `userPassword` and `ownerPassword` secrets.
+///
+/// Gotenberg ignores an owner password without a user password and returns an unencrypted PDF,
+/// so an owner password alone is rejected.
+pub(crate) fn password_fields(
+    request: GotenbergRequest,
+    user_password: Option<Secret>,
+    owner_password: Option<Secret>,
+) -> Result<GotenbergRequest, Error> {
+    match (user_password, owner_password) {
+        (Some(user_password), Some(owner_password)) => Ok(request
+            .secret("userPassword", user_password)
+            .secret("ownerPassword", owner_password)),
+        (Some(user_password), None) => Ok(request.secret("userPassword", user_password)),
+        (None, Some(_)) => Err(Error::ParseError(
+            "Passwords".to_string(),
+            "ownerPassword".to_string(),
+            "an owner password requires a user password".to_string(),
+        )),
+        (None, None) => Ok(request),
+    }
+}
+
+fn markdown_files(
+    request: GotenbergRequest,
+    markdown: HashMap<&str, &str>,
+) -> Result<GotenbergRequest, Error> {
+    let mut request = request;
+    for (filename, content) in markdown {
+        if !filename.ends_with(".md") {
+            return Err(Error::FilenameError(
+                "Markdown filename must end with '.md'".to_string(),
+            ));
+        }
+        let file =
+            FormFile::new(filename, filename, content.to_string()).content_type("text/markdown");
+        request = request.file(file);
+    }
+    Ok(request)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_options_are_encoded_once() {
+        let options = DocumentOptions {
+            trace_id: Some("doc-trace".to_string()),
+            output_filename: Some("report".to_string()),
+            landscape: Some(true),
+            flatten: Some(true),
+            ..Default::default()
+        };
+        let request =
+            GotenbergRequest::pdf_from_doc(("report.docx", b"doc".to_vec()), options).unwrap();
+
+        assert_eq!(request.endpoint, "forms/libreoffice/convert");
+        assert!(request
+            .fields
+            .contains(&("landscape".to_string(), "true".to_string())));
+        assert_eq!(request.files[0].name, "files");
+        assert_eq!(request.files[0].filename, "report.docx");
+        assert_eq!(request.trace(), Some("doc-trace"));
+        assert!(request.headers.contains(&(
+            "Gotenberg-Output-Filename".to_string(),
+            "report".to_string()
+        )));
+        assert_eq!(request.features(), &[Feature::Flatten]);
+    }
+
+    #[test]
+    fn test_webhook() {
+        let request = GotenbergRequest::flatten_pdf(b"%PDF".to_vec());
+        assert_eq!(request.clone().webhook(None).unwrap(), request);
+
+        let webhook = WebhookOptions {
+            url: "https://example.com/success".to_string(),
+            error_url: "https://example.com/error".to_string(),
+            ..Default::default()
+        };
+        let request = request.webhook(Some(&webhook)).unwrap();
+        assert!(request
+            .headers
+            .contains(&("gotenberg-webhook-url".to_string(), webhook.url.clone())));
+        assert!(request.trace().is_some());
+    }
+
+    #[test]
+    fn test_debug_redacts_passwords() {
+        let request = GotenbergRequest::encrypt_pdf(b"%PDF".to_vec(), "open-sesame", None);
+        let debug = format!("{:?}", request);
+        assert!(!debug.contains("open-sesame"));
+        assert!(debug.contains(r#"("userPassword", Secret(***))"#));
+        assert!(debug.contains("length: 4"));
+
+        // The secrets are only exposed when the body is encoded
+        assert!(request.fields.is_empty());
+        let body = request.multipart_body().to_bytes();
+        assert!(std::str::from_utf8(&body).unwrap().contains("open-sesame"));
+    }
+
+    #[test]
+    fn test_owner_password_requires_user_password() {
+        let options = WebOptions {
+            owner_password: Some("owner".into()),
+            ..Default::default()
+        };
+        let result = GotenbergRequest::pdf_from_url("https://example.com", options);
+        assert!(matches!(result, Err(Error::ParseError(_, field, _)) if field == "ownerPassword"));
+
+        let options = WebOptions {
+            user_password: Some("user".into()),
+            owner_password: Some("owner".into()),
+            ..Default::default()
+        };
+        let request = GotenbergRequest::pdf_from_url("https://example.com", options).unwrap();
+        let names: Vec<&str> = request
+            .secrets
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert_eq!(names, vec!["userPassword", "ownerPassword"]);
+    }
+
+    #[test]
+    fn test_multipart_body() {
+        let request = GotenbergRequest::new("forms/pdfengines/merge")
+            .text("pdfua", "true")
+            .file(
+                FormFile::new("files", "a \"1\".pdf", b"%PDF".to_vec())
+                    .content_type("application/pdf"),
+            )
+            .header("Authorization", "Basic dXNlcjpwYXNz");
+        let body = request.multipart_body();
+        let boundary = body.boundary().to_string();
+
+        let expected = format!(
+            "--{b}\r\nContent-Disposition: form-data; name=\"pdfua\"\r\n\r\ntrue\r\n\
+             --{b}\r\nContent-Disposition: form-data; name=\"files\"; filename=\"a %221%22.pdf\"\r\n\
+             Content-Type: application/pdf\r\n\r\n%PDF\r\n\
+             --{b}--\r\n",
+            b = boundary
+        );
+        assert_eq!(body.to_bytes(), expected.as_bytes());
+        assert_eq!(body.content_length(), expected.len() as u64);
+
+        // The credentials are not shown in debug output
+        assert!(!format!("{:?}", request).contains("dXNlcjpwYXNz"));
+    }
+
+    #[test]
+    fn test_markdown_filenames() {
+        let markdown = HashMap::from([("notes.txt", "# Notes")]);
+        let result =
+            GotenbergRequest::pdf_from_markdown("<html></html>", markdown, Default::default());
+        assert!(matches!(result, Err(Error::FilenameError(_))));
+    }
+}
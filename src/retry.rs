@@ -0,0 +1,228 @@
+use super::*;
+use std::time::Duration;
+
+/// Policy for retrying requests that failed for a transient reason.
+///
+/// Gotenberg answers `503 Service Unavailable` while Chromium or LibreOffice restarts or when its queue is full,
+/// and drops connections once `--api-timeout` is reached. A retry policy re-sends such requests with an
+/// exponential backoff between attempts.
+///
+/// Retries are disabled unless a policy is set on the client.
+///
+/// # Example
+///
+/// ```
+/// use gotenberg_pdf::{Client, ErrorKind, RetryPolicy};
+/// use std::time::Duration;
+///
+/// let policy = RetryPolicy {
+///     max_attempts: 5,
+///     initial_backoff: Duration::from_secs(1),
+///     retryable_kinds: vec![ErrorKind::Unavailable, ErrorKind::Conflict],
+///     ..Default::default()
+/// };
+///
+/// let client = Client::new("http://localhost:3000").retry_policy(policy);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first one.
+    /// Default: `3`
+    pub max_attempts: u32,
+
+    /// Delay before the first retry.
+    /// Default: `500ms`
+    pub initial_backoff: Duration,
+
+    /// Upper bound for the delay between two attempts.
+    /// Default: `10s`
+    pub max_backoff: Duration,
+
+    /// Factor the delay is multiplied by after every retry.
+    /// Default: `2.0`
+    pub multiplier: f64,
+
+    /// Fraction of the delay that is randomized, between `0.0` (no jitter) and `1.0` (full jitter).
+    /// Spreads out retries of clients that failed at the same time.
+    /// Default: `0.5`
+    pub jitter: f64,
+
+    /// Kinds of server errors that are retried.
+    /// Default: `[ErrorKind::Unavailable]`
+    pub retryable_kinds: Vec<ErrorKind>,
+
+    /// Additional HTTP status codes that are retried, eg. errors from a proxy in front of Gotenberg.
+    /// Default: `[502, 504]`
+    pub retryable_statuses: Vec<u16>,
+
+    /// Retry when the server could not be reached or the connection was dropped.
+    /// Default: `true`
+    pub retry_connection_errors: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+            multiplier: 2.0,
+            jitter: 0.5,
+            retryable_kinds: vec![ErrorKind::Unavailable],
+            retryable_statuses: vec![502, 504],
+            retry_connection_errors: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Create the default policy with the given maximum number of attempts.
+    pub fn new(max_attempts: u32) -> Self {
+        RetryPolicy {
+            max_attempts,
+            ..Default::default()
+        }
+    }
+
+    /// Check if the error is worth retrying, regardless of the number of attempts made.
+    pub fn is_retryable(&self, error: &Error) -> bool {
+        match error {
+            Error::RenderingError(error) => {
+                self.retryable_kinds.contains(&error.kind)
+                    || self.retryable_statuses.contains(&error.status)
+            }
+            // Builder errors (eg. an invalid URL) fail the same way every time
+            Error::CommunicationError(error) => self.retry_connection_errors && !error.is_builder(),
+            Error::TransportError(_) => self.retry_connection_errors,
+            _ => false,
+        }
+    }
+
+    /// The delay before the attempt following `attempt`, without jitter.
+    ///
+    /// `attempt` starts at 1 for the first request.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        // A NaN multiplier is ignored by `max`, and a delay too large for a `Duration` saturates
+        let delay = self.initial_backoff.as_secs_f64() * self.multiplier.max(1.0).powi(exponent);
+        let delay = match Duration::try_from_secs_f64(delay) {
+            Ok(delay) => delay,
+            // A zero initial backoff times an infinite factor
+            Err(_) if delay.is_nan() => Duration::ZERO,
+            Err(_) => Duration::MAX,
+        };
+        delay.min(self.max_backoff)
+    }
+
+    /// The delay to wait after `attempt` failed with `error`, or `None` if the request should not be retried.
+    pub(crate) fn delay_after(&self, attempt: u32, error: &Error) -> Option<Duration> {
+        if attempt >= self.max_attempts || !self.is_retryable(error) {
+            return None;
+        }
+
+        let backoff = self.backoff(attempt);
+        // NaN survives `clamp`, and would make `mul_f64` panic
+        let jitter = match self.jitter.is_nan() {
+            true => 0.0,
+            false => self.jitter.clamp(0.0, 1.0) * random_fraction(),
+        };
+        Some(backoff.mul_f64(1.0 - jitter))
+    }
+}
+
+/// A random number between 0.0 and 1.0, good enough to spread out retries.
+fn random_fraction() -> f64 {
+    (random_u64() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Wait before the next attempt.
+///
+/// There is no timer available without a runtime on wasm32, so retries are sent right away there.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) async fn sleep(_duration: Duration) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api_error(status: u16) -> Error {
+        Error::RenderingError(ApiError::new(status, String::new(), &HeaderMap::new()))
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        let policy = RetryPolicy::default();
+        assert!(policy.is_retryable(&api_error(503)));
+        assert!(policy.is_retryable(&api_error(502)));
+        assert!(!policy.is_retryable(&api_error(400)));
+        assert!(!policy.is_retryable(&Error::FilenameError(String::new())));
+    }
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(300),
+            ..Default::default()
+        };
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(300));
+        assert_eq!(policy.backoff(100), Duration::from_millis(300));
+    }
+
+    #[test]
+    fn test_delay_after() {
+        let policy = RetryPolicy {
+            max_attempts: 2,
+            initial_backoff: Duration::from_millis(100),
+            ..Default::default()
+        };
+
+        let delay = policy.delay_after(1, &api_error(503)).unwrap();
+        assert!(delay >= Duration::from_millis(50) && delay <= Duration::from_millis(100));
+
+        assert_eq!(policy.delay_after(2, &api_error(503)), None);
+        assert_eq!(policy.delay_after(1, &api_error(400)), None);
+    }
+
+    #[test]
+    fn test_out_of_range_values() {
+        let policy = RetryPolicy {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::MAX,
+            multiplier: f64::MAX,
+            jitter: f64::NAN,
+            ..Default::default()
+        };
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(3), Duration::MAX);
+        assert_eq!(
+            policy.delay_after(1, &api_error(503)),
+            Some(Duration::from_millis(100))
+        );
+
+        let policy = RetryPolicy {
+            multiplier: f64::NAN,
+            jitter: f64::INFINITY,
+            ..Default::default()
+        };
+        assert_eq!(policy.backoff(3), Duration::from_millis(500));
+        assert_eq!(
+            RetryPolicy {
+                initial_backoff: Duration::ZERO,
+                multiplier: f64::INFINITY,
+                ..Default::default()
+            }
+            .backoff(3),
+            Duration::ZERO
+        );
+        let delay = policy.delay_after(1, &api_error(503)).unwrap();
+        assert!(delay <= Duration::from_millis(500));
+    }
+}
@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+/// A sensitive string, such as a PDF password.
+///
+/// The value is redacted from `Debug` output and, with the `zeroize` feature enabled, securely zeroized when dropped.
+///
+/// # Example
+///
+/// ```
+/// use gotenberg_pdf::{Secret, WebOptions};
+///
+/// let mut options = WebOptions::default();
+/// options.user_password = Some("open-sesame".into());
+///
+/// assert_eq!(format!("{:?}", Secret::from("open-sesame")), "Secret(***)");
+/// ```
+#[derive(Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Secret(String);
+
+impl Secret {
+    /// Create a new secret.
+    pub fn new(secret: impl Into<String>) -> Self {
+        Secret(secret.into())
+    }
+
+    /// Expose the secret value.
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Drop for Secret {
+    fn drop(&mut self) {
+        // Securely zeroize the secret
+        #[cfg(feature = "zeroize")]
+        self.0.zeroize();
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Secret(***)")
+    }
+}
+
+impl From<&str> for Secret {
+    fn from(secret: &str) -> Self {
+        Secret::new(secret)
+    }
+}
+
+impl From<String> for Secret {
+    fn from(secret: String) -> Self {
+        Secret::new(secret)
+    }
+}
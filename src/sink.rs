@@ -0,0 +1,363 @@
+use super::*;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[cfg(any(feature = "blocking", feature = "ureq"))]
+use crate::transport::BlockingTransportResponse;
+#[cfg(feature = "stream")]
+use futures::{Stream, StreamExt};
+#[cfg(any(feature = "blocking", feature = "ureq"))]
+use reqwest::header::CONTENT_TYPE;
+#[cfg(any(feature = "blocking", feature = "ureq"))]
+use std::io::{Read, Write};
+#[cfg(feature = "stream")]
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+const PDF_MAGIC: &[u8] = b"%PDF-";
+
+/// Checks that a PDF output starts with the PDF magic bytes before anything is written.
+/// Other outputs, eg. screenshots, are written as they come.
+struct PdfCheck {
+    prefix: Vec<u8>,
+    checked: bool,
+}
+
+impl PdfCheck {
+    fn new(kind: OutputKind) -> Self {
+        PdfCheck {
+            prefix: Vec::new(),
+            checked: kind != OutputKind::Pdf,
+        }
+    }
+
+    /// Returns the bytes to write, holding back the first chunks until there are enough bytes to check.
+    fn push<'a>(&'a mut self, chunk: &'a [u8]) -> Result<&'a [u8], Error> {
+        if self.checked {
+            return Ok(chunk);
+        }
+
+        self.prefix.extend_from_slice(chunk);
+        if self.prefix.len() < PDF_MAGIC.len() {
+            return Ok(&[]);
+        }
+        if !self.prefix.starts_with(PDF_MAGIC) {
+            return Err(self.error());
+        }
+
+        self.checked = true;
+        Ok(&self.prefix)
+    }
+
+    /// Fails if the output ended before the magic bytes could be checked.
+    fn finish(&self) -> Result<(), Error> {
+        match self.checked {
+            true => Ok(()),
+            false => Err(self.error()),
+        }
+    }
+
+    fn error(&self) -> Error {
+        let start = &self.prefix[..self.prefix.len().min(32)];
+        Error::ParseError(
+            "PDF".to_string(),
+            String::from_utf8_lossy(start).to_string(),
+            "the output does not start with `%PDF-`".to_string(),
+        )
+    }
+}
+
+/// A temporary file next to the destination, renamed over it once complete and deleted otherwise.
+struct TempFile {
+    path: PathBuf,
+    destination: PathBuf,
+    persisted: bool,
+}
+
+impl TempFile {
+    fn new(destination: &Path) -> Self {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+        let name = destination
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "output".to_string());
+        let path = destination.with_file_name(format!(
+            ".{}.{}-{}.tmp",
+            name,
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        TempFile {
+            path,
+            destination: destination.to_path_buf(),
+            persisted: false,
+        }
+    }
+
+    #[cfg(any(feature = "blocking", feature = "ureq"))]
+    fn persist(mut self) -> Result<(), Error> {
+        std::fs::rename(&self.path, &self.destination).map_err(Error::IoError)?;
+        self.persisted = true;
+        Ok(())
+    }
+
+    #[cfg(feature = "stream")]
+    async fn persist_async(mut self) -> Result<(), Error> {
+        tokio::fs::rename(&self.path, &self.destination)
+            .await
+            .map_err(Error::IoError)?;
+        self.persisted = true;
+        Ok(())
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if !self.persisted {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// The kind of output announced by the response, or `expected` if its `Content-Type` does not tell,
+/// eg. a ZIP archive instead of a PDF when the output was split.
+fn response_kind(content_type: Option<&str>, expected: OutputKind) -> OutputKind {
+    OutputKind::from_content_type(content_type).unwrap_or(expected)
+}
+
+/// Copy a streamed response into the writer, checking it according to its `Content-Type`. See [`write_output`].
+#[cfg(feature = "stream")]
+pub(crate) async fn write_response(
+    output: StreamingOutput,
+    writer: impl AsyncWrite + Unpin,
+    expected: OutputKind,
+) -> Result<u64, Error> {
+    let kind = response_kind(output.content_type.as_deref(), expected);
+    write_output(output.stream, writer, kind).await
+}
+
+/// Copy a streamed response into the file at `path`, checking it according to its `Content-Type`. See [`write_output_to_path`].
+#[cfg(feature = "stream")]
+pub(crate) async fn write_response_to_path(
+    output: StreamingOutput,
+    path: &Path,
+    expected: OutputKind,
+) -> Result<u64, Error> {
+    let kind = response_kind(output.content_type.as_deref(), expected);
+    write_output_to_path(output.stream, path, kind).await
+}
+
+/// Copy a stream of the given kind of output into the writer, returning the number of bytes written.
+#[cfg(feature = "stream")]
+async fn write_output(
+    stream: impl Stream<Item = Result<Bytes, ReqwestError>>,
+    mut writer: impl AsyncWrite + Unpin,
+    kind: OutputKind,
+) -> Result<u64, Error> {
+    let mut stream = std::pin::pin!(stream);
+    let mut check = PdfCheck::new(kind);
+    let mut written = 0;
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(Into::into)?;
+        let bytes = check.push(&chunk)?;
+        writer.write_all(bytes).await.map_err(Error::IoError)?;
+        written += bytes.len() as u64;
+    }
+    check.finish()?;
+
+    writer.flush().await.map_err(Error::IoError)?;
+    Ok(written)
+}
+
+/// Copy a stream of the given kind of output into the file at `path`, replacing it atomically once the whole output was written.
+#[cfg(feature = "stream")]
+async fn write_output_to_path(
+    stream: impl Stream<Item = Result<Bytes, ReqwestError>>,
+    path: &Path,
+    kind: OutputKind,
+) -> Result<u64, Error> {
+    let temp = TempFile::new(path);
+    let mut file = tokio::fs::File::create(&temp.path)
+        .await
+        .map_err(Error::IoError)?;
+    let written = write_output(stream, &mut file, kind).await?;
+    file.sync_all().await.map_err(Error::IoError)?;
+    drop(file);
+
+    temp.persist_async().await?;
+    Ok(written)
+}
+
+/// Copy a response into the writer, checking it according to its `Content-Type`. See [`copy_output`].
+#[cfg(any(feature = "blocking", feature = "ureq"))]
+pub(crate) fn copy_response(
+    response: BlockingTransportResponse,
+    writer: impl Write,
+    expected: OutputKind,
+) -> Result<u64, Error> {
+    let kind = response_kind(blocking_content_type(&response), expected);
+    copy_output(response, writer, kind)
+}
+
+/// Copy a response into the file at `path`, checking it according to its `Content-Type`. See [`copy_output_to_path`].
+#[cfg(any(feature = "blocking", feature = "ureq"))]
+pub(crate) fn copy_response_to_path(
+    response: BlockingTransportResponse,
+    path: &Path,
+    expected: OutputKind,
+) -> Result<u64, Error> {
+    let kind = response_kind(blocking_content_type(&response), expected);
+    copy_output_to_path(response, path, kind)
+}
+
+#[cfg(any(feature = "blocking", feature = "ureq"))]
+fn blocking_content_type(response: &BlockingTransportResponse) -> Option<&str> {
+    response
+        .headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+}
+
+/// Copy the given kind of output from the reader into the writer, returning the number of bytes written.
+#[cfg(any(feature = "blocking", feature = "ureq"))]
+fn copy_output(
+    mut reader: impl Read,
+    mut writer: impl Write,
+    kind: OutputKind,
+) -> Result<u64, Error> {
+    let mut check = PdfCheck::new(kind);
+    let mut buffer = vec![0; 64 * 1024];
+    let mut written = 0;
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(Error::IoError(e)),
+        };
+        let bytes = check.push(&buffer[..read])?;
+        writer.write_all(bytes).map_err(Error::IoError)?;
+        written += bytes.len() as u64;
+    }
+    check.finish()?;
+
+    writer.flush().map_err(Error::IoError)?;
+    Ok(written)
+}
+
+/// Copy the given kind of output from the reader into the file at `path`, replacing it atomically once the whole output was written.
+#[cfg(any(feature = "blocking", feature = "ureq"))]
+fn copy_output_to_path(reader: impl Read, path: &Path, kind: OutputKind) -> Result<u64, Error> {
+    let temp = TempFile::new(path);
+    let mut file = std::fs::File::create(&temp.path).map_err(Error::IoError)?;
+    let written = copy_output(reader, &mut file, kind)?;
+    file.sync_all().map_err(Error::IoError)?;
+    drop(file);
+
+    temp.persist()?;
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A destination in the temporary directory, unique to the test and the process running it.
+    fn destination(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("gotenberg_pdf_{}_{}.pdf", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_pdf_check() {
+        // The magic bytes may be split over several chunks
+        let mut check = PdfCheck::new(OutputKind::Pdf);
+        assert_eq!(check.push(b"%P").unwrap(), b"");
+        assert!(check.finish().is_err());
+        assert_eq!(check.push(b"DF-1.7\n").unwrap(), b"%PDF-1.7\n");
+        assert_eq!(check.push(b"%%EOF").unwrap(), b"%%EOF");
+        assert!(check.finish().is_ok());
+
+        // Screenshots are not checked
+        let mut check = PdfCheck::new(OutputKind::Image);
+        assert_eq!(check.push(b"\x89PNG").unwrap(), b"\x89PNG");
+        assert!(check.finish().is_ok());
+
+        let mut check = PdfCheck::new(OutputKind::Pdf);
+        match check.push(br#"{"error":"conversion failed"}"#) {
+            Err(Error::ParseError(kind, start, _)) => {
+                assert_eq!(kind, "PDF");
+                assert_eq!(start, r#"{"error":"conversion failed"}"#);
+            }
+            other => panic!("Expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_temp_file() {
+        let destination = destination("sink_test");
+        let temp = TempFile::new(&destination);
+        assert_eq!(temp.path.parent(), destination.parent());
+        assert_ne!(temp.path, TempFile::new(&destination).path);
+
+        // An unfinished temporary file is deleted
+        std::fs::write(&temp.path, b"%PDF-").unwrap();
+        let path = temp.path.clone();
+        drop(temp);
+        assert!(!path.exists());
+    }
+
+    #[cfg(any(feature = "blocking", feature = "ureq"))]
+    #[test]
+    fn test_copy_response() {
+        let response = |content_type: Option<&str>, body: &'static [u8]| {
+            let mut headers = HeaderMap::new();
+            if let Some(content_type) = content_type {
+                headers.insert(CONTENT_TYPE, content_type.parse().unwrap());
+            }
+            BlockingTransportResponse {
+                status: 200,
+                headers,
+                body: Box::new(body),
+            }
+        };
+
+        // A split conversion answers with a ZIP archive instead of a PDF
+        let mut zip = Vec::new();
+        let written = copy_response(
+            response(Some("application/zip"), b"PK\x03\x04"),
+            &mut zip,
+            OutputKind::Pdf,
+        )
+        .unwrap();
+        assert_eq!(written, 4);
+        assert_eq!(zip, b"PK\x03\x04");
+
+        // Without a content type, the output is checked as the route's usual output
+        let result = copy_response(response(None, b"PK\x03\x04"), Vec::new(), OutputKind::Pdf);
+        assert!(matches!(result, Err(Error::ParseError(..))));
+        let result = copy_response(
+            response(Some("application/pdf"), b"<html>"),
+            Vec::new(),
+            OutputKind::Pdf,
+        );
+        assert!(matches!(result, Err(Error::ParseError(..))));
+    }
+
+    #[cfg(any(feature = "blocking", feature = "ureq"))]
+    #[test]
+    fn test_copy_pdf_to_path() {
+        let destination = destination("copy_test");
+        std::fs::write(&destination, b"%PDF-1.4 previous").unwrap();
+
+        let written =
+            copy_output_to_path(&b"%PDF-1.7 new"[..], &destination, OutputKind::Pdf).unwrap();
+        assert_eq!(written, 12);
+        assert_eq!(std::fs::read(&destination).unwrap(), b"%PDF-1.7 new");
+
+        // A failed copy leaves the previous file untouched
+        assert!(copy_output_to_path(&b"<html>"[..], &destination, OutputKind::Pdf).is_err());
+        assert_eq!(std::fs::read(&destination).unwrap(), b"%PDF-1.7 new");
+        std::fs::remove_file(&destination).unwrap();
+    }
+}
@@ -2,10 +2,16 @@ use super::*;
 use futures::Stream;
 use reqwest::multipart;
 use reqwest::{Client as ReqwestClient, Error as ReqwestError, Response};
+use std::future::Future;
+use std::path::Path;
+use tokio::io::AsyncWrite;
 
 #[cfg(feature = "zeroize")]
 use zeroize::Zeroize;
 
+/// Streaming parts sent after the fields and files of a request, with the name of their form field.
+type Uploads = Vec<(String, multipart::Part)>;
+
 /// Gotenberg Streaming API client. Available with the `stream` feature enabled.
 ///
 /// The client can be freely cloned and moved across threads.
@@ -17,6 +23,8 @@ pub struct StreamingClient {
     base_url: String,
     username: Option<String>,
     password: Option<String>,
+    retry_policy: Option<RetryPolicy>,
+    server_version: Option<GotenbergVersion>,
 }
 
 impl Drop for StreamingClient {
@@ -39,6 +47,8 @@ impl Debug for StreamingClient {
         f.debug_struct("StreamingClient")
             .field("base_url", &self.base_url)
             .field("username", &self.username)
+            .field("retry_policy", &self.retry_policy)
+            .field("server_version", &self.server_version)
             .finish()
     }
 }
@@ -59,6 +69,8 @@ impl StreamingClient {
             base_url: base_url.to_string(),
             username: None,
             password: None,
+            retry_policy: None,
+            server_version: None,
         }
     }
 
@@ -76,6 +88,8 @@ impl StreamingClient {
             base_url: base_url.to_string(),
             username: None,
             password: None,
+            retry_policy: None,
+            server_version: None,
         }
     }
 
@@ -102,51 +116,158 @@ impl StreamingClient {
         client
     }
 
-    async fn post_stream(
+    /// Retry requests that failed for a transient reason according to the given policy,
+    /// consuming the current client and returning a new instance of the client.
+    ///
+    /// Only failures up to the response headers are retried. Once the body is being streamed, errors are passed on.
+    pub fn retry_policy(self, policy: RetryPolicy) -> Self {
+        let mut client = self;
+        client.retry_policy = Some(policy);
+
+        client
+    }
+
+    /// Set the version of the Gotenberg server, consuming the current client and returning a new instance of the client.
+    ///
+    /// Requests using a route or an option the server does not support then fail with
+    /// [`Error::UnsupportedFeature`] before anything is sent. Use [`StreamingClient::version_typed`] to query the version.
+    pub fn server_version(self, version: GotenbergVersion) -> Self {
+        let mut client = self;
+        client.server_version = Some(version);
+
+        client
+    }
+
+    /// Generic POST method that encodes a request into a multipart form and sends it.
+    /// Used for utility methods that don't require streaming.
+    /// In the case of webhook usage, the server will respond with 204 No Content and no bytes,
+    /// so we return empty bytes.
+    async fn post(
         &self,
-        endpoint: &str,
-        form: multipart::Form,
-        trace: Option<String>,
-    ) -> Result<impl Stream<Item = Result<Bytes, ReqwestError>>, Error> {
-        let url = format!("{}/{}", self.base_url, endpoint);
+        request: GotenbergRequest,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<Bytes, Error> {
+        let response = self.send_with_retry(request, webhook).await?;
+        response.bytes().await.map_err(Into::into)
+    }
 
-        let mut req = self.client.post(&url).multipart(form);
+    /// Same as [`StreamingClient::send_with_retry`], but keeps the response headers alongside the stream.
+    async fn send_output(
+        &self,
+        request: GotenbergRequest,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<StreamingOutput, Error> {
+        self.send_form_output(request, webhook, || async { Ok(Uploads::default()) })
+            .await
+    }
 
-        if let Some(trace) = trace {
-            req = req.header("Gotenberg-Trace", trace);
-        }
+    /// Same as [`StreamingClient::send_output`], followed by the parts opened by `uploads` for every attempt.
+    async fn send_form_output<F>(
+        &self,
+        request: GotenbergRequest,
+        webhook: Option<&WebhookOptions>,
+        uploads: impl Fn() -> F,
+    ) -> Result<StreamingOutput, Error>
+    where
+        F: Future<Output = Result<Uploads, Error>>,
+    {
+        // Add the webhook headers first, to know the trace generated for the request
+        let request = request.webhook(webhook)?;
+        let trace = request.trace().map(str::to_string);
+        let response = self.send_form(request, None, uploads).await?;
+        Ok(StreamingOutput::from_response(response, trace))
+    }
 
-        if let (Some(username), Some(password)) = (&self.username, &self.password) {
-            req = req.basic_auth(username, Some(password));
-        }
+    /// Same as [`StreamingClient::post`], but keeps the response headers alongside the bytes.
+    async fn post_output(
+        &self,
+        request: GotenbergRequest,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        self.post_form_output(request, webhook, || async { Ok(Uploads::default()) })
+            .await
+    }
 
-        let response = req.send().await.map_err(Into::into)?;
+    /// Same as [`StreamingClient::post_output`], followed by the parts opened by `uploads` for every attempt.
+    async fn post_form_output<F>(
+        &self,
+        request: GotenbergRequest,
+        webhook: Option<&WebhookOptions>,
+        uploads: impl Fn() -> F,
+    ) -> Result<RenderOutput, Error>
+    where
+        F: Future<Output = Result<Uploads, Error>>,
+    {
+        let request = request.webhook(webhook)?;
+        let trace = request.trace().map(str::to_string);
+        let response = self.send_form(request, None, uploads).await?;
+        let headers = response.headers().clone();
+        let bytes = response.bytes().await.map_err(Into::into)?;
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            return Err(Error::RenderingError(format!(
-                "Failed to render PDF: {} - {}",
-                status, body
-            )));
-        }
+        let mut output = RenderOutput::from_parts(&headers, bytes);
+        output.trace = output.trace.or(trace);
+        Ok(output)
+    }
 
-        Ok(response.bytes_stream())
+    /// Send the request, rebuilding the form for every attempt as a sent form cannot be reused.
+    async fn send_with_retry(
+        &self,
+        request: GotenbergRequest,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<Response, Error> {
+        self.send_form(request, webhook, || async { Ok(Uploads::default()) })
+            .await
     }
 
-    /// Generic POST method that takes a multipart form and sends it.
-    /// Used for utility methods that don't require streaming.
-    async fn post(
+    /// Same as [`StreamingClient::send_with_retry`], followed by the parts opened by `uploads`, which is called for every attempt.
+    async fn send_form<F>(
+        &self,
+        request: GotenbergRequest,
+        webhook: Option<&WebhookOptions>,
+        uploads: impl Fn() -> F,
+    ) -> Result<Response, Error>
+    where
+        F: Future<Output = Result<Uploads, Error>>,
+    {
+        version::check_features(
+            self.server_version.as_ref(),
+            &request.endpoint,
+            &request.features,
+        )?;
+        let request = request.webhook(webhook)?;
+
+        let mut attempt = 1;
+        loop {
+            let mut form = request.form()?;
+            for (name, part) in uploads().await? {
+                form = form.part(name, part);
+            }
+            let result = self.send(&request, form).await;
+
+            let delay = match (&result, &self.retry_policy) {
+                (Err(error), Some(policy)) => policy.delay_after(attempt, error),
+                _ => None,
+            };
+            match delay {
+                Some(delay) => retry::sleep(delay).await,
+                None => return result,
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Send a single request, turning unsuccessful responses into errors.
+    /// In webhook mode, the 204 No Content response of the server is passed on, streaming no bytes.
+    async fn send(
         &self,
-        endpoint: &str,
+        request: &GotenbergRequest,
         form: multipart::Form,
-        trace: Option<String>,
-    ) -> Result<Bytes, Error> {
-        let url = format!("{}/{}", self.base_url, endpoint);
+    ) -> Result<Response, Error> {
+        let url = format!("{}/{}", self.base_url, request.endpoint);
 
         let mut req = self.client.post(&url).multipart(form);
-        if let Some(trace) = trace {
-            req = req.header("Gotenberg-Trace", trace);
+        for (name, value) in &request.headers {
+            req = req.header(name, value);
         }
 
         // Add basic auth if username and password are provided
@@ -157,15 +278,13 @@ impl StreamingClient {
         let response: Response = req.send().await.map_err(Into::into)?;
 
         if !response.status().is_success() {
-            let status = response.status();
+            let status = response.status().as_u16();
+            let headers = response.headers().clone();
             let body = response.text().await.unwrap_or_default();
-            return Err(Error::RenderingError(format!(
-                "Failed to render PDF: {} - {}",
-                status, body
-            )));
+            return Err(Error::RenderingError(ApiError::new(status, body, &headers)));
         }
 
-        response.bytes().await.map_err(Into::into)
+        Ok(response)
     }
 
     /// Convert a URL to a PDF using the Chromium engine.
@@ -173,33 +292,102 @@ impl StreamingClient {
         &self,
         url: &str,
         options: WebOptions,
+        webhook: Option<&WebhookOptions>,
     ) -> Result<impl Stream<Item = Result<Bytes, ReqwestError>>, Error> {
-        let trace = options.trace_id.clone();
-        let form = multipart::Form::new().text("url", url.to_string());
-        let form = options.fill_form(form);
-
-        self.post_stream("forms/chromium/convert/url", form, trace)
+        self.pdf_from_url_with_response(url, options, webhook)
             .await
+            .map(|output| output.stream)
+    }
+
+    /// Same as [`StreamingClient::pdf_from_url`], but returns a [`StreamingOutput`] with the output filename, content type and trace.
+    pub async fn pdf_from_url_with_response(
+        &self,
+        url: &str,
+        options: WebOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<StreamingOutput, Error> {
+        let request = GotenbergRequest::pdf_from_url(url, options)?;
+        self.send_output(request, webhook).await
+    }
+
+    /// Same as [`StreamingClient::pdf_from_url`], but copies the PDF into `writer` and returns the number of bytes written.
+    ///
+    /// With `split_mode` set, Gotenberg answers with a ZIP archive of the parts, which is written instead.
+    pub async fn pdf_from_url_to_writer(
+        &self,
+        url: &str,
+        options: WebOptions,
+        writer: impl AsyncWrite + Unpin,
+    ) -> Result<u64, Error> {
+        let output = self.pdf_from_url_with_response(url, options, None).await?;
+        sink::write_response(output, writer, OutputKind::Pdf).await
+    }
+
+    /// Same as [`StreamingClient::pdf_from_url`], but writes the PDF to the file at `path` and returns the number of bytes written.
+    ///
+    /// The PDF is written to a temporary file next to `path`, which replaces `path` once the whole PDF was written.
+    /// On error, the temporary file is deleted and `path` is left untouched.
+    pub async fn pdf_from_url_to_path(
+        &self,
+        url: &str,
+        options: WebOptions,
+        path: impl AsRef<Path>,
+    ) -> Result<u64, Error> {
+        let output = self.pdf_from_url_with_response(url, options, None).await?;
+        sink::write_response_to_path(output, path.as_ref(), OutputKind::Pdf).await
     }
 
     /// Convert HTML to a PDF using the Chromium engine.
+    ///
+    /// Pass an [`HtmlBundle`] to upload images, stylesheets or fonts alongside the HTML.
     pub async fn pdf_from_html(
         &self,
-        html: &str,
+        html: impl Into<HtmlBundle>,
         options: WebOptions,
+        webhook: Option<&WebhookOptions>,
     ) -> Result<impl Stream<Item = Result<Bytes, ReqwestError>>, Error> {
-        let trace = options.trace_id.clone();
-        let file_bytes = html.to_string().into_bytes();
-        let part = multipart::Part::bytes(file_bytes)
-            .file_name("index.html")
-            .mime_str("text/html")
-            .unwrap();
+        self.pdf_from_html_with_response(html, options, webhook)
+            .await
+            .map(|output| output.stream)
+    }
+
+    /// Same as [`StreamingClient::pdf_from_html`], but returns a [`StreamingOutput`] with the output filename, content type and trace.
+    pub async fn pdf_from_html_with_response(
+        &self,
+        html: impl Into<HtmlBundle>,
+        options: WebOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<StreamingOutput, Error> {
+        let request = GotenbergRequest::pdf_from_html(html, options)?;
+        self.send_output(request, webhook).await
+    }
 
-        let form = multipart::Form::new().part("index.html", part);
-        let form = options.fill_form(form);
+    /// Same as [`StreamingClient::pdf_from_html`], but copies the PDF into `writer` and returns the number of bytes written.
+    pub async fn pdf_from_html_to_writer(
+        &self,
+        html: impl Into<HtmlBundle>,
+        options: WebOptions,
+        writer: impl AsyncWrite + Unpin,
+    ) -> Result<u64, Error> {
+        let output = self
+            .pdf_from_html_with_response(html, options, None)
+            .await?;
+        sink::write_response(output, writer, OutputKind::Pdf).await
+    }
 
-        self.post_stream("forms/chromium/convert/html", form, trace)
-            .await
+    /// Same as [`StreamingClient::pdf_from_html`], but writes the PDF to the file at `path` and returns the number of bytes written.
+    ///
+    /// See [`StreamingClient::pdf_from_url_to_path`] for how partial files are avoided.
+    pub async fn pdf_from_html_to_path(
+        &self,
+        html: impl Into<HtmlBundle>,
+        options: WebOptions,
+        path: impl AsRef<Path>,
+    ) -> Result<u64, Error> {
+        let output = self
+            .pdf_from_html_with_response(html, options, None)
+            .await?;
+        sink::write_response_to_path(output, path.as_ref(), OutputKind::Pdf).await
     }
 
     /// Convert Markdown to a PDF using the Chromium engine.
@@ -222,35 +410,56 @@ impl StreamingClient {
     /// The markdown files should be in a "filename" => "content" format. The filename key string must end with `.md`.
     pub async fn pdf_from_markdown(
         &self,
-        html_template: &str,
+        html_template: impl Into<HtmlBundle>,
         markdown: HashMap<&str, &str>,
         options: WebOptions,
+        webhook: Option<&WebhookOptions>,
     ) -> Result<impl Stream<Item = Result<Bytes, ReqwestError>>, Error> {
-        let trace = options.trace_id.clone();
+        self.pdf_from_markdown_with_response(html_template, markdown, options, webhook)
+            .await
+            .map(|output| output.stream)
+    }
 
-        let file_bytes = html_template.to_string().into_bytes();
-        let html_part = multipart::Part::bytes(file_bytes)
-            .file_name("index.html")
-            .mime_str("text/html")
-            .unwrap();
+    /// Same as [`StreamingClient::pdf_from_markdown`], but returns a [`StreamingOutput`] with the output filename, content type and trace.
+    pub async fn pdf_from_markdown_with_response(
+        &self,
+        html_template: impl Into<HtmlBundle>,
+        markdown: HashMap<&str, &str>,
+        options: WebOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<StreamingOutput, Error> {
+        let request = GotenbergRequest::pdf_from_markdown(html_template, markdown, options)?;
+        self.send_output(request, webhook).await
+    }
 
-        let mut form = multipart::Form::new().part("index.html", html_part);
-        for (filename, content) in markdown {
-            if !filename.ends_with(".md") {
-                return Err(Error::FilenameError(
-                    "Markdown filename must end with '.md'".to_string(),
-                ));
-            }
-            let part = multipart::Part::bytes(content.as_bytes().to_vec())
-                .file_name(filename.to_string())
-                .mime_str("text/markdown")
-                .unwrap();
-            form = form.part(filename.to_string(), part);
-        }
+    /// Same as [`StreamingClient::pdf_from_markdown`], but copies the PDF into `writer` and returns the number of bytes written.
+    pub async fn pdf_from_markdown_to_writer(
+        &self,
+        html_template: impl Into<HtmlBundle>,
+        markdown: HashMap<&str, &str>,
+        options: WebOptions,
+        writer: impl AsyncWrite + Unpin,
+    ) -> Result<u64, Error> {
+        let output = self
+            .pdf_from_markdown_with_response(html_template, markdown, options, None)
+            .await?;
+        sink::write_response(output, writer, OutputKind::Pdf).await
+    }
 
-        let form = options.fill_form(form);
-        self.post_stream("forms/chromium/convert/markdown", form, trace)
-            .await
+    /// Same as [`StreamingClient::pdf_from_markdown`], but writes the PDF to the file at `path` and returns the number of bytes written.
+    ///
+    /// See [`StreamingClient::pdf_from_url_to_path`] for how partial files are avoided.
+    pub async fn pdf_from_markdown_to_path(
+        &self,
+        html_template: impl Into<HtmlBundle>,
+        markdown: HashMap<&str, &str>,
+        options: WebOptions,
+        path: impl AsRef<Path>,
+    ) -> Result<u64, Error> {
+        let output = self
+            .pdf_from_markdown_with_response(html_template, markdown, options, None)
+            .await?;
+        sink::write_response_to_path(output, path.as_ref(), OutputKind::Pdf).await
     }
 
     /// Take a screenshot of a webpage using the Chromium engine.
@@ -258,68 +467,158 @@ impl StreamingClient {
         &self,
         url: &str,
         options: ScreenshotOptions,
+        webhook: Option<&WebhookOptions>,
     ) -> Result<impl Stream<Item = Result<Bytes, ReqwestError>>, Error> {
-        let trace = options.trace_id.clone();
-        let form = multipart::Form::new().text("url", url.to_string());
-        let form = options.fill_form(form);
-
-        self.post_stream("forms/chromium/screenshot/url", form, trace)
+        self.screenshot_url_with_response(url, options, webhook)
             .await
+            .map(|output| output.stream)
+    }
+
+    /// Same as [`StreamingClient::screenshot_url`], but returns a [`StreamingOutput`] with the output filename, content type and trace.
+    pub async fn screenshot_url_with_response(
+        &self,
+        url: &str,
+        options: ScreenshotOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<StreamingOutput, Error> {
+        let request = GotenbergRequest::screenshot_url(url, options);
+        self.send_output(request, webhook).await
+    }
+
+    /// Same as [`StreamingClient::screenshot_url`], but copies the screenshot into `writer` and returns the number of bytes written.
+    pub async fn screenshot_url_to_writer(
+        &self,
+        url: &str,
+        options: ScreenshotOptions,
+        writer: impl AsyncWrite + Unpin,
+    ) -> Result<u64, Error> {
+        let output = self
+            .screenshot_url_with_response(url, options, None)
+            .await?;
+        sink::write_response(output, writer, OutputKind::Image).await
+    }
+
+    /// Same as [`StreamingClient::screenshot_url`], but writes the screenshot to the file at `path` and returns the number of bytes written.
+    ///
+    /// See [`StreamingClient::pdf_from_url_to_path`] for how partial files are avoided.
+    pub async fn screenshot_url_to_path(
+        &self,
+        url: &str,
+        options: ScreenshotOptions,
+        path: impl AsRef<Path>,
+    ) -> Result<u64, Error> {
+        let output = self
+            .screenshot_url_with_response(url, options, None)
+            .await?;
+        sink::write_response_to_path(output, path.as_ref(), OutputKind::Image).await
     }
 
     /// Take a screenshot of an HTML page using the Chromium engine.
+    ///
+    /// Pass an [`HtmlBundle`] to upload images, stylesheets or fonts alongside the HTML.
     pub async fn screenshot_html(
         &self,
-        html: &str,
+        html: impl Into<HtmlBundle>,
         options: ScreenshotOptions,
+        webhook: Option<&WebhookOptions>,
     ) -> Result<impl Stream<Item = Result<Bytes, ReqwestError>>, Error> {
-        let trace = options.trace_id.clone();
-        let file_bytes = html.to_string().into_bytes();
-        let part = multipart::Part::bytes(file_bytes)
-            .file_name("index.html")
-            .mime_str("text/html")
-            .unwrap();
+        self.screenshot_html_with_response(html, options, webhook)
+            .await
+            .map(|output| output.stream)
+    }
 
-        let form = multipart::Form::new().part("index.html", part);
-        let form = options.fill_form(form);
+    /// Same as [`StreamingClient::screenshot_html`], but returns a [`StreamingOutput`] with the output filename, content type and trace.
+    pub async fn screenshot_html_with_response(
+        &self,
+        html: impl Into<HtmlBundle>,
+        options: ScreenshotOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<StreamingOutput, Error> {
+        let request = GotenbergRequest::screenshot_html(html, options)?;
+        self.send_output(request, webhook).await
+    }
 
-        self.post_stream("forms/chromium/screenshot/html", form, trace)
-            .await
+    /// Same as [`StreamingClient::screenshot_html`], but copies the screenshot into `writer` and returns the number of bytes written.
+    pub async fn screenshot_html_to_writer(
+        &self,
+        html: impl Into<HtmlBundle>,
+        options: ScreenshotOptions,
+        writer: impl AsyncWrite + Unpin,
+    ) -> Result<u64, Error> {
+        let output = self
+            .screenshot_html_with_response(html, options, None)
+            .await?;
+        sink::write_response(output, writer, OutputKind::Image).await
+    }
+
+    /// Same as [`StreamingClient::screenshot_html`], but writes the screenshot to the file at `path` and returns the number of bytes written.
+    ///
+    /// See [`StreamingClient::pdf_from_url_to_path`] for how partial files are avoided.
+    pub async fn screenshot_html_to_path(
+        &self,
+        html: impl Into<HtmlBundle>,
+        options: ScreenshotOptions,
+        path: impl AsRef<Path>,
+    ) -> Result<u64, Error> {
+        let output = self
+            .screenshot_html_with_response(html, options, None)
+            .await?;
+        sink::write_response_to_path(output, path.as_ref(), OutputKind::Image).await
     }
 
     /// Take a screenshot of a set of markdown files using the Chromium engine.
     pub async fn screenshot_markdown(
         &self,
-        html_template: &str,
+        html_template: impl Into<HtmlBundle>,
         markdown: HashMap<&str, &str>,
         options: ScreenshotOptions,
+        webhook: Option<&WebhookOptions>,
     ) -> Result<impl Stream<Item = Result<Bytes, ReqwestError>>, Error> {
-        let trace = options.trace_id.clone();
-
-        let file_bytes = html_template.to_string().into_bytes();
-        let html_part = multipart::Part::bytes(file_bytes)
-            .file_name("index.html")
-            .mime_str("text/html")
-            .unwrap();
+        self.screenshot_markdown_with_response(html_template, markdown, options, webhook)
+            .await
+            .map(|output| output.stream)
+    }
 
-        let mut form = multipart::Form::new().part("index.html", html_part);
-        for (filename, content) in markdown {
-            if !filename.ends_with(".md") {
-                return Err(Error::FilenameError(
-                    "Markdown filename must end with '.md'".to_string(),
-                ));
-            }
-            let part = multipart::Part::bytes(content.as_bytes().to_vec())
-                .file_name(filename.to_string())
-                .mime_str("text/markdown")
-                .unwrap();
-            form = form.part(filename.to_string(), part);
-        }
+    /// Same as [`StreamingClient::screenshot_markdown`], but returns a [`StreamingOutput`] with the output filename, content type and trace.
+    pub async fn screenshot_markdown_with_response(
+        &self,
+        html_template: impl Into<HtmlBundle>,
+        markdown: HashMap<&str, &str>,
+        options: ScreenshotOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<StreamingOutput, Error> {
+        let request = GotenbergRequest::screenshot_markdown(html_template, markdown, options)?;
+        self.send_output(request, webhook).await
+    }
 
-        let form = options.fill_form(form);
+    /// Same as [`StreamingClient::screenshot_markdown`], but copies the screenshot into `writer` and returns the number of bytes written.
+    pub async fn screenshot_markdown_to_writer(
+        &self,
+        html_template: impl Into<HtmlBundle>,
+        markdown: HashMap<&str, &str>,
+        options: ScreenshotOptions,
+        writer: impl AsyncWrite + Unpin,
+    ) -> Result<u64, Error> {
+        let output = self
+            .screenshot_markdown_with_response(html_template, markdown, options, None)
+            .await?;
+        sink::write_response(output, writer, OutputKind::Image).await
+    }
 
-        self.post_stream("forms/chromium/screenshot/markdown", form, trace)
-            .await
+    /// Same as [`StreamingClient::screenshot_markdown`], but writes the screenshot to the file at `path` and returns the number of bytes written.
+    ///
+    /// See [`StreamingClient::pdf_from_url_to_path`] for how partial files are avoided.
+    pub async fn screenshot_markdown_to_path(
+        &self,
+        html_template: impl Into<HtmlBundle>,
+        markdown: HashMap<&str, &str>,
+        options: ScreenshotOptions,
+        path: impl AsRef<Path>,
+    ) -> Result<u64, Error> {
+        let output = self
+            .screenshot_markdown_with_response(html_template, markdown, options, None)
+            .await?;
+        sink::write_response_to_path(output, path.as_ref(), OutputKind::Image).await
     }
 
     /// Convert a document to a PDF using the LibreOffice engine.
@@ -337,87 +636,435 @@ impl StreamingClient {
     /// .wb2 .wk1 .wks .wmf .wpd .wpg .wps .xbm .xhtml .xls .xlsb .xlsm .xlsx .xlt
     /// .xltm .xltx .xlw .xml .xpm .zabw
     /// ```
+    ///
+    /// The document is either uploaded with its filename, eg. `("report.docx", bytes)`, or a [`DownloadFrom`] the server downloads.
     pub async fn pdf_from_doc(
         &self,
-        filename: &str,
-        bytes: Vec<u8>,
+        file: impl Into<NamedFile>,
         options: DocumentOptions,
+        webhook: Option<&WebhookOptions>,
     ) -> Result<impl Stream<Item = Result<Bytes, ReqwestError>>, Error> {
-        let trace = options.trace_id.clone();
+        self.pdf_from_doc_with_response(file, options, webhook)
+            .await
+            .map(|output| output.stream)
+    }
 
-        let part = multipart::Part::bytes(bytes).file_name(filename.to_string());
-        let form = multipart::Form::new().part("files", part);
-        let form = options.fill_form(form);
+    /// Same as [`StreamingClient::pdf_from_doc`], but returns a [`StreamingOutput`] with the output filename, content type and trace.
+    pub async fn pdf_from_doc_with_response(
+        &self,
+        file: impl Into<NamedFile>,
+        options: DocumentOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<StreamingOutput, Error> {
+        let request = GotenbergRequest::pdf_from_doc(file, options)?;
+        self.send_output(request, webhook).await
+    }
 
-        self.post_stream("forms/libreoffice/convert", form, trace)
-            .await
+    /// Same as [`StreamingClient::pdf_from_doc`], but copies the PDF into `writer` and returns the number of bytes written.
+    ///
+    /// See [`StreamingClient::pdf_from_url_to_writer`] for split conversions.
+    pub async fn pdf_from_doc_to_writer(
+        &self,
+        file: impl Into<NamedFile>,
+        options: DocumentOptions,
+        writer: impl AsyncWrite + Unpin,
+    ) -> Result<u64, Error> {
+        let output = self.pdf_from_doc_with_response(file, options, None).await?;
+        sink::write_response(output, writer, OutputKind::Pdf).await
+    }
+
+    /// Same as [`StreamingClient::pdf_from_doc`], but writes the PDF to the file at `path` and returns the number of bytes written.
+    ///
+    /// See [`StreamingClient::pdf_from_url_to_path`] for how partial files are avoided.
+    pub async fn pdf_from_doc_to_path(
+        &self,
+        file: impl Into<NamedFile>,
+        options: DocumentOptions,
+        path: impl AsRef<Path>,
+    ) -> Result<u64, Error> {
+        let output = self.pdf_from_doc_with_response(file, options, None).await?;
+        sink::write_response_to_path(output, path.as_ref(), OutputKind::Pdf).await
+    }
+
+    /// Convert several documents to PDF in a single request using the LibreOffice engine.
+    ///
+    /// With `options.merge` set, the documents are merged into a single PDF in the order they are given,
+    /// otherwise one PDF is returned per document. See [`StreamingClient::pdf_from_doc`] for the supported file formats.
+    ///
+    /// Documents are given as `(filename, bytes)` tuples, or as [`DownloadFrom`] for the server to download them.
+    /// Downloaded documents are named by the server, so they cannot be merged in order: with `options.merge` set,
+    /// a [`DownloadFrom`] fails the request with an [`Error::FilenameError`].
+    pub async fn pdfs_from_docs(
+        &self,
+        files: Vec<impl Into<NamedFile>>,
+        options: DocumentOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<ConvertedDocuments, Error> {
+        let merge = options.merge.unwrap_or(false);
+        let files: Vec<NamedFile> = files.into_iter().map(Into::into).collect();
+        let filenames: Vec<String> = files
+            .iter()
+            .filter_map(|file| file.filename().map(str::to_string))
+            .collect();
+        let output = self
+            .pdfs_from_docs_with_response(files, options, webhook)
+            .await?;
+        ConvertedDocuments::from_response(output.bytes, merge, &filenames)
+    }
+
+    /// Same as [`StreamingClient::pdfs_from_docs`], but returns the raw [`RenderOutput`], see [`RenderOutput::files`] to unpack it.
+    pub async fn pdfs_from_docs_with_response(
+        &self,
+        files: Vec<impl Into<NamedFile>>,
+        options: DocumentOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        let request = GotenbergRequest::pdfs_from_docs(files, options)?;
+        self.post_output(request, webhook).await
     }
 
     /// Transforms a PDF file into the requested PDF/A format and/or PDF/UA.
     pub async fn convert_pdf(
         &self,
-        pdf_bytes: Vec<u8>,
+        pdf: impl Into<InputFile>,
         pdfa: Option<PDFFormat>,
         pdfua: bool,
+        webhook: Option<&WebhookOptions>,
     ) -> Result<impl Stream<Item = Result<Bytes, ReqwestError>>, Error> {
-        let pdf_part = multipart::Part::bytes(pdf_bytes).file_name("file.pdf".to_string());
-        let mut form = multipart::Form::new().part("file.pdf", pdf_part);
+        self.convert_pdf_with_response(pdf, pdfa, pdfua, webhook)
+            .await
+            .map(|output| output.stream)
+    }
 
-        if let Some(pdfa) = pdfa {
-            form = form.text("pdfa", pdfa.to_string());
-        }
+    /// Same as [`StreamingClient::convert_pdf`], but returns a [`StreamingOutput`] with the output filename, content type and trace.
+    pub async fn convert_pdf_with_response(
+        &self,
+        pdf: impl Into<InputFile>,
+        pdfa: Option<PDFFormat>,
+        pdfua: bool,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<StreamingOutput, Error> {
+        let request = GotenbergRequest::convert_pdf(pdf, pdfa, pdfua);
+        self.send_output(request, webhook).await
+    }
+
+    /// Same as [`StreamingClient::convert_pdf`], but copies the PDF into `writer` and returns the number of bytes written.
+    pub async fn convert_pdf_to_writer(
+        &self,
+        pdf: impl Into<InputFile>,
+        pdfa: Option<PDFFormat>,
+        pdfua: bool,
+        writer: impl AsyncWrite + Unpin,
+    ) -> Result<u64, Error> {
+        let output = self
+            .convert_pdf_with_response(pdf, pdfa, pdfua, None)
+            .await?;
+        sink::write_response(output, writer, OutputKind::Pdf).await
+    }
+
+    /// Same as [`StreamingClient::convert_pdf`], but writes the PDF to the file at `path` and returns the number of bytes written.
+    ///
+    /// See [`StreamingClient::pdf_from_url_to_path`] for how partial files are avoided.
+    pub async fn convert_pdf_to_path(
+        &self,
+        pdf: impl Into<InputFile>,
+        pdfa: Option<PDFFormat>,
+        pdfua: bool,
+        path: impl AsRef<Path>,
+    ) -> Result<u64, Error> {
+        let output = self
+            .convert_pdf_with_response(pdf, pdfa, pdfua, None)
+            .await?;
+        sink::write_response_to_path(output, path.as_ref(), OutputKind::Pdf).await
+    }
+
+    /// Merge several PDF files into a single PDF.
+    ///
+    /// The files are merged in the order they are given. Every filename must end with `.pdf`.
+    /// Files downloaded by the server are named by the server, so their order could not be kept: a
+    /// [`DownloadFrom`] fails the request with an [`Error::FilenameError`].
+    pub async fn merge_pdfs(
+        &self,
+        files: Vec<impl Into<NamedFile>>,
+        options: MergeOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<impl Stream<Item = Result<Bytes, ReqwestError>>, Error> {
+        self.merge_pdfs_with_response(files, options, webhook)
+            .await
+            .map(|output| output.stream)
+    }
+
+    /// Same as [`StreamingClient::merge_pdfs`], but returns a [`StreamingOutput`] with the output filename, content type and trace.
+    pub async fn merge_pdfs_with_response(
+        &self,
+        files: Vec<impl Into<NamedFile>>,
+        options: MergeOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<StreamingOutput, Error> {
+        let request = GotenbergRequest::merge_pdfs(files, options)?;
+        self.send_output(request, webhook).await
+    }
+
+    /// Same as [`StreamingClient::merge_pdfs`], but copies the PDF into `writer` and returns the number of bytes written.
+    pub async fn merge_pdfs_to_writer(
+        &self,
+        files: Vec<impl Into<NamedFile>>,
+        options: MergeOptions,
+        writer: impl AsyncWrite + Unpin,
+    ) -> Result<u64, Error> {
+        let output = self.merge_pdfs_with_response(files, options, None).await?;
+        sink::write_response(output, writer, OutputKind::Pdf).await
+    }
+
+    /// Same as [`StreamingClient::merge_pdfs`], but writes the PDF to the file at `path` and returns the number of bytes written.
+    ///
+    /// See [`StreamingClient::pdf_from_url_to_path`] for how partial files are avoided.
+    pub async fn merge_pdfs_to_path(
+        &self,
+        files: Vec<impl Into<NamedFile>>,
+        options: MergeOptions,
+        path: impl AsRef<Path>,
+    ) -> Result<u64, Error> {
+        let output = self.merge_pdfs_with_response(files, options, None).await?;
+        sink::write_response_to_path(output, path.as_ref(), OutputKind::Pdf).await
+    }
+
+    /// Split a PDF file into several PDF files.
+    ///
+    /// The ZIP archive returned by the server is unpacked into a list of `(filename, bytes)` tuples.
+    /// If the server returns a single PDF (eg. when `split_unify` is set), it is returned as `file.pdf`.
+    pub async fn split_pdf(
+        &self,
+        pdf: impl Into<InputFile>,
+        options: SplitOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<Vec<(String, Bytes)>, Error> {
+        let output = self.split_pdf_with_response(pdf, options, webhook).await?;
+        archive::files_from_response(output.bytes, "file.pdf")
+    }
+
+    /// Same as [`StreamingClient::split_pdf`], but returns the raw [`RenderOutput`], see [`RenderOutput::files`] to unpack it.
+    pub async fn split_pdf_with_response(
+        &self,
+        pdf: impl Into<InputFile>,
+        options: SplitOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        let request = GotenbergRequest::split_pdf(pdf, options)?;
+        self.post_output(request, webhook).await
+    }
+
+    /// Encrypt a PDF file with a user password and an optional owner password.
+    ///
+    /// The user password is required to open the PDF, the owner password grants full access to it.
+    pub async fn encrypt_pdf(
+        &self,
+        pdf: impl Into<InputFile>,
+        user_password: impl Into<Secret>,
+        owner_password: Option<Secret>,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<impl Stream<Item = Result<Bytes, ReqwestError>>, Error> {
+        self.encrypt_pdf_with_response(pdf, user_password, owner_password, webhook)
+            .await
+            .map(|output| output.stream)
+    }
+
+    /// Same as [`StreamingClient::encrypt_pdf`], but returns a [`StreamingOutput`] with the output filename, content type and trace.
+    pub async fn encrypt_pdf_with_response(
+        &self,
+        pdf: impl Into<InputFile>,
+        user_password: impl Into<Secret>,
+        owner_password: Option<Secret>,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<StreamingOutput, Error> {
+        let request = GotenbergRequest::encrypt_pdf(pdf, user_password, owner_password);
+        self.send_output(request, webhook).await
+    }
+
+    /// Same as [`StreamingClient::encrypt_pdf`], but copies the PDF into `writer` and returns the number of bytes written.
+    pub async fn encrypt_pdf_to_writer(
+        &self,
+        pdf: impl Into<InputFile>,
+        user_password: impl Into<Secret>,
+        owner_password: Option<Secret>,
+        writer: impl AsyncWrite + Unpin,
+    ) -> Result<u64, Error> {
+        let output = self
+            .encrypt_pdf_with_response(pdf, user_password, owner_password, None)
+            .await?;
+        sink::write_response(output, writer, OutputKind::Pdf).await
+    }
+
+    /// Same as [`StreamingClient::encrypt_pdf`], but writes the PDF to the file at `path` and returns the number of bytes written.
+    ///
+    /// See [`StreamingClient::pdf_from_url_to_path`] for how partial files are avoided.
+    pub async fn encrypt_pdf_to_path(
+        &self,
+        pdf: impl Into<InputFile>,
+        user_password: impl Into<Secret>,
+        owner_password: Option<Secret>,
+        path: impl AsRef<Path>,
+    ) -> Result<u64, Error> {
+        let output = self
+            .encrypt_pdf_with_response(pdf, user_password, owner_password, None)
+            .await?;
+        sink::write_response_to_path(output, path.as_ref(), OutputKind::Pdf).await
+    }
+
+    /// Flatten a PDF file, merging its form fields and annotations into the page content so they can no longer be edited.
+    pub async fn flatten_pdf(
+        &self,
+        pdf: impl Into<InputFile>,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<impl Stream<Item = Result<Bytes, ReqwestError>>, Error> {
+        self.flatten_pdf_with_response(pdf, webhook)
+            .await
+            .map(|output| output.stream)
+    }
+
+    /// Same as [`StreamingClient::flatten_pdf`], but returns a [`StreamingOutput`] with the output filename, content type and trace.
+    pub async fn flatten_pdf_with_response(
+        &self,
+        pdf: impl Into<InputFile>,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<StreamingOutput, Error> {
+        let request = GotenbergRequest::flatten_pdf(pdf);
+        self.send_output(request, webhook).await
+    }
+
+    /// Same as [`StreamingClient::flatten_pdf`], but copies the PDF into `writer` and returns the number of bytes written.
+    pub async fn flatten_pdf_to_writer(
+        &self,
+        pdf: impl Into<InputFile>,
+        writer: impl AsyncWrite + Unpin,
+    ) -> Result<u64, Error> {
+        let output = self.flatten_pdf_with_response(pdf, None).await?;
+        sink::write_response(output, writer, OutputKind::Pdf).await
+    }
 
-        form = form.text("pdfua", pdfua.to_string());
+    /// Same as [`StreamingClient::flatten_pdf`], but writes the PDF to the file at `path` and returns the number of bytes written.
+    ///
+    /// See [`StreamingClient::pdf_from_url_to_path`] for how partial files are avoided.
+    pub async fn flatten_pdf_to_path(
+        &self,
+        pdf: impl Into<InputFile>,
+        path: impl AsRef<Path>,
+    ) -> Result<u64, Error> {
+        let output = self.flatten_pdf_with_response(pdf, None).await?;
+        sink::write_response_to_path(output, path.as_ref(), OutputKind::Pdf).await
+    }
 
-        self.post_stream("forms/pdfengines/convert", form, None)
+    /// Embed files into a PDF as attachments, eg. the XML invoice of a ZUGFeRD / Factur-X document.
+    pub async fn embed_files(
+        &self,
+        pdf: impl Into<InputFile>,
+        attachments: Vec<impl Into<EmbeddedFile>>,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<impl Stream<Item = Result<Bytes, ReqwestError>>, Error> {
+        self.embed_files_with_response(pdf, attachments, webhook)
             .await
+            .map(|output| output.stream)
+    }
+
+    /// Same as [`StreamingClient::embed_files`], but returns a [`StreamingOutput`] with the output filename, content type and trace.
+    pub async fn embed_files_with_response(
+        &self,
+        pdf: impl Into<InputFile>,
+        attachments: Vec<impl Into<EmbeddedFile>>,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<StreamingOutput, Error> {
+        let request = GotenbergRequest::embed_files(pdf, attachments)?;
+        self.send_output(request, webhook).await
+    }
+
+    /// Same as [`StreamingClient::embed_files`], but copies the PDF into `writer` and returns the number of bytes written.
+    pub async fn embed_files_to_writer(
+        &self,
+        pdf: impl Into<InputFile>,
+        attachments: Vec<impl Into<EmbeddedFile>>,
+        writer: impl AsyncWrite + Unpin,
+    ) -> Result<u64, Error> {
+        let output = self
+            .embed_files_with_response(pdf, attachments, None)
+            .await?;
+        sink::write_response(output, writer, OutputKind::Pdf).await
+    }
+
+    /// Same as [`StreamingClient::embed_files`], but writes the PDF to the file at `path` and returns the number of bytes written.
+    ///
+    /// See [`StreamingClient::pdf_from_url_to_path`] for how partial files are avoided.
+    pub async fn embed_files_to_path(
+        &self,
+        pdf: impl Into<InputFile>,
+        attachments: Vec<impl Into<EmbeddedFile>>,
+        path: impl AsRef<Path>,
+    ) -> Result<u64, Error> {
+        let output = self
+            .embed_files_with_response(pdf, attachments, None)
+            .await?;
+        sink::write_response_to_path(output, path.as_ref(), OutputKind::Pdf).await
     }
 
     /// Read the metadata of a PDF file
     pub async fn read_metadata(
         &self,
-        pdf_bytes: Vec<u8>,
+        pdf: impl Into<InputFile>,
     ) -> Result<HashMap<String, serde_json::Value>, Error> {
-        let form = multipart::Form::new();
-        let part = multipart::Part::bytes(pdf_bytes).file_name("file.pdf".to_string());
-        let form = form.part("file.pdf", part);
-
-        #[derive(Debug, Deserialize)]
-        pub struct MeatadataContainer {
-            #[serde(rename = "file.pdf")]
-            pub filepdf: HashMap<String, serde_json::Value>,
-        }
-
         let bytes = self
-            .post("forms/pdfengines/metadata/read", form, None)
+            .post(GotenbergRequest::read_metadata(pdf), None)
             .await?;
-        let metadata: MeatadataContainer = serde_json::from_slice(&bytes).map_err(|e| {
-            Error::ParseError(
-                "Metadata".to_string(),
-                String::from_utf8_lossy(&bytes).to_string(),
-                e.to_string(),
-            )
-        })?;
-
-        Ok(metadata.filepdf)
+        parse_metadata(&bytes)
     }
 
     /// Write metadata to a PDF file
     pub async fn write_metadata(
         &self,
-        pdf_bytes: Vec<u8>,
+        pdf: impl Into<InputFile>,
         metadata: HashMap<String, serde_json::Value>,
+        webhook: Option<&WebhookOptions>,
     ) -> Result<Bytes, Error> {
-        let form = multipart::Form::new();
-        let part = multipart::Part::bytes(pdf_bytes).file_name("file.pdf".to_string());
-        let form = form.part("file.pdf", part);
-        let metadata = serde_json::to_string(&metadata).map_err(|e| {
-            Error::ParseError("Metadata".to_string(), "".to_string(), e.to_string())
-        })?;
-        let part = multipart::Part::text(metadata);
-        let form = form.part("metadata", part);
-        self.post("forms/pdfengines/metadata/write", form, None)
+        self.write_metadata_with_response(pdf, metadata, webhook)
             .await
+            .map(|output| output.bytes)
+    }
+
+    /// Same as [`StreamingClient::write_metadata`], but returns a [`RenderOutput`] with the output filename, content type and trace.
+    pub async fn write_metadata_with_response(
+        &self,
+        pdf: impl Into<InputFile>,
+        metadata: HashMap<String, serde_json::Value>,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        let request = GotenbergRequest::write_metadata(pdf, metadata)?;
+        self.post_output(request, webhook).await
+    }
+
+    /// Same as [`StreamingClient::write_metadata`], but copies the PDF into `writer` and returns the number of bytes written.
+    pub async fn write_metadata_to_writer(
+        &self,
+        pdf: impl Into<InputFile>,
+        metadata: HashMap<String, serde_json::Value>,
+        writer: impl AsyncWrite + Unpin,
+    ) -> Result<u64, Error> {
+        let output = self
+            .send_output(GotenbergRequest::write_metadata(pdf, metadata)?, None)
+            .await?;
+        sink::write_response(output, writer, OutputKind::Pdf).await
+    }
+
+    /// Same as [`StreamingClient::write_metadata`], but writes the PDF to the file at `path` and returns the number of bytes written.
+    ///
+    /// See [`StreamingClient::pdf_from_url_to_path`] for how partial files are avoided.
+    pub async fn write_metadata_to_path(
+        &self,
+        pdf: impl Into<InputFile>,
+        metadata: HashMap<String, serde_json::Value>,
+        path: impl AsRef<Path>,
+    ) -> Result<u64, Error> {
+        let output = self
+            .send_output(GotenbergRequest::write_metadata(pdf, metadata)?, None)
+            .await?;
+        sink::write_response_to_path(output, path.as_ref(), OutputKind::Pdf).await
     }
 
     /// Get the health status of the Gotenberg server.
@@ -437,6 +1084,11 @@ impl StreamingClient {
         Ok(body)
     }
 
+    /// Get the version of the Gotenberg server, parsed into a [`GotenbergVersion`].
+    pub async fn version_typed(&self) -> Result<GotenbergVersion, Error> {
+        self.version().await?.parse()
+    }
+
     /// Get the metrics of the Gotenberg server in prometheus format.
     /// The results will not be parsed and are returned as a multi-line string.
     ///
@@ -452,4 +1104,120 @@ impl StreamingClient {
         let body = response.text().await.map_err(Into::into)?;
         Ok(body)
     }
+
+    /// Get the metrics of the Gotenberg server, parsed into [`metrics::GotenbergMetrics`].
+    ///
+    /// The namespace set with `--prometheus-namespace` is detected from the metric names.
+    pub async fn metrics_typed(&self) -> Result<metrics::GotenbergMetrics, Error> {
+        let body = self.metrics().await?;
+        metrics::GotenbergMetrics::parse(&body)
+    }
+}
+
+/// Uploads streamed from files, readers and streams instead of in-memory bytes.
+#[cfg(not(target_arch = "wasm32"))]
+impl StreamingClient {
+    /// Readers and streams can only be sent once, so requests uploading them are not retried.
+    fn for_upload(&self, upload: &FileUpload) -> std::borrow::Cow<'_, Self> {
+        if upload.is_repeatable() {
+            return std::borrow::Cow::Borrowed(self);
+        }
+
+        let mut client = self.clone();
+        client.retry_policy = None;
+        std::borrow::Cow::Owned(client)
+    }
+
+    /// Same as [`StreamingClient::pdf_from_doc`], but streams the document instead of loading it into memory.
+    ///
+    /// The filename of the upload must have an extension, as LibreOffice uses it to detect the format of the document.
+    pub async fn pdf_from_doc_upload(
+        &self,
+        upload: FileUpload,
+        options: DocumentOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<impl Stream<Item = Result<Bytes, ReqwestError>>, Error> {
+        self.pdf_from_doc_upload_with_response(upload, options, webhook)
+            .await
+            .map(|output| output.stream)
+    }
+
+    /// Same as [`StreamingClient::pdf_from_doc_upload`], but returns a [`StreamingOutput`] with the output filename, content type and trace.
+    pub async fn pdf_from_doc_upload_with_response(
+        &self,
+        upload: FileUpload,
+        options: DocumentOptions,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<StreamingOutput, Error> {
+        upload.check_filename()?;
+        let request = GotenbergRequest::pdf_from_doc_fields(options)?;
+        self.for_upload(&upload)
+            .send_form_output(request, webhook, || upload.doc_part())
+            .await
+    }
+
+    /// Same as [`StreamingClient::convert_pdf`], but streams the PDF instead of loading it into memory.
+    pub async fn convert_pdf_upload(
+        &self,
+        upload: FileUpload,
+        pdfa: Option<PDFFormat>,
+        pdfua: bool,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<impl Stream<Item = Result<Bytes, ReqwestError>>, Error> {
+        self.convert_pdf_upload_with_response(upload, pdfa, pdfua, webhook)
+            .await
+            .map(|output| output.stream)
+    }
+
+    /// Same as [`StreamingClient::convert_pdf_upload`], but returns a [`StreamingOutput`] with the output filename, content type and trace.
+    pub async fn convert_pdf_upload_with_response(
+        &self,
+        upload: FileUpload,
+        pdfa: Option<PDFFormat>,
+        pdfua: bool,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<StreamingOutput, Error> {
+        let request = GotenbergRequest::convert_pdf_fields(pdfa, pdfua);
+        self.for_upload(&upload)
+            .send_form_output(request, webhook, || upload.pdf_part())
+            .await
+    }
+
+    /// Same as [`StreamingClient::read_metadata`], but streams the PDF instead of loading it into memory.
+    pub async fn read_metadata_upload(
+        &self,
+        upload: FileUpload,
+    ) -> Result<HashMap<String, serde_json::Value>, Error> {
+        let request = GotenbergRequest::new("forms/pdfengines/metadata/read");
+        let response = self
+            .for_upload(&upload)
+            .send_form(request, None, || upload.pdf_part())
+            .await?;
+        parse_metadata(&response.bytes().await.map_err(Into::into)?)
+    }
+
+    /// Same as [`StreamingClient::write_metadata`], but streams the PDF instead of loading it into memory.
+    pub async fn write_metadata_upload(
+        &self,
+        upload: FileUpload,
+        metadata: HashMap<String, serde_json::Value>,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<Bytes, Error> {
+        self.write_metadata_upload_with_response(upload, metadata, webhook)
+            .await
+            .map(|output| output.bytes)
+    }
+
+    /// Same as [`StreamingClient::write_metadata_upload`], but returns a [`RenderOutput`] with the output filename, content type and trace.
+    pub async fn write_metadata_upload_with_response(
+        &self,
+        upload: FileUpload,
+        metadata: HashMap<String, serde_json::Value>,
+        webhook: Option<&WebhookOptions>,
+    ) -> Result<RenderOutput, Error> {
+        let request = GotenbergRequest::write_metadata_fields(metadata)?;
+        self.for_upload(&upload)
+            .post_form_output(request, webhook, || upload.pdf_part())
+            .await
+    }
 }
@@ -36,7 +36,7 @@ async fn test_url_to_pdf_streaming() {
     options.skip_network_idle_events = Some(false);
 
     let stream = client
-        .pdf_from_url("https://example.com", options)
+        .pdf_from_url("https://example.com", options, None)
         .await
         .unwrap();
     let data = collect_stream(stream).await;
@@ -57,7 +57,10 @@ async fn test_web_options_trace_id_streaming() {
     let mut options = WebOptions::default();
     options.trace_id = Some("test-trace-id".to_string());
 
-    let stream = client.pdf_from_html(HTML_CONTENT, options).await.unwrap();
+    let stream = client
+        .pdf_from_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
     let _pdf_bytes = collect_stream(stream).await;
 }
 
@@ -68,7 +71,10 @@ async fn test_web_options_single_page_streaming() {
     let mut options = WebOptions::default();
     options.single_page = Some(true);
 
-    let stream = client.pdf_from_html(HTML_CONTENT, options).await.unwrap();
+    let stream = client
+        .pdf_from_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
     let _pdf_bytes = collect_stream(stream).await;
 }
 
@@ -80,7 +86,10 @@ async fn test_web_options_paper_size_streaming() {
     options.paper_width = Some("210mm".parse().unwrap());
     options.paper_height = Some("297mm".parse().unwrap());
 
-    let stream = client.pdf_from_html(HTML_CONTENT, options).await.unwrap();
+    let stream = client
+        .pdf_from_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
     let _pdf_bytes = collect_stream(stream).await;
 }
 
@@ -94,7 +103,10 @@ async fn test_web_options_margins_streaming() {
     options.margin_left = Some("0.5in".parse().unwrap());
     options.margin_right = Some("0.5in".parse().unwrap());
 
-    let stream = client.pdf_from_html(HTML_CONTENT, options).await.unwrap();
+    let stream = client
+        .pdf_from_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
     let _pdf_bytes = collect_stream(stream).await;
 }
 
@@ -105,7 +117,10 @@ async fn test_web_options_prefer_css_page_size_streaming() {
     let mut options = WebOptions::default();
     options.prefer_css_page_size = Some(true);
 
-    let stream = client.pdf_from_html(HTML_CONTENT, options).await.unwrap();
+    let stream = client
+        .pdf_from_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
     let _pdf_bytes = collect_stream(stream).await;
 }
 
@@ -116,7 +131,10 @@ async fn test_web_options_print_background_streaming() {
     let mut options = WebOptions::default();
     options.print_background = Some(true);
 
-    let stream = client.pdf_from_html(HTML_CONTENT, options).await.unwrap();
+    let stream = client
+        .pdf_from_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
     let _pdf_bytes = collect_stream(stream).await;
 }
 
@@ -127,7 +145,10 @@ async fn test_web_options_landscape_streaming() {
     let mut options = WebOptions::default();
     options.landscape = Some(true);
 
-    let stream = client.pdf_from_html(HTML_CONTENT, options).await.unwrap();
+    let stream = client
+        .pdf_from_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
     let _pdf_bytes = collect_stream(stream).await;
 }
 
@@ -138,7 +159,10 @@ async fn test_web_options_scale_streaming() {
     let mut options = WebOptions::default();
     options.scale = Some(1.5);
 
-    let stream = client.pdf_from_html(HTML_CONTENT, options).await.unwrap();
+    let stream = client
+        .pdf_from_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
     let _pdf_bytes = collect_stream(stream).await;
 }
 
@@ -149,7 +173,10 @@ async fn test_web_options_page_ranges_streaming() {
     let mut options = WebOptions::default();
     options.native_page_ranges = Some("1-3,5".parse().unwrap());
 
-    let stream = client.pdf_from_html(HTML_CONTENT, options).await.unwrap();
+    let stream = client
+        .pdf_from_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
     let _pdf_bytes = collect_stream(stream).await;
 }
 
@@ -161,7 +188,10 @@ async fn test_web_options_header_footer_streaming() {
     options.header_html = Some("<h1>Header Test: <div class='title'></div></h1>".into());
     options.footer_html = Some("Page Number: <div class='pageNumber'></div>".into());
 
-    let stream = client.pdf_from_html(HTML_CONTENT, options).await.unwrap();
+    let stream = client
+        .pdf_from_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
     let _pdf_bytes = collect_stream(stream).await;
 }
 
@@ -172,7 +202,10 @@ async fn test_web_options_wait_delay_streaming() {
     let mut options = WebOptions::default();
     options.wait_delay = Some(Duration::from_secs(1));
 
-    let stream = client.pdf_from_html(HTML_CONTENT, options).await.unwrap();
+    let stream = client
+        .pdf_from_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
     let _pdf_bytes = collect_stream(stream).await;
 }
 
@@ -183,7 +216,10 @@ async fn test_web_options_emulated_media_type_streaming() {
     let mut options = WebOptions::default();
     options.emulated_media_type = Some("screen".parse().unwrap());
 
-    let stream = client.pdf_from_html(HTML_CONTENT, options).await.unwrap();
+    let stream = client
+        .pdf_from_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
     let _pdf_bytes = collect_stream(stream).await;
 }
 
@@ -194,7 +230,10 @@ async fn test_web_options_fail_on_http_status_codes_streaming() {
     let mut options = WebOptions::default();
     options.fail_on_http_status_codes = Some(vec![404, 500]);
 
-    let stream = client.pdf_from_html(HTML_CONTENT, options).await.unwrap();
+    let stream = client
+        .pdf_from_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
     let _pdf_bytes = collect_stream(stream).await;
 }
 
@@ -208,7 +247,10 @@ async fn test_web_options_metadata_streaming() {
         ("Author".to_string(), "Test Author".into()),
     ]));
 
-    let stream = client.pdf_from_html(HTML_CONTENT, options).await.unwrap();
+    let stream = client
+        .pdf_from_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
     let _pdf_bytes = collect_stream(stream).await;
 }
 
@@ -219,7 +261,10 @@ async fn test_web_options_user_agent_streaming() {
     let mut options = WebOptions::default();
     options.user_agent = Some("TestUserAgent/1.0".into());
 
-    let stream = client.pdf_from_html(HTML_CONTENT, options).await.unwrap();
+    let stream = client
+        .pdf_from_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
     let _pdf_bytes = collect_stream(stream).await;
 }
 
@@ -231,7 +276,7 @@ async fn test_web_options_negative_scale_streaming() {
     // Negative scale should fail
     options.scale = Some(-1.0);
 
-    let result = client.pdf_from_html(HTML_CONTENT, options).await;
+    let result = client.pdf_from_html(HTML_CONTENT, options, None).await;
     assert!(result.is_err(), "Expected negative scale to fail");
 }
 
@@ -243,7 +288,7 @@ async fn test_web_options_unsupported_user_agent_streaming() {
     // Unsupported user agent format
     options.user_agent = Some("\0invalid_user_agent".into());
 
-    let result = client.pdf_from_html(HTML_CONTENT, options).await;
+    let result = client.pdf_from_html(HTML_CONTENT, options, None).await;
     assert!(result.is_err(), "Expected unsupported user agent to fail");
 }
 
@@ -254,7 +299,10 @@ async fn test_screenshot_options_trace_id_streaming() {
     let mut options = ScreenshotOptions::default();
     options.trace_id = Some("test-trace-id".to_string());
 
-    let stream = client.screenshot_html(HTML_CONTENT, options).await.unwrap();
+    let stream = client
+        .screenshot_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
     let _image_bytes = collect_stream(stream).await;
 }
 
@@ -264,7 +312,10 @@ async fn test_screenshot_options_width_streaming() {
     let mut options = ScreenshotOptions::default();
     options.width = Some(1024);
 
-    let stream = client.screenshot_html(HTML_CONTENT, options).await.unwrap();
+    let stream = client
+        .screenshot_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
     let _image_bytes = collect_stream(stream).await;
 }
 
@@ -274,7 +325,10 @@ async fn test_screenshot_options_height_streaming() {
     let mut options = ScreenshotOptions::default();
     options.height = Some(768);
 
-    let stream = client.screenshot_html(HTML_CONTENT, options).await.unwrap();
+    let stream = client
+        .screenshot_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
     let _image_bytes = collect_stream(stream).await;
 }
 
@@ -284,7 +338,10 @@ async fn test_screenshot_options_clip_streaming() {
     let mut options = ScreenshotOptions::default();
     options.clip = Some(true);
 
-    let stream = client.screenshot_html(HTML_CONTENT, options).await.unwrap();
+    let stream = client
+        .screenshot_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
     let _image_bytes = collect_stream(stream).await;
 }
 
@@ -294,7 +351,10 @@ async fn test_screenshot_options_format_streaming() {
     let mut options = ScreenshotOptions::default();
     options.format = Some(ImageFormat::Jpeg);
 
-    let stream = client.screenshot_html(HTML_CONTENT, options).await.unwrap();
+    let stream = client
+        .screenshot_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
     let _image_bytes = collect_stream(stream).await;
 }
 
@@ -304,7 +364,10 @@ async fn test_screenshot_options_quality_streaming() {
     let mut options = ScreenshotOptions::default();
     options.quality = Some(85);
 
-    let stream = client.screenshot_html(HTML_CONTENT, options).await.unwrap();
+    let stream = client
+        .screenshot_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
     let _image_bytes = collect_stream(stream).await;
 }
 
@@ -314,7 +377,10 @@ async fn test_screenshot_options_omit_background_streaming() {
     let mut options = ScreenshotOptions::default();
     options.omit_background = Some(true);
 
-    let stream = client.screenshot_html(HTML_CONTENT, options).await.unwrap();
+    let stream = client
+        .screenshot_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
     let _image_bytes = collect_stream(stream).await;
 }
 
@@ -324,7 +390,10 @@ async fn test_screenshot_options_optimize_for_speed_streaming() {
     let mut options = ScreenshotOptions::default();
     options.optimize_for_speed = Some(true);
 
-    let stream = client.screenshot_html(HTML_CONTENT, options).await.unwrap();
+    let stream = client
+        .screenshot_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
     let _image_bytes = collect_stream(stream).await;
 }
 
@@ -334,7 +403,10 @@ async fn test_screenshot_options_wait_delay_streaming() {
     let mut options = ScreenshotOptions::default();
     options.wait_delay = Some(Duration::from_secs(1));
 
-    let stream = client.screenshot_html(HTML_CONTENT, options).await.unwrap();
+    let stream = client
+        .screenshot_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
     let _image_bytes = collect_stream(stream).await;
 }
 
@@ -359,7 +431,10 @@ async fn test_screenshot_options_wait_for_expression_streaming() {
     let mut options = ScreenshotOptions::default();
     options.wait_for_expression = Some("window.isReady === true".to_string());
 
-    let stream = client.screenshot_html(html_content, options).await.unwrap();
+    let stream = client
+        .screenshot_html(html_content, options, None)
+        .await
+        .unwrap();
     let _image_bytes = collect_stream(stream).await;
 }
 
@@ -369,7 +444,10 @@ async fn test_screenshot_options_emulated_media_type_streaming() {
     let mut options = ScreenshotOptions::default();
     options.emulated_media_type = Some(MediaType::Screen);
 
-    let stream = client.screenshot_html(HTML_CONTENT, options).await.unwrap();
+    let stream = client
+        .screenshot_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
     let _image_bytes = collect_stream(stream).await;
 }
 
@@ -384,7 +462,10 @@ async fn test_screenshot_options_cookies_streaming() {
         ..Default::default()
     }]);
 
-    let stream = client.screenshot_html(HTML_CONTENT, options).await.unwrap();
+    let stream = client
+        .screenshot_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
     let _image_bytes = collect_stream(stream).await;
 }
 
@@ -394,7 +475,10 @@ async fn test_screenshot_options_skip_network_idle_events_streaming() {
     let mut options = ScreenshotOptions::default();
     options.skip_network_idle_events = Some(false);
 
-    let stream = client.screenshot_html(HTML_CONTENT, options).await.unwrap();
+    let stream = client
+        .screenshot_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
     let _image_bytes = collect_stream(stream).await;
 }
 
@@ -404,7 +488,10 @@ async fn test_screenshot_options_user_agent_streaming() {
     let mut options = ScreenshotOptions::default();
     options.user_agent = Some("Test-Agent".to_string());
 
-    let stream = client.screenshot_html(HTML_CONTENT, options).await.unwrap();
+    let stream = client
+        .screenshot_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
     let _image_bytes = collect_stream(stream).await;
 }
 
@@ -421,7 +508,10 @@ async fn test_screenshot_options_extra_http_headers_streaming() {
         .collect(),
     );
 
-    let stream = client.screenshot_html(HTML_CONTENT, options).await.unwrap();
+    let stream = client
+        .screenshot_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
     let _image_bytes = collect_stream(stream).await;
 }
 
@@ -431,7 +521,10 @@ async fn test_screenshot_options_fail_on_http_status_codes_streaming() {
     let mut options = ScreenshotOptions::default();
     options.fail_on_http_status_codes = Some(vec![404, 500]);
 
-    let stream = client.screenshot_html(HTML_CONTENT, options).await.unwrap();
+    let stream = client
+        .screenshot_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
     let _image_bytes = collect_stream(stream).await;
 }
 
@@ -441,7 +534,10 @@ async fn test_screenshot_options_fail_on_resource_http_status_codes_streaming()
     let mut options = ScreenshotOptions::default();
     options.fail_on_resource_http_status_codes = Some(vec![403, 502]);
 
-    let stream = client.screenshot_html(HTML_CONTENT, options).await.unwrap();
+    let stream = client
+        .screenshot_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
     let _image_bytes = collect_stream(stream).await;
 }
 
@@ -451,7 +547,10 @@ async fn test_screenshot_options_fail_on_resource_loading_failed_streaming() {
     let mut options = ScreenshotOptions::default();
     options.fail_on_resource_loading_failed = Some(true);
 
-    let stream = client.screenshot_html(HTML_CONTENT, options).await.unwrap();
+    let stream = client
+        .screenshot_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
     let _image_bytes = collect_stream(stream).await;
 }
 
@@ -461,7 +560,10 @@ async fn test_screenshot_options_fail_on_console_exceptions_streaming() {
     let mut options = ScreenshotOptions::default();
     options.fail_on_console_exceptions = Some(true);
 
-    let stream = client.screenshot_html(HTML_CONTENT, options).await.unwrap();
+    let stream = client
+        .screenshot_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
     let _image_bytes = collect_stream(stream).await;
 }
 
@@ -472,7 +574,7 @@ async fn test_doc_options_trace_id_streaming() {
     options.trace_id = Some("some-trace-id".to_string());
 
     let stream = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
     let _pdf_content = collect_stream(stream).await;
@@ -486,9 +588,9 @@ async fn test_doc_options_password_streaming() {
 
     let stream = client
         .pdf_from_doc(
-            "example.odt",
-            PASSWORD_PROTECTED_ODT_CONTENT.to_vec(),
+            ("example.odt", PASSWORD_PROTECTED_ODT_CONTENT.to_vec()),
             options,
+            None,
         )
         .await
         .unwrap();
@@ -502,7 +604,7 @@ async fn test_doc_options_landscape_streaming() {
     options.landscape = Some(true);
 
     let stream = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
     let _pdf_content = collect_stream(stream).await;
@@ -515,7 +617,7 @@ async fn test_doc_options_export_form_fields_streaming() {
     options.export_form_fields = Some(false);
 
     let stream = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
     let _pdf_content = collect_stream(stream).await;
@@ -528,7 +630,7 @@ async fn test_doc_options_allow_duplicate_field_names_streaming() {
     options.allow_duplicate_field_names = Some(true);
 
     let stream = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
     let _pdf_content = collect_stream(stream).await;
@@ -541,7 +643,7 @@ async fn test_doc_options_export_bookmarks_streaming() {
     options.export_bookmarks = Some(false);
 
     let stream = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
     let _pdf_content = collect_stream(stream).await;
@@ -554,7 +656,7 @@ async fn test_doc_options_export_notes_streaming() {
     options.export_notes = Some(true);
 
     let stream = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
     let _pdf_content = collect_stream(stream).await;
@@ -567,7 +669,7 @@ async fn test_doc_options_quality_streaming() {
     options.quality = Some(75);
 
     let stream = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
     let _pdf_content = collect_stream(stream).await;
@@ -580,7 +682,7 @@ async fn test_doc_options_max_image_resolution_streaming() {
     options.max_image_resolution = Some(600);
 
     let stream = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
     let _pdf_content = collect_stream(stream).await;
@@ -593,7 +695,7 @@ async fn test_doc_options_pdfua_streaming() {
     options.pdfua = Some(true);
 
     let stream = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
     let _pdf_content = collect_stream(stream).await;
@@ -606,7 +708,7 @@ async fn test_doc_options_native_page_ranges_streaming() {
     options.native_page_ranges = Some("1-3,5".parse().unwrap());
 
     let stream = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
     let _pdf_content = collect_stream(stream).await;
@@ -619,7 +721,7 @@ async fn test_doc_options_export_bookmarks_to_pdf_destination_streaming() {
     options.export_bookmarks_to_pdf_destination = Some(true);
 
     let stream = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
     let _pdf_content = collect_stream(stream).await;
@@ -632,7 +734,7 @@ async fn test_doc_options_export_placeholders_streaming() {
     options.export_placeholders = Some(true);
 
     let stream = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
     let _pdf_content = collect_stream(stream).await;
@@ -645,7 +747,7 @@ async fn test_doc_options_export_notes_pages_streaming() {
     options.export_notes_pages = Some(true);
 
     let stream = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
     let _pdf_content = collect_stream(stream).await;
@@ -658,7 +760,7 @@ async fn test_doc_options_export_only_notes_pages_streaming() {
     options.export_only_notes_pages = Some(true);
 
     let stream = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
     let _pdf_content = collect_stream(stream).await;
@@ -671,7 +773,7 @@ async fn test_doc_options_export_notes_in_margin_streaming() {
     options.export_notes_in_margin = Some(true);
 
     let stream = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
     let _pdf_content = collect_stream(stream).await;
@@ -684,7 +786,7 @@ async fn test_doc_options_convert_ooo_target_to_pdf_target_streaming() {
     options.convert_ooo_target_to_pdf_target = Some(true);
 
     let stream = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
     let _pdf_content = collect_stream(stream).await;
@@ -697,7 +799,7 @@ async fn test_doc_options_export_links_relative_fsys_streaming() {
     options.export_links_relative_fsys = Some(true);
 
     let stream = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
     let _pdf_content = collect_stream(stream).await;
@@ -710,7 +812,7 @@ async fn test_doc_options_export_hidden_slides_streaming() {
     options.export_hidden_slides = Some(true);
 
     let stream = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
     let _pdf_content = collect_stream(stream).await;
@@ -723,7 +825,7 @@ async fn test_doc_options_skip_empty_pages_streaming() {
     options.skip_empty_pages = Some(true);
 
     let stream = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
     let _pdf_content = collect_stream(stream).await;
@@ -736,7 +838,7 @@ async fn test_doc_options_add_original_document_as_stream_streaming() {
     options.add_original_document_as_stream = Some(true);
 
     let stream = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
     let _pdf_content = collect_stream(stream).await;
@@ -749,7 +851,7 @@ async fn test_doc_options_single_page_sheets_streaming() {
     options.single_page_sheets = Some(true);
 
     let stream = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
     let _pdf_content = collect_stream(stream).await;
@@ -762,7 +864,7 @@ async fn test_doc_options_lossless_image_compression_streaming() {
     options.lossless_image_compression = Some(true);
 
     let stream = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
     let _pdf_content = collect_stream(stream).await;
@@ -775,7 +877,7 @@ async fn test_doc_options_reduce_image_resolution_streaming() {
     options.reduce_image_resolution = Some(true);
 
     let stream = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
     let _pdf_content = collect_stream(stream).await;
@@ -788,8 +890,321 @@ async fn test_doc_options_pdfa_streaming() {
     options.pdfa = Some(PDFFormat::A1b);
 
     let stream = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
     let _pdf_content = collect_stream(stream).await;
 }
+
+#[tokio::test]
+async fn test_merge_pdfs_streaming() {
+    let client = StreamingClient::new("http://localhost:3000");
+
+    let stream = client
+        .pdf_from_html(HTML_CONTENT, WebOptions::default(), None)
+        .await
+        .unwrap();
+    let cover = collect_stream(stream).await;
+    let stream = client
+        .pdf_from_doc(
+            ("example.docx", DOCX_CONTENT.to_vec()),
+            DocumentOptions::default(),
+            None,
+        )
+        .await
+        .unwrap();
+    let invoice = collect_stream(stream).await;
+
+    let stream = client
+        .merge_pdfs(
+            vec![
+                ("cover.pdf".to_string(), cover),
+                ("invoice.pdf".to_string(), invoice),
+            ],
+            MergeOptions::default(),
+            None,
+        )
+        .await
+        .unwrap();
+    let data = collect_stream(stream).await;
+    assert!(data.starts_with(b"%PDF"));
+}
+
+#[tokio::test]
+async fn test_split_pdf_streaming() {
+    let client = StreamingClient::new("http://localhost:3000");
+
+    let stream = client
+        .pdf_from_doc(
+            ("example.docx", DOCX_CONTENT.to_vec()),
+            DocumentOptions::default(),
+            None,
+        )
+        .await
+        .unwrap();
+    let pdf_content = collect_stream(stream).await;
+
+    let files = client
+        .split_pdf(
+            pdf_content,
+            SplitOptions::new(SplitMode::Intervals(1)),
+            None,
+        )
+        .await
+        .unwrap();
+    assert!(!files.is_empty());
+    assert!(files[0].1.starts_with(b"%PDF"));
+}
+
+#[tokio::test]
+async fn test_encrypt_pdf_streaming() {
+    let client = StreamingClient::new("http://localhost:3000");
+
+    let stream = client
+        .pdf_from_html(HTML_CONTENT, WebOptions::default(), None)
+        .await
+        .unwrap();
+    let pdf_content = collect_stream(stream).await;
+
+    let stream = client
+        .encrypt_pdf(pdf_content, "user-secret", None, None)
+        .await
+        .unwrap();
+    let data = collect_stream(stream).await;
+    assert!(data.windows(8).any(|w| w == b"/Encrypt"));
+}
+
+#[tokio::test]
+async fn test_flatten_pdf_streaming() {
+    let client = StreamingClient::new("http://localhost:3000");
+
+    let stream = client
+        .pdf_from_doc(
+            ("example.docx", DOCX_CONTENT.to_vec()),
+            DocumentOptions::default(),
+            None,
+        )
+        .await
+        .unwrap();
+    let pdf_content = collect_stream(stream).await;
+
+    let stream = client.flatten_pdf(pdf_content, None).await.unwrap();
+    let data = collect_stream(stream).await;
+    assert!(data.starts_with(b"%PDF"));
+}
+
+#[tokio::test]
+async fn test_embed_files_streaming() {
+    let client = StreamingClient::new("http://localhost:3000");
+
+    let stream = client
+        .pdf_from_html(HTML_CONTENT, WebOptions::default(), None)
+        .await
+        .unwrap();
+    let pdf_content = collect_stream(stream).await;
+
+    let attachment = Attachment::new("invoice.xml", "text/xml", b"<invoice/>".to_vec());
+    let stream = client
+        .embed_files(pdf_content, vec![attachment], None)
+        .await
+        .unwrap();
+    let data = collect_stream(stream).await;
+    assert!(data.windows(11).any(|w| w == b"invoice.xml"));
+}
+
+#[tokio::test]
+async fn test_pdfs_from_docs_streaming() {
+    let client = StreamingClient::new("http://localhost:3000");
+
+    let files = vec![
+        ("first.docx".to_string(), DOCX_CONTENT.to_vec()),
+        ("second.docx".to_string(), DOCX_CONTENT.to_vec()),
+    ];
+    let options = DocumentOptions {
+        merge: Some(true),
+        ..Default::default()
+    };
+
+    let converted = client.pdfs_from_docs(files, options, None).await.unwrap();
+    assert!(matches!(converted, ConvertedDocuments::Merged(_)));
+}
+
+#[tokio::test]
+async fn test_pdf_from_html_with_assets_streaming() {
+    let client = StreamingClient::new("http://localhost:3000");
+
+    let html = r#"<html><head><link rel="stylesheet" href="style.css"></head><body><h1>My PDF</h1></body></html>"#;
+    let bundle =
+        HtmlBundle::new(html).asset(Asset::new("style.css", b"h1 { color: red; }".to_vec()));
+
+    let stream = client
+        .pdf_from_html(bundle, WebOptions::default(), None)
+        .await
+        .unwrap();
+    let data = collect_stream(stream).await;
+    assert!(data.starts_with(b"%PDF"));
+}
+
+#[tokio::test]
+async fn test_pdf_from_html_bundle_dir_streaming() {
+    let client = StreamingClient::new("http://localhost:3000");
+
+    let bundle = HtmlBundle::from_dir("test_files/bundle").unwrap();
+    let stream = client
+        .pdf_from_html(bundle, WebOptions::default(), None)
+        .await
+        .unwrap();
+    let data = collect_stream(stream).await;
+    assert!(data.starts_with(b"%PDF"));
+}
+
+#[tokio::test]
+async fn test_retry_policy_streaming() {
+    let server = crate::test_helper::FlakyWebserver::start(2, 503);
+    let client = StreamingClient::new(&server.url).retry_policy(RetryPolicy {
+        initial_backoff: Duration::from_millis(10),
+        ..Default::default()
+    });
+
+    let stream = client
+        .pdf_from_html(HTML_CONTENT, WebOptions::default(), None)
+        .await
+        .unwrap();
+    let data = collect_stream(stream).await;
+    assert!(data.starts_with(b"%PDF"));
+    assert_eq!(server.requests(), 3);
+}
+
+#[tokio::test]
+async fn test_pdf_to_writer_streaming() {
+    let server = crate::test_helper::FlakyWebserver::start(1, 503);
+    let client = StreamingClient::new(&server.url).retry_policy(RetryPolicy {
+        initial_backoff: Duration::from_millis(10),
+        ..Default::default()
+    });
+
+    let mut pdf_content = Vec::new();
+    let written = client
+        .pdf_from_html_to_writer(HTML_CONTENT, WebOptions::default(), &mut pdf_content)
+        .await
+        .unwrap();
+    assert_eq!(written, pdf_content.len() as u64);
+    assert!(pdf_content.starts_with(b"%PDF"));
+    assert_eq!(server.requests(), 2);
+
+    let path = std::env::temp_dir().join(format!(
+        "gotenberg_pdf_streaming_to_path_{}.pdf",
+        std::process::id()
+    ));
+    let written = client
+        .pdf_from_url_to_path("https://example.com", WebOptions::default(), &path)
+        .await
+        .unwrap();
+    assert_eq!(tokio::fs::read(&path).await.unwrap().len() as u64, written);
+    tokio::fs::remove_file(&path).await.unwrap();
+
+    let mut pdf_content = Vec::new();
+    let written = client
+        .flatten_pdf_to_writer(b"%PDF-1.7".to_vec(), &mut pdf_content)
+        .await
+        .unwrap();
+    assert_eq!(written, pdf_content.len() as u64);
+
+    // Nothing is written when the request fails
+    let server = crate::test_helper::FlakyWebserver::start(1, 400);
+    let client = StreamingClient::new(&server.url);
+    let result = client
+        .pdf_from_doc_to_path(
+            ("example.docx", DOCX_CONTENT.to_vec()),
+            DocumentOptions::default(),
+            &path,
+        )
+        .await;
+    assert!(matches!(result, Err(Error::RenderingError(_))));
+    assert!(!path.exists());
+}
+
+#[tokio::test]
+async fn test_file_upload_streaming() {
+    let server = crate::test_helper::FlakyWebserver::start(0, 503);
+    let client = StreamingClient::new(&server.url);
+
+    let stream = client
+        .convert_pdf_upload(
+            FileUpload::from_path("test_files/example.docx"),
+            None,
+            true,
+            None,
+        )
+        .await
+        .unwrap();
+    let data = collect_stream(stream).await;
+    assert!(data.starts_with(b"%PDF"));
+
+    let file = tokio::fs::File::open("test_files/example.docx")
+        .await
+        .unwrap();
+    let stream = client
+        .pdf_from_doc_upload(
+            FileUpload::from_reader("example.docx", file),
+            DocumentOptions::default(),
+            None,
+        )
+        .await
+        .unwrap();
+    let data = collect_stream(stream).await;
+    assert!(data.starts_with(b"%PDF"));
+    assert_eq!(server.requests(), 2);
+}
+
+#[tokio::test]
+async fn test_pdf_from_html_with_webhook() {
+    // init test server to capture webhooks from Gotenberg
+    let server = crate::test_helper::TestWebserver::start(3003);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let client = StreamingClient::new("http://localhost:3000");
+    let webhook = WebhookOptions {
+        url: "http://host.docker.internal:3003/webhook/success".to_string(),
+        error_url: "http://host.docker.internal:3003/webhook/failure".to_string(),
+        ..Default::default()
+    };
+
+    let stream = client
+        .pdf_from_html(HTML_CONTENT, WebOptions::default(), Some(&webhook))
+        .await
+        .unwrap();
+    assert!(collect_stream(Box::pin(stream)).await.is_empty());
+
+    // Retrieve the details captured by the test server.
+    let details = server
+        .get_request_details(Duration::from_secs(20))
+        .expect("Did not receive request details");
+    assert_eq!(details.method, "POST");
+    assert_eq!(details.url, "/webhook/success");
+
+    server.join_handle.join().expect("Server thread panicked");
+}
+
+#[tokio::test]
+async fn test_pdf_from_html_with_response_streaming() {
+    let client = StreamingClient::new("http://localhost:3000");
+
+    let options = WebOptions {
+        trace_id: Some("test-trace".to_string()),
+        output_filename: Some("invoice".to_string()),
+        ..Default::default()
+    };
+
+    let output = client
+        .pdf_from_html_with_response(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
+    assert_eq!(output.content_type.as_deref(), Some("application/pdf"));
+    assert_eq!(output.filename.as_deref(), Some("invoice.pdf"));
+    assert_eq!(output.trace.as_deref(), Some("test-trace"));
+    assert!(output.webhook_ticket().is_none());
+
+    let data = collect_stream(output.stream).await;
+    assert!(data.starts_with(b"%PDF"));
+}
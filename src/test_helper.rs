@@ -1,7 +1,9 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::mpsc::{channel, Receiver};
+use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
-use tiny_http::{Response, Server};
+use tiny_http::{Header, Response, Server};
 
 /// Captures details about an incoming HTTP request.
 #[derive(Debug)]
@@ -9,6 +11,17 @@ pub struct RequestDetails {
     pub method: String,
     pub url: String,
     pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl RequestDetails {
+    /// The captured headers as a `HeaderMap`.
+    pub fn header_map(&self) -> reqwest::header::HeaderMap {
+        self.headers
+            .iter()
+            .map(|(name, value)| (name.parse().unwrap(), value.parse().unwrap()))
+            .collect()
+    }
 }
 
 /// A simple test server that listens on a given port, captures one request, and responds.
@@ -26,7 +39,10 @@ impl TestWebserver {
 
         let join_handle = thread::spawn(move || {
             let server = Server::http(&address).unwrap();
-            if let Some(request) = server.incoming_requests().next() {
+            if let Some(mut request) = server.incoming_requests().next() {
+                let mut body = Vec::new();
+                let _ = request.as_reader().read_to_end(&mut body);
+
                 // Capture request details.
                 let details = RequestDetails {
                     method: request.method().to_string(),
@@ -36,6 +52,7 @@ impl TestWebserver {
                         .iter()
                         .map(|h| (h.field.as_str().to_string(), h.value.as_str().to_string()))
                         .collect(),
+                    body,
                 };
                 // Send the captured details to the test.
                 let _ = tx.send(details);
@@ -53,3 +70,45 @@ impl TestWebserver {
         self.rx.recv_timeout(timeout).ok()
     }
 }
+
+/// A stand-in for Gotenberg that answers the first `failures` requests with the given status code,
+/// and every request after that with a minimal PDF.
+pub struct FlakyWebserver {
+    /// Base URL of the server, eg. `http://127.0.0.1:12345`.
+    pub url: String,
+    requests: Arc<AtomicUsize>,
+}
+
+impl FlakyWebserver {
+    /// Starts the server on a free port.
+    pub fn start(failures: usize, status: u16) -> Self {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", server.server_addr());
+        let requests = Arc::new(AtomicUsize::new(0));
+
+        let counter = requests.clone();
+        thread::spawn(move || {
+            for mut request in server.incoming_requests() {
+                // Read the whole form before answering, like Gotenberg does.
+                let mut body = Vec::new();
+                let _ = request.as_reader().read_to_end(&mut body);
+
+                let response = if counter.fetch_add(1, Ordering::SeqCst) < failures {
+                    Response::from_string("Service Unavailable").with_status_code(status)
+                } else {
+                    let content_type =
+                        Header::from_bytes("Content-Type", "application/pdf").unwrap();
+                    Response::from_string("%PDF-1.7").with_header(content_type)
+                };
+                let _ = request.respond(response);
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    /// The number of requests received so far.
+    pub fn requests(&self) -> usize {
+        self.requests.load(Ordering::SeqCst)
+    }
+}
@@ -1,5 +1,5 @@
 use super::*;
-use crate::test_helper::TestWebserver;
+use crate::test_helper::{FlakyWebserver, TestWebserver};
 use std::time::Duration;
 use tokio;
 
@@ -535,7 +535,7 @@ async fn test_doc_options_trace_id() {
     options.trace_id = Some("some-trace-id".to_string());
 
     let _pdf_content = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options, None)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
 }
@@ -547,8 +547,7 @@ async fn test_doc_options_password() {
 
     let _pdf_content = client
         .pdf_from_doc(
-            "example.odt",
-            PASSWORD_PROTECTED_ODT_CONTENT.to_vec(),
+            ("example.odt", PASSWORD_PROTECTED_ODT_CONTENT.to_vec()),
             options,
             None,
         )
@@ -563,7 +562,7 @@ async fn test_doc_options_landscape() {
     options.landscape = Some(true);
 
     let _pdf_content = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options, None)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
 }
@@ -575,7 +574,7 @@ async fn test_doc_options_export_form_fields() {
     options.export_form_fields = Some(false);
 
     let _pdf_content = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options, None)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
 }
@@ -587,7 +586,7 @@ async fn test_doc_options_allow_duplicate_field_names() {
     options.allow_duplicate_field_names = Some(true);
 
     let _pdf_content = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options, None)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
 }
@@ -599,7 +598,7 @@ async fn test_doc_options_export_bookmarks() {
     options.export_bookmarks = Some(false);
 
     let _pdf_content = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options, None)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
 }
@@ -611,7 +610,7 @@ async fn test_doc_options_export_notes() {
     options.export_notes = Some(true);
 
     let _pdf_content = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options, None)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
 }
@@ -623,7 +622,7 @@ async fn test_doc_options_quality() {
     options.quality = Some(75);
 
     let _pdf_content = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options, None)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
 }
@@ -635,7 +634,7 @@ async fn test_doc_options_max_image_resolution() {
     options.max_image_resolution = Some(600);
 
     let _pdf_content = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options, None)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
 }
@@ -647,7 +646,7 @@ async fn test_doc_options_pdfua() {
     options.pdfua = Some(true);
 
     let _pdf_content = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options, None)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
 }
@@ -659,7 +658,7 @@ async fn test_doc_options_native_page_ranges() {
     options.native_page_ranges = Some("1-3,5".parse().unwrap());
 
     let _pdf_content = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options, None)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
 }
@@ -671,7 +670,7 @@ async fn test_doc_options_export_bookmarks_to_pdf_destination() {
     options.export_bookmarks_to_pdf_destination = Some(true);
 
     let _pdf_content = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options, None)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
 }
@@ -683,7 +682,7 @@ async fn test_doc_options_export_placeholders() {
     options.export_placeholders = Some(true);
 
     let _pdf_content = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options, None)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
 }
@@ -695,7 +694,7 @@ async fn test_doc_options_export_notes_pages() {
     options.export_notes_pages = Some(true);
 
     let _pdf_content = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options, None)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
 }
@@ -707,7 +706,7 @@ async fn test_doc_options_export_only_notes_pages() {
     options.export_only_notes_pages = Some(true);
 
     let _pdf_content = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options, None)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
 }
@@ -719,7 +718,7 @@ async fn test_doc_options_export_notes_in_margin() {
     options.export_notes_in_margin = Some(true);
 
     let _pdf_content = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options, None)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
 }
@@ -731,7 +730,7 @@ async fn test_doc_options_convert_ooo_target_to_pdf_target() {
     options.convert_ooo_target_to_pdf_target = Some(true);
 
     let _pdf_content = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options, None)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
 }
@@ -743,7 +742,7 @@ async fn test_doc_options_export_links_relative_fsys() {
     options.export_links_relative_fsys = Some(true);
 
     let _pdf_content = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options, None)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
 }
@@ -755,7 +754,7 @@ async fn test_doc_options_export_hidden_slides() {
     options.export_hidden_slides = Some(true);
 
     let _pdf_content = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options, None)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
 }
@@ -767,7 +766,7 @@ async fn test_doc_options_skip_empty_pages() {
     options.skip_empty_pages = Some(true);
 
     let _pdf_content = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options, None)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
 }
@@ -779,7 +778,7 @@ async fn test_doc_options_add_original_document_as_stream() {
     options.add_original_document_as_stream = Some(true);
 
     let _pdf_content = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options, None)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
 }
@@ -791,7 +790,7 @@ async fn test_doc_options_single_page_sheets() {
     options.single_page_sheets = Some(true);
 
     let _pdf_content = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options, None)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
 }
@@ -803,7 +802,7 @@ async fn test_doc_options_lossless_image_compression() {
     options.lossless_image_compression = Some(true);
 
     let _pdf_content = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options, None)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
 }
@@ -815,7 +814,7 @@ async fn test_doc_options_reduce_image_resolution() {
     options.reduce_image_resolution = Some(true);
 
     let _pdf_content = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options, None)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
 }
@@ -827,7 +826,7 @@ async fn test_doc_options_pdfa() {
     options.pdfa = Some(PDFFormat::A1b);
 
     let _pdf_content = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options, None)
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
         .await
         .unwrap();
 }
@@ -839,7 +838,11 @@ async fn test_pdf_metadata() {
 
     // Create the PDF
     let pdf_content = client
-        .pdf_from_doc("example.docx", DOCX_CONTENT.to_vec(), options.clone(), None)
+        .pdf_from_doc(
+            ("example.docx", DOCX_CONTENT.to_vec()),
+            options.clone(),
+            None,
+        )
         .await
         .unwrap();
 
@@ -880,12 +883,19 @@ pub async fn test_version_string() {
 
     // It should start with 8.
     assert!(version.starts_with("8."));
+
+    let version = client.version_typed().await.unwrap();
+    assert_eq!(version.major, 8);
 }
 
 #[tokio::test]
 pub async fn test_metrics() {
     let client = Client::new("http://localhost:3000");
     let _metrics = client.metrics().await.unwrap();
+
+    let metrics = client.metrics_typed().await.unwrap();
+    assert_eq!(metrics.namespace, "gotenberg");
+    assert!(metrics.chromium_requests_queue_size.is_some());
 }
 
 #[tokio::test]
@@ -898,7 +908,7 @@ pub async fn test_webhook_options() {
         extra_http_headers: None,
     };
 
-    let headers = webhook.to_headers();
+    let headers = webhook.to_headers().unwrap();
 
     assert_eq!(headers.get("GOTENBERG-WEBHOOK-URL").unwrap(), &webhook.url);
     assert_eq!(
@@ -933,7 +943,7 @@ pub async fn test_webhook_options_headers() {
         extra_http_headers: Some(extra_headers.clone()),
     };
 
-    let headers = webhook.to_headers();
+    let headers = webhook.to_headers().unwrap();
 
     assert_eq!(headers.get("GOTENBERG-WEBHOOK-URL").unwrap(), &webhook.url);
     assert_eq!(
@@ -962,6 +972,64 @@ pub async fn test_webhook_options_headers() {
     }
 }
 
+#[test]
+fn test_webhook_options_validation() {
+    let webhook = |url: &str| WebhookOptions {
+        url: url.to_string(),
+        error_url: "https://example.com/error".to_string(),
+        ..Default::default()
+    };
+
+    assert!(webhook("https://example.com/success?id=1")
+        .to_headers()
+        .is_ok());
+    for url in [
+        "",
+        "/relative/path",
+        "ftp://example.com/success",
+        "https://example.com/success\n",
+        "https://example.com/\u{7f}success",
+        " https://example.com/success",
+    ] {
+        assert_eq!(
+            webhook(url).to_headers().unwrap_err().to_string(),
+            Error::WebhookError(WebhookError::InvalidUrl("url", url.to_string())).to_string()
+        );
+    }
+
+    let invalid_header = WebhookOptions {
+        extra_http_headers: Some(HashMap::from([(
+            "X-Custom-Header".to_string(),
+            "line\nbreak".to_string(),
+        )])),
+        ..webhook("https://example.com/success")
+    };
+    assert!(matches!(
+        invalid_header.to_headers(),
+        Err(Error::WebhookError(WebhookError::InvalidHeader(name))) if name == "X-Custom-Header"
+    ));
+}
+
+#[tokio::test]
+async fn test_invalid_webhook_is_not_sent() {
+    let server = FlakyWebserver::start(0, 503);
+    let client = Client::new(&server.url);
+    let webhook = WebhookOptions {
+        url: "https://example.com/success\r\nX-Injected: true".to_string(),
+        error_url: "https://example.com/error".to_string(),
+        ..Default::default()
+    };
+
+    let result = client
+        .flatten_pdf(b"%PDF-1.7".to_vec(), Some(&webhook))
+        .await;
+    assert!(matches!(
+        result,
+        Err(Error::WebhookError(WebhookError::InvalidUrl("url", _)))
+    ));
+    assert_eq!(server.requests(), 0);
+}
+
 #[tokio::test]
 async fn test_pdf_from_html_with_webhook() {
     // init test server to capture webhooks from Gotenberg
@@ -1001,6 +1069,1088 @@ async fn test_pdf_from_html_with_webhook() {
         .find(|(name, _)| name.to_lowercase() == "x-custom-header");
     assert_eq!(header.unwrap().1, "ABC");
 
+    let callback = webhook::WebhookCallback::from_parts(&details.header_map(), details.body);
+    match callback.unwrap() {
+        webhook::WebhookCallback::Success {
+            bytes,
+            content_type,
+            ..
+        } => {
+            assert!(bytes.starts_with(b"%PDF"));
+            assert_eq!(content_type.as_deref(), Some("application/pdf"));
+        }
+        other => panic!("Expected a successful callback, got {:?}", other),
+    }
+
     // Ensure the server thread finishes.
     server.join_handle.join().expect("Server thread panicked");
 }
+
+#[test]
+fn test_merge_filenames_keep_order() {
+    assert_eq!(merge_filename(0, "zebra.pdf").unwrap(), "00000_zebra.pdf");
+    assert_eq!(merge_filename(1, "apple.PDF").unwrap(), "00001_apple.PDF");
+    assert!(matches!(
+        merge_filename(0, "invoice.docx"),
+        Err(Error::FilenameError(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_merge_pdfs() {
+    let client = Client::new("http://localhost:3000");
+
+    let cover = client
+        .pdf_from_html(HTML_CONTENT, WebOptions::default(), None)
+        .await
+        .unwrap();
+    let invoice = client
+        .pdf_from_doc(
+            ("example.docx", DOCX_CONTENT.to_vec()),
+            DocumentOptions::default(),
+            None,
+        )
+        .await
+        .unwrap();
+
+    let options = MergeOptions {
+        trace_id: Some("test-merge".to_string()),
+        metadata: Some(HashMap::from([(
+            "Title".to_string(),
+            "Merged Document".into(),
+        )])),
+        ..Default::default()
+    };
+
+    let pdf_content = client
+        .merge_pdfs(
+            vec![
+                ("cover.pdf".to_string(), cover.to_vec()),
+                ("invoice.pdf".to_string(), invoice.to_vec()),
+            ],
+            options,
+            None,
+        )
+        .await
+        .unwrap();
+    assert!(pdf_content.starts_with(b"%PDF"));
+
+    let metadata = client.read_metadata(pdf_content.to_vec()).await.unwrap();
+    assert_eq!(
+        metadata.get("Title"),
+        Some(&serde_json::Value::String("Merged Document".to_string()))
+    );
+}
+
+#[test]
+fn test_split_mode_form_fields() {
+    assert_eq!(
+        SplitMode::Intervals(2).form_fields(),
+        [("splitMode", "intervals".into()), ("splitSpan", "2".into())]
+    );
+    assert_eq!(
+        SplitMode::Pages("1-2,4".parse().unwrap()).form_fields(),
+        [("splitMode", "pages".into()), ("splitSpan", "1-2,4".into())]
+    );
+}
+
+#[tokio::test]
+async fn test_split_pdf() {
+    let client = Client::new("http://localhost:3000");
+
+    let pdf_content = client
+        .pdf_from_doc(
+            ("example.docx", DOCX_CONTENT.to_vec()),
+            DocumentOptions::default(),
+            None,
+        )
+        .await
+        .unwrap();
+    let merged = client
+        .merge_pdfs(
+            vec![
+                ("first.pdf".to_string(), pdf_content.to_vec()),
+                ("second.pdf".to_string(), pdf_content.to_vec()),
+            ],
+            MergeOptions::default(),
+            None,
+        )
+        .await
+        .unwrap();
+
+    let files = client
+        .split_pdf(
+            merged.to_vec(),
+            SplitOptions::new(SplitMode::Intervals(1)),
+            None,
+        )
+        .await
+        .unwrap();
+    assert!(files.len() >= 2);
+    for (filename, bytes) in files {
+        assert!(filename.ends_with(".pdf"));
+        assert!(bytes.starts_with(b"%PDF"));
+    }
+
+    let options = SplitOptions {
+        split_mode: SplitMode::Pages("1".parse().unwrap()),
+        split_unify: Some(true),
+        ..Default::default()
+    };
+    let files = client
+        .split_pdf(merged.to_vec(), options, None)
+        .await
+        .unwrap();
+    assert_eq!(files.len(), 1);
+    assert!(files[0].1.starts_with(b"%PDF"));
+}
+
+#[tokio::test]
+async fn test_web_options_split_mode() {
+    let client = Client::new("http://localhost:3000");
+
+    let options = WebOptions {
+        split_mode: Some(SplitMode::Intervals(1)),
+        ..Default::default()
+    };
+
+    let bytes = client
+        .pdf_from_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
+    let files = archive::unpack(&bytes).unwrap();
+    assert!(!files.is_empty());
+}
+
+#[tokio::test]
+async fn test_web_options_encryption() {
+    let client = Client::new("http://localhost:3000");
+
+    let options = WebOptions {
+        user_password: Some("user-secret".into()),
+        owner_password: Some("owner-secret".into()),
+        ..Default::default()
+    };
+
+    let pdf_content = client
+        .pdf_from_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
+    assert!(pdf_content.windows(8).any(|w| w == b"/Encrypt"));
+}
+
+#[tokio::test]
+async fn test_doc_options_encryption() {
+    let client = Client::new("http://localhost:3000");
+
+    let options = DocumentOptions {
+        user_password: Some("user-secret".into()),
+        ..Default::default()
+    };
+
+    let pdf_content = client
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
+        .await
+        .unwrap();
+    assert!(pdf_content.windows(8).any(|w| w == b"/Encrypt"));
+}
+
+#[tokio::test]
+async fn test_encrypt_pdf() {
+    let client = Client::new("http://localhost:3000");
+
+    let pdf_content = client
+        .pdf_from_html(HTML_CONTENT, WebOptions::default(), None)
+        .await
+        .unwrap();
+    assert!(!pdf_content.windows(8).any(|w| w == b"/Encrypt"));
+
+    let pdf_content = client
+        .encrypt_pdf(
+            pdf_content.to_vec(),
+            "user-secret",
+            Some("owner-secret".into()),
+            None,
+        )
+        .await
+        .unwrap();
+    assert!(pdf_content.windows(8).any(|w| w == b"/Encrypt"));
+}
+
+#[tokio::test]
+async fn test_doc_options_flatten() {
+    let client = Client::new("http://localhost:3000");
+
+    let options = DocumentOptions {
+        export_form_fields: Some(true),
+        flatten: Some(true),
+        ..Default::default()
+    };
+
+    let pdf_content = client
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
+        .await
+        .unwrap();
+    assert!(pdf_content.starts_with(b"%PDF"));
+}
+
+#[tokio::test]
+async fn test_flatten_pdf() {
+    let client = Client::new("http://localhost:3000");
+
+    let options = DocumentOptions {
+        export_form_fields: Some(true),
+        ..Default::default()
+    };
+    let pdf_content = client
+        .pdf_from_doc(("example.docx", DOCX_CONTENT.to_vec()), options, None)
+        .await
+        .unwrap();
+
+    let pdf_content = client
+        .flatten_pdf(pdf_content.to_vec(), None)
+        .await
+        .unwrap();
+    assert!(pdf_content.starts_with(b"%PDF"));
+}
+
+const FACTURX_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rsm:CrossIndustryInvoice xmlns:rsm="urn:un:unece:uncefact:data:standard:CrossIndustryInvoice:100"/>
+"#;
+
+#[test]
+fn test_attachment_mime_type() {
+    let attachment = Attachment::new("factur-x.xml", "text/xml", FACTURX_XML.into());
+    assert_eq!(attachment.valid_mime_type().unwrap(), mime::TEXT_XML);
+
+    let attachment = Attachment::new("factur-x.xml", "not a mime type", vec![]);
+    assert!(matches!(
+        attachment.valid_mime_type(),
+        Err(Error::ParseError(..))
+    ));
+
+    // The request fails instead of sending the attachment without its MIME type
+    let options = WebOptions {
+        embeds: vec![attachment],
+        ..Default::default()
+    };
+    assert!(matches!(
+        GotenbergRequest::pdf_from_url("https://example.com", options),
+        Err(Error::ParseError(..))
+    ));
+}
+
+#[tokio::test]
+async fn test_web_options_embeds() {
+    let client = Client::new("http://localhost:3000");
+
+    let options = WebOptions {
+        pdfa: Some(PDFFormat::A3b),
+        embeds: vec![Attachment::new(
+            "factur-x.xml",
+            "text/xml",
+            FACTURX_XML.into(),
+        )],
+        ..Default::default()
+    };
+
+    let pdf_content = client
+        .pdf_from_html(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
+    assert!(pdf_content.windows(12).any(|w| w == b"factur-x.xml"));
+}
+
+#[tokio::test]
+async fn test_embed_files() {
+    let client = Client::new("http://localhost:3000");
+
+    let pdf_content = client
+        .pdf_from_html(HTML_CONTENT, WebOptions::default(), None)
+        .await
+        .unwrap();
+
+    let pdf_content = client
+        .embed_files(
+            pdf_content.to_vec(),
+            vec![Attachment::new(
+                "factur-x.xml",
+                "text/xml",
+                FACTURX_XML.into(),
+            )],
+            None,
+        )
+        .await
+        .unwrap();
+    assert!(pdf_content.windows(12).any(|w| w == b"factur-x.xml"));
+}
+
+#[test]
+fn test_converted_documents_from_response() {
+    assert_eq!(pdf_filename("report.final.docx"), "report.final.pdf");
+    assert_eq!(pdf_filename("README"), "README.pdf");
+
+    let pdf = Bytes::from_static(b"%PDF-1.7");
+    let merged =
+        ConvertedDocuments::from_response(pdf.clone(), true, &["00000_a.docx".into()]).unwrap();
+    assert_eq!(merged, ConvertedDocuments::Merged(pdf.clone()));
+
+    let single =
+        ConvertedDocuments::from_response(pdf.clone(), false, &["invoice.xlsx".into()]).unwrap();
+    assert_eq!(
+        single,
+        ConvertedDocuments::Files(vec![("invoice.pdf".to_string(), pdf)])
+    );
+
+    let webhook =
+        ConvertedDocuments::from_response(Bytes::new(), false, &["a.docx".into()]).unwrap();
+    assert_eq!(webhook, ConvertedDocuments::Files(vec![]));
+}
+
+#[tokio::test]
+async fn test_pdfs_from_docs() {
+    let client = Client::new("http://localhost:3000");
+
+    let files = vec![
+        ("first.docx".to_string(), DOCX_CONTENT.to_vec()),
+        ("second.docx".to_string(), DOCX_CONTENT.to_vec()),
+    ];
+
+    let converted = client
+        .pdfs_from_docs(files.clone(), DocumentOptions::default(), None)
+        .await
+        .unwrap();
+    match converted {
+        ConvertedDocuments::Files(files) => {
+            let mut filenames: Vec<_> = files.iter().map(|(name, _)| name.as_str()).collect();
+            filenames.sort();
+            assert_eq!(filenames, vec!["first.pdf", "second.pdf"]);
+        }
+        ConvertedDocuments::Merged(_) => panic!("Expected one PDF per document"),
+    }
+
+    let options = DocumentOptions {
+        merge: Some(true),
+        ..Default::default()
+    };
+    let converted = client.pdfs_from_docs(files, options, None).await.unwrap();
+    match converted {
+        ConvertedDocuments::Merged(bytes) => assert!(bytes.starts_with(b"%PDF")),
+        ConvertedDocuments::Files(_) => panic!("Expected a merged PDF"),
+    }
+}
+
+const BUNDLE_HTML: &str = r#"
+<!doctype html>
+<html>
+    <head><link rel="stylesheet" href="style.css"></head>
+    <body><img src="logo.svg"><h1>My PDF</h1></body>
+</html>
+"#;
+
+const LOGO_SVG: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10"><rect width="10" height="10"/></svg>"#;
+
+#[tokio::test]
+async fn test_pdf_from_html_with_assets() {
+    let client = Client::new("http://localhost:3000");
+
+    let bundle = HtmlBundle::new(BUNDLE_HTML)
+        .asset(Asset::new("style.css", b"h1 { color: red; }".to_vec()))
+        .asset(Asset::new("logo.svg", LOGO_SVG.as_bytes().to_vec()));
+
+    let pdf_content = client
+        .pdf_from_html(bundle, WebOptions::default(), None)
+        .await
+        .unwrap();
+    assert!(pdf_content.starts_with(b"%PDF"));
+
+    let bundle = HtmlBundle::new(BUNDLE_HTML).asset(Asset::new("css/style.css", vec![]));
+    let result = client
+        .pdf_from_html(bundle, WebOptions::default(), None)
+        .await;
+    assert!(matches!(result, Err(Error::FilenameError(_))));
+}
+
+#[tokio::test]
+async fn test_pdf_from_html_bundle_dir() {
+    let client = Client::new("http://localhost:3000");
+
+    let bundle = HtmlBundle::from_dir("test_files/bundle").unwrap();
+    let pdf_content = client
+        .pdf_from_html(bundle.clone(), WebOptions::default(), None)
+        .await
+        .unwrap();
+    assert!(pdf_content.starts_with(b"%PDF"));
+
+    let image = client
+        .screenshot_html(bundle, ScreenshotOptions::default(), None)
+        .await
+        .unwrap();
+    assert!(!image.is_empty());
+}
+
+#[tokio::test]
+async fn test_pdf_from_html_with_response() {
+    let client = Client::new("http://localhost:3000");
+
+    let options = WebOptions {
+        trace_id: Some("test-trace".to_string()),
+        output_filename: Some("invoice".to_string()),
+        ..Default::default()
+    };
+
+    let output = client
+        .pdf_from_html_with_response(HTML_CONTENT, options, None)
+        .await
+        .unwrap();
+    assert_eq!(output.kind(), OutputKind::Pdf);
+    assert_eq!(output.filename.as_deref(), Some("invoice.pdf"));
+    assert_eq!(output.trace.as_deref(), Some("test-trace"));
+    assert!(!output.is_empty());
+
+    let output = client
+        .screenshot_html_with_response(HTML_CONTENT, ScreenshotOptions::default(), None)
+        .await
+        .unwrap();
+    assert_eq!(output.kind(), OutputKind::Image);
+}
+
+#[test]
+fn test_error_kind_from_status() {
+    assert_eq!(ErrorKind::from_status(400), ErrorKind::InvalidFormData);
+    assert_eq!(ErrorKind::from_status(401), ErrorKind::Unauthorized);
+    assert_eq!(ErrorKind::from_status(403), ErrorKind::Forbidden);
+    assert_eq!(ErrorKind::from_status(409), ErrorKind::Conflict);
+    assert_eq!(ErrorKind::from_status(413), ErrorKind::PayloadTooLarge);
+    assert_eq!(ErrorKind::from_status(503), ErrorKind::Unavailable);
+    assert_eq!(ErrorKind::from_status(500), ErrorKind::Other);
+
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert("Gotenberg-Trace", "abc".parse().unwrap());
+    let error = ApiError::new(503, "Service Unavailable".to_string(), &headers);
+    assert_eq!(error.kind, ErrorKind::Unavailable);
+    assert_eq!(error.to_string(), "503 - Service Unavailable (trace: abc)");
+}
+
+#[tokio::test]
+async fn test_invalid_form_data_error() {
+    let client = Client::new("http://localhost:3000");
+
+    let options = WebOptions {
+        trace_id: Some("invalid-scale".to_string()),
+        scale: Some(100.0),
+        ..Default::default()
+    };
+
+    let result = client.pdf_from_html(HTML_CONTENT, options, None).await;
+    match result {
+        Err(Error::RenderingError(error)) => {
+            assert_eq!(error.kind, ErrorKind::InvalidFormData);
+            assert_eq!(error.status, 400);
+            assert_eq!(error.trace.as_deref(), Some("invalid-scale"));
+        }
+        other => panic!("Expected an invalid form data error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_retry_policy() {
+    let policy = RetryPolicy {
+        max_attempts: 3,
+        initial_backoff: Duration::from_millis(10),
+        ..Default::default()
+    };
+
+    // Recovers after two 503 responses, re-sending the full form every time
+    let server = FlakyWebserver::start(2, 503);
+    let client = Client::new(&server.url).retry_policy(policy.clone());
+    let pdf_content = client
+        .pdf_from_doc(
+            ("example.docx", DOCX_CONTENT.to_vec()),
+            DocumentOptions::default(),
+            None,
+        )
+        .await
+        .unwrap();
+    assert!(pdf_content.starts_with(b"%PDF"));
+    assert_eq!(server.requests(), 3);
+
+    // Gives up after the maximum number of attempts
+    let server = FlakyWebserver::start(5, 503);
+    let client = Client::new(&server.url).retry_policy(policy.clone());
+    let result = client.flatten_pdf(b"%PDF-1.7".to_vec(), None).await;
+    assert!(matches!(
+        result,
+        Err(Error::RenderingError(ApiError {
+            kind: ErrorKind::Unavailable,
+            ..
+        }))
+    ));
+    assert_eq!(server.requests(), 3);
+
+    // Non-retryable errors fail right away
+    let server = FlakyWebserver::start(1, 400);
+    let client = Client::new(&server.url).retry_policy(policy);
+    let result = client.flatten_pdf(b"%PDF-1.7".to_vec(), None).await;
+    assert!(result.is_err());
+    assert_eq!(server.requests(), 1);
+
+    // Without a policy, requests are sent once
+    let server = FlakyWebserver::start(1, 503);
+    let client = Client::new(&server.url);
+    assert!(client
+        .flatten_pdf(b"%PDF-1.7".to_vec(), None)
+        .await
+        .is_err());
+    assert_eq!(server.requests(), 1);
+}
+
+#[cfg(feature = "stream")]
+#[tokio::test]
+async fn test_file_upload() {
+    let policy = RetryPolicy {
+        max_attempts: 3,
+        initial_backoff: Duration::from_millis(10),
+        ..Default::default()
+    };
+
+    // Files read from a path are opened again for every attempt
+    let server = FlakyWebserver::start(1, 503);
+    let client = Client::new(&server.url).retry_policy(policy.clone());
+    let pdf_content = client
+        .pdf_from_doc_upload(
+            FileUpload::from_path("test_files/example.docx"),
+            DocumentOptions::default(),
+            None,
+        )
+        .await
+        .unwrap();
+    assert!(pdf_content.starts_with(b"%PDF"));
+    assert_eq!(server.requests(), 2);
+
+    // Readers can only be sent once, so they are not retried
+    let server = FlakyWebserver::start(1, 503);
+    let client = Client::new(&server.url).retry_policy(policy);
+    let file = tokio::fs::File::open("test_files/example.docx")
+        .await
+        .unwrap();
+    let result = client
+        .pdf_from_doc_upload(
+            FileUpload::from_reader("example.docx", file),
+            DocumentOptions::default(),
+            None,
+        )
+        .await;
+    assert!(result.is_err());
+    assert_eq!(server.requests(), 1);
+
+    let chunks: Vec<Result<Bytes, std::io::Error>> = vec![
+        Ok(Bytes::from_static(b"%PDF")),
+        Ok(Bytes::from_static(b"-1.7")),
+    ];
+    let upload = FileUpload::from_stream("file.pdf", futures::stream::iter(chunks)).length(8);
+    let pdf_content = client
+        .convert_pdf_upload(upload, None, true, None)
+        .await
+        .unwrap();
+    assert!(pdf_content.starts_with(b"%PDF"));
+
+    // Documents need an extension for LibreOffice to detect their format
+    let result = client
+        .pdf_from_doc_upload(
+            FileUpload::from_path("test_files/example.docx").filename("example"),
+            DocumentOptions::default(),
+            None,
+        )
+        .await;
+    assert!(matches!(result, Err(Error::FilenameError(_))));
+}
+
+#[tokio::test]
+async fn test_with_endpoints() {
+    // Requests are spread evenly over the instances
+    let first = FlakyWebserver::start(0, 503);
+    let second = FlakyWebserver::start(0, 503);
+    let client = Client::with_endpoints(vec![first.url.clone(), second.url.clone()]);
+    for _ in 0..4 {
+        client
+            .flatten_pdf(b"%PDF-1.7".to_vec(), None)
+            .await
+            .unwrap();
+    }
+    assert_eq!(first.requests(), 2);
+    assert_eq!(second.requests(), 2);
+
+    // Unreachable and unavailable instances are skipped
+    let unavailable = FlakyWebserver::start(100, 503);
+    let healthy = FlakyWebserver::start(0, 503);
+    let client = Client::with_endpoints(vec![
+        "http://127.0.0.1:1".to_string(),
+        unavailable.url.clone(),
+        healthy.url.clone(),
+    ])
+    .balancing(Balancing::LeastInFlight);
+    for _ in 0..3 {
+        let pdf_content = client
+            .flatten_pdf(b"%PDF-1.7".to_vec(), None)
+            .await
+            .unwrap();
+        assert!(pdf_content.starts_with(b"%PDF"));
+    }
+    assert_eq!(healthy.requests(), 3);
+}
+
+#[test]
+#[should_panic(expected = "at least one base URL")]
+fn test_with_no_endpoints() {
+    Client::with_endpoints(vec![]);
+}
+
+#[tokio::test]
+async fn test_concurrency_limits() {
+    let server = FlakyWebserver::start(0, 503);
+    let client = Client::new(&server.url).concurrency_limits(ConcurrencyLimits {
+        pdf_engines: Some(1),
+        ..Default::default()
+    });
+    assert_eq!(client.limiter_stats(Engine::Chromium), None);
+
+    let (first, second, third) = tokio::join!(
+        client.flatten_pdf(b"%PDF-1.7".to_vec(), None),
+        client.flatten_pdf(b"%PDF-1.7".to_vec(), None),
+        client.flatten_pdf(b"%PDF-1.7".to_vec(), None),
+    );
+    assert!(first.is_ok() && second.is_ok() && third.is_ok());
+
+    let stats = client.limiter_stats(Engine::PdfEngines).unwrap();
+    assert_eq!(stats.limit, 1);
+    assert_eq!(stats.acquired, 3);
+    assert_eq!(stats.in_flight, 0);
+    assert_eq!(stats.queued, 0);
+    assert!(stats.max_wait > Duration::ZERO);
+}
+
+#[tokio::test]
+async fn test_server_version() {
+    let server = FlakyWebserver::start(0, 503);
+    let client = Client::new(&server.url).server_version("8.12.0".parse().unwrap());
+
+    // Unsupported routes and options fail before anything is sent
+    let result = client.flatten_pdf(b"%PDF-1.7".to_vec(), None).await;
+    assert!(matches!(
+        result,
+        Err(Error::UnsupportedFeature(Feature::Flatten, _))
+    ));
+
+    let options = WebOptions {
+        generate_tagged_pdf: Some(true),
+        ..Default::default()
+    };
+    let result = client
+        .pdf_from_url("https://example.com", options, None)
+        .await;
+    assert!(matches!(
+        result,
+        Err(Error::UnsupportedFeature(Feature::GenerateTaggedPdf, _))
+    ));
+    assert_eq!(server.requests(), 0);
+
+    // Supported features are sent as usual
+    let options = WebOptions {
+        pdfua: Some(true),
+        ..Default::default()
+    };
+    client
+        .pdf_from_url("https://example.com", options, None)
+        .await
+        .unwrap();
+    assert_eq!(server.requests(), 1);
+}
+
+#[tokio::test]
+async fn test_webhook_callback_failure() {
+    let server = TestWebserver::start(3004);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // Send an error callback the way Gotenberg does
+    reqwest::Client::new()
+        .post("http://127.0.0.1:3004/webhook/failure")
+        .header("Content-Type", "application/json")
+        .header("Gotenberg-Trace", "abc")
+        .body(r#"{"status":400,"message":"Invalid form data"}"#)
+        .send()
+        .await
+        .unwrap();
+
+    let details = server
+        .get_request_details(Duration::from_secs(5))
+        .expect("Did not receive request details");
+    let callback =
+        webhook::WebhookCallback::from_parts(&details.header_map(), details.body).unwrap();
+    assert_eq!(
+        callback,
+        webhook::WebhookCallback::Failure {
+            status: 400,
+            message: "Invalid form data".to_string(),
+            trace: Some("abc".to_string()),
+        }
+    );
+
+    server.join_handle.join().expect("Server thread panicked");
+}
+
+#[tokio::test]
+async fn test_webhook_ticket() {
+    // Gotenberg answers requests in webhook mode with 204 No Content
+    let server = FlakyWebserver::start(usize::MAX, 204);
+    let client = Client::new(&server.url);
+    let webhook = WebhookOptions {
+        url: "http://localhost:3001/webhook/success".to_string(),
+        error_url: "http://localhost:3001/webhook/failure".to_string(),
+        ..Default::default()
+    };
+
+    // A trace is generated when none is set
+    let output = client
+        .pdf_from_url_with_response("https://example.com", WebOptions::default(), Some(&webhook))
+        .await
+        .unwrap();
+    let ticket = output.webhook_ticket().unwrap();
+    assert_eq!(ticket.trace_id.len(), 36);
+
+    let options = WebOptions {
+        trace_id: Some("my-job".to_string()),
+        ..Default::default()
+    };
+    let output = client
+        .pdf_from_url_with_response("https://example.com", options, Some(&webhook))
+        .await
+        .unwrap();
+    let ticket = output.webhook_ticket().unwrap();
+    assert_eq!(ticket.trace_id, "my-job");
+
+    // The callback is handed over to the waiting task
+    let registry = webhook::WebhookRegistry::new();
+    let wait = registry.wait(&ticket);
+    let receiver = registry.clone();
+    tokio::spawn(async move {
+        let mut headers = HeaderMap::new();
+        headers.insert("Content-Type", "application/pdf".parse().unwrap());
+        headers.insert("Gotenberg-Trace", "my-job".parse().unwrap());
+        let callback = webhook::WebhookCallback::from_parts(&headers, "%PDF-1.7").unwrap();
+        assert!(receiver.complete(callback));
+    });
+    let pdf = wait.await.unwrap().into_result().unwrap();
+    assert!(pdf.bytes.starts_with(b"%PDF"));
+
+    // Regular responses have no ticket
+    let server = FlakyWebserver::start(0, 503);
+    let output = Client::new(&server.url)
+        .pdf_from_url_with_response("https://example.com", WebOptions::default(), None)
+        .await
+        .unwrap();
+    assert_eq!(output.webhook_ticket(), None);
+}
+
+#[tokio::test]
+async fn test_download_from() {
+    let server = TestWebserver::start(3005);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let client = Client::new("http://127.0.0.1:3005");
+    let download =
+        DownloadFrom::new("https://example.com/form.pdf").header("Authorization", "Bearer token");
+    client.flatten_pdf(download.clone(), None).await.unwrap();
+
+    // The server downloads the file, so nothing is uploaded
+    let details = server
+        .get_request_details(Duration::from_secs(5))
+        .expect("Did not receive request details");
+    let body = String::from_utf8_lossy(&details.body);
+    assert!(body.contains("name=\"downloadFrom\""));
+    assert!(body.contains(
+        r#"[{"url":"https://example.com/form.pdf","extraHttpHeaders":{"Authorization":"Bearer token"}}]"#
+    ));
+    assert!(!body.contains("filename="));
+    server.join_handle.join().expect("Server thread panicked");
+
+    // Older servers do not support downloading files
+    let client = Client::new("http://127.0.0.1:1").server_version(GotenbergVersion::new(8, 9, 0));
+    let result = client.read_metadata(download).await;
+    assert!(matches!(
+        result,
+        Err(Error::UnsupportedFeature(Feature::DownloadFrom, _))
+    ));
+}
+
+#[tokio::test]
+async fn test_download_from_several_files() {
+    let transport = transport::MockTransport::new();
+    let client = Client::new("http://localhost:3000").transport(transport.clone());
+
+    let files: Vec<NamedFile> = vec![
+        ("cover.docx", b"cover".to_vec()).into(),
+        DownloadFrom::new("https://example.com/a.docx").into(),
+        DownloadFrom::new("https://example.com/b.docx").into(),
+    ];
+    client
+        .pdfs_from_docs(files, DocumentOptions::default(), None)
+        .await
+        .unwrap();
+
+    // Every download goes into a single field, next to the uploaded files
+    let request = &transport.requests()[0].request;
+    assert_eq!(request.files.len(), 1);
+    assert_eq!(request.files[0].filename, "cover.docx");
+    let downloads: Vec<_> = request
+        .fields
+        .iter()
+        .filter(|(name, _)| name == "downloadFrom")
+        .collect();
+    assert_eq!(
+        downloads,
+        vec![&(
+            "downloadFrom".to_string(),
+            r#"[{"url":"https://example.com/a.docx"},{"url":"https://example.com/b.docx"}]"#
+                .to_string()
+        )]
+    );
+
+    // Embedded files are marked as such
+    let attachments: Vec<EmbeddedFile> = vec![
+        Attachment::new("invoice.xml", "text/xml", b"<xml/>".to_vec()).into(),
+        DownloadFrom::new("https://example.com/factur-x.xml").into(),
+    ];
+    client
+        .embed_files(b"%PDF-1.7".to_vec(), attachments, None)
+        .await
+        .unwrap();
+    let request = &transport.requests()[1].request;
+    assert_eq!(request.files[1].name, "embeds");
+    assert!(request.fields.contains(&(
+        "downloadFrom".to_string(),
+        r#"[{"url":"https://example.com/factur-x.xml","embedded":true}]"#.to_string()
+    )));
+}
+
+#[tokio::test]
+async fn test_download_from_merge() {
+    let transport = transport::MockTransport::new();
+    let client = Client::new("http://localhost:3000").transport(transport.clone());
+
+    // The server names downloaded files itself, so their place in the merge cannot be kept
+    let files: Vec<NamedFile> = vec![
+        ("cover.pdf", b"%PDF-1.7".to_vec()).into(),
+        DownloadFrom::new("https://example.com/a.pdf").into(),
+    ];
+    let result = client
+        .merge_pdfs(files, MergeOptions::default(), None)
+        .await;
+    assert!(matches!(result, Err(Error::FilenameError(_))));
+
+    let files: Vec<NamedFile> = vec![
+        ("cover.docx", b"cover".to_vec()).into(),
+        DownloadFrom::new("https://example.com/a.docx").into(),
+    ];
+    let options = DocumentOptions {
+        merge: Some(true),
+        ..Default::default()
+    };
+    let result = client.pdfs_from_docs(files, options, None).await;
+    assert!(matches!(result, Err(Error::FilenameError(_))));
+
+    assert!(transport.requests().is_empty());
+}
+
+#[tokio::test]
+async fn test_download_from_doc() {
+    let transport = transport::MockTransport::new();
+    let client = Client::new("http://localhost:3000").transport(transport.clone());
+
+    client
+        .pdf_from_doc(
+            DownloadFrom::new("https://example.com/report.docx"),
+            DocumentOptions::default(),
+            None,
+        )
+        .await
+        .unwrap();
+
+    let request = &transport.requests()[0].request;
+    assert!(request.files.is_empty());
+    assert!(request.fields.contains(&(
+        "downloadFrom".to_string(),
+        r#"[{"url":"https://example.com/report.docx"}]"#.to_string()
+    )));
+}
+
+#[test]
+fn test_parse_metadata() {
+    // A downloaded PDF keeps its own filename
+    let metadata = parse_metadata(br#"{"invoice.pdf":{"Author":"Jane"}}"#).unwrap();
+    assert_eq!(metadata["Author"], "Jane");
+
+    assert!(parse_metadata(b"{}").is_err());
+    assert!(parse_metadata(b"not json").is_err());
+}
+
+#[tokio::test]
+async fn test_mock_transport() {
+    let transport = transport::MockTransport::new()
+        .respond_with(transport::MockResponse::new(503, "Service Unavailable"))
+        .respond_with(transport::MockResponse::new(400, "Invalid form data"));
+    let client = Client::new("http://localhost:3000/")
+        .auth("username", "password")
+        .retry_policy(RetryPolicy {
+            initial_backoff: Duration::from_millis(10),
+            ..Default::default()
+        })
+        .transport(transport.clone());
+
+    // The 503 is retried, the 400 is not
+    let result = client
+        .pdf_from_url("https://example.com", WebOptions::default(), None)
+        .await;
+    match result {
+        Err(Error::RenderingError(error)) => {
+            assert_eq!(error.kind, ErrorKind::InvalidFormData);
+            assert_eq!(error.message, "Invalid form data");
+        }
+        result => panic!("Expected a rendering error, got {:?}", result),
+    }
+
+    // Once the queue is empty, requests are answered with a PDF
+    let pdf = client
+        .pdf_from_url("https://example.com", WebOptions::default(), None)
+        .await
+        .unwrap();
+    assert!(pdf.starts_with(b"%PDF"));
+
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 3);
+    assert_eq!(
+        requests[0].url,
+        "http://localhost:3000/forms/chromium/convert/url"
+    );
+    assert_eq!(
+        requests[0].request.fields[0],
+        ("url".to_string(), "https://example.com".to_string())
+    );
+    assert!(requests[0].request.headers.contains(&(
+        "Authorization".to_string(),
+        "Basic dXNlcm5hbWU6cGFzc3dvcmQ=".to_string()
+    )));
+
+    // Health checks go through the transport too
+    let transport = transport::MockTransport::new()
+        .respond_with(transport::MockResponse::new(200, r#"{"status":"up","details":{"chromium":{"status":"up","timestamp":"2025-01-01T00:00:00Z"},"libreoffice":{"status":"up","timestamp":"2025-01-01T00:00:00Z"}}}"#));
+    let client = Client::new("http://localhost:3000").transport(transport.clone());
+    let health = client.health_check().await.unwrap();
+    assert!(matches!(health.status, health::HealthStatus::Up));
+    assert_eq!(transport.requests()[0].url, "http://localhost:3000/health");
+}
+
+#[cfg(feature = "stream")]
+#[tokio::test]
+async fn test_mock_transport_upload() {
+    let transport = transport::MockTransport::new();
+    let client = Client::new("http://localhost:3000").transport(transport.clone());
+
+    let upload = FileUpload::from_path("test_files/example.docx");
+    client
+        .pdf_from_doc_upload(upload, DocumentOptions::default(), None)
+        .await
+        .unwrap();
+
+    // Streamed uploads are recorded like in-memory files
+    let request = &transport.requests()[0].request;
+    assert_eq!(request.endpoint, "forms/libreoffice/convert");
+    assert_eq!(request.files[0].name, "files");
+    assert_eq!(request.files[0].filename, "example.docx");
+    assert_eq!(request.files[0].bytes, DOCX_CONTENT);
+}
+
+#[cfg(feature = "stream")]
+#[tokio::test]
+async fn test_cluster_stream_upload() {
+    let transport = transport::MockTransport::new()
+        .respond_with(transport::MockResponse::new(503, "Service Unavailable"));
+    let client = Client::with_endpoints(vec![
+        "http://gotenberg-1:3000".to_string(),
+        "http://gotenberg-2:3000".to_string(),
+    ])
+    .transport(transport.clone());
+
+    // A stream cannot fail over to the next instance, the error of the first one is returned
+    let chunks: Vec<Result<Bytes, std::io::Error>> = vec![Ok(Bytes::from_static(b"%PDF-1.7"))];
+    let upload = FileUpload::from_stream("file.pdf", futures::stream::iter(chunks));
+    let result = client.convert_pdf_upload(upload, None, true, None).await;
+    match result {
+        Err(Error::RenderingError(error)) => assert_eq!(error.kind, ErrorKind::Unavailable),
+        result => panic!("Expected the unavailable error, got {:?}", result),
+    }
+    assert_eq!(transport.requests().len(), 1);
+}
+
+#[cfg(feature = "hyper")]
+#[tokio::test]
+async fn test_hyper_transport() {
+    let server = TestWebserver::start(3006);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let client = Client::new("http://127.0.0.1:3006").transport(transport::HyperTransport::new());
+    let bytes = client
+        .flatten_pdf(b"%PDF-1.7".to_vec(), None)
+        .await
+        .unwrap();
+    assert_eq!(&bytes[..], b"OK");
+
+    let details = server
+        .get_request_details(Duration::from_secs(5))
+        .expect("Did not receive request details");
+    assert_eq!(details.url, "/forms/pdfengines/flatten");
+    let headers = details.header_map();
+    assert!(headers["Content-Type"]
+        .to_str()
+        .unwrap()
+        .starts_with("multipart/form-data; boundary="));
+    assert_eq!(
+        headers["Content-Length"].to_str().unwrap(),
+        details.body.len().to_string()
+    );
+    let body = String::from_utf8_lossy(&details.body);
+    assert!(body.contains("name=\"file.pdf\"; filename=\"file.pdf\""));
+
+    server.join_handle.join().expect("Server thread panicked");
+}
+
+#[cfg(all(feature = "hyper", feature = "stream"))]
+#[tokio::test]
+async fn test_hyper_transport_upload() {
+    let server = FlakyWebserver::start(1, 503);
+    let client = Client::new(&server.url)
+        .retry_policy(RetryPolicy {
+            initial_backoff: Duration::from_millis(10),
+            ..Default::default()
+        })
+        .transport(transport::HyperTransport::new());
+
+    let upload = FileUpload::from_path("test_files/example.docx");
+    let pdf = client
+        .pdf_from_doc_upload(upload, DocumentOptions::default(), None)
+        .await
+        .unwrap();
+    assert!(pdf.starts_with(b"%PDF"));
+    assert_eq!(server.requests(), 2);
+
+    // Connection errors are retried
+    let client = Client::new("http://127.0.0.1:9")
+        .retry_policy(RetryPolicy {
+            initial_backoff: Duration::from_millis(10),
+            max_attempts: 2,
+            ..Default::default()
+        })
+        .transport(transport::HyperTransport::new());
+    let result = client.flatten_pdf(b"%PDF-1.7".to_vec(), None).await;
+    assert!(matches!(result, Err(Error::TransportError(_))));
+}
@@ -0,0 +1,359 @@
+//! The [`Client`](crate::Client) sends its requests through a [`Transport`], and the [`BlockingClient`](crate::BlockingClient)
+//! through a [`BlockingTransport`]. Both use reqwest by default.
+//!
+//! Other backends are available behind feature flags:
+//!   - `hyper`: [`HyperTransport`], for applications already built on a hyper client.
+//!   - `ureq`: [`UreqTransport`], a blocking backend without an async runtime.
+//!
+//! [`MockTransport`] answers requests from memory, to test code using the clients without a Gotenberg server.
+//!
+//! Every request of these clients goes through their transport, including [`Client::health_check`](crate::Client::health_check),
+//! [`Client::version`](crate::Client::version) and [`Client::metrics`](crate::Client::metrics).
+//! The `StreamingClient` does not use transports: it always sends its requests with reqwest, as it hands out the reqwest response streams.
+//!
+//! # Example
+//!
+//! ```
+//! use gotenberg_pdf::transport::{MockResponse, MockTransport};
+//! use gotenberg_pdf::{Client, WebOptions};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), gotenberg_pdf::Error> {
+//! let transport = MockTransport::new().respond_with(MockResponse::pdf("%PDF-1.7"));
+//! let client = Client::new("http://localhost:3000").transport(transport.clone());
+//!
+//! let pdf = client
+//!     .pdf_from_url("https://example.com", WebOptions::default(), None)
+//!     .await?;
+//!
+//! assert_eq!(&pdf[..], b"%PDF-1.7");
+//! assert_eq!(transport.requests()[0].url, "http://localhost:3000/forms/chromium/convert/url");
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{Bytes, Error, GotenbergRequest};
+use futures_util::stream::{Stream, TryStreamExt};
+use reqwest::header::HeaderMap;
+use std::fmt::{self, Debug};
+use std::future::Future;
+use std::io::Read;
+use std::pin::Pin;
+
+#[cfg(feature = "hyper")]
+#[cfg_attr(docsrs, doc(cfg(feature = "hyper")))]
+pub use crate::hyper_transport::{HyperBody, HyperTransport};
+pub use crate::mock_transport::{MockResponse, MockTransport, RecordedRequest};
+#[cfg(feature = "ureq")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ureq")))]
+pub use crate::ureq_transport::UreqTransport;
+
+/// A stream of body chunks, for the files uploaded and the responses received.
+pub type BodyStream = Pin<Box<dyn Stream<Item = Result<Bytes, Error>> + Send>>;
+
+/// The future returned by [`Transport::send`].
+pub type TransportFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<TransportResponse, Error>> + Send + 'a>>;
+
+/// Sends requests to Gotenberg for the [`Client`](crate::Client).
+///
+/// A transport POSTs the request as a `multipart/form-data` body to the URL, with the headers of the request,
+/// and returns the response whatever its status: the client turns unsuccessful responses into errors,
+/// and retries the requests according to its [`RetryPolicy`](crate::RetryPolicy).
+///
+/// Errors sending the request should be returned as [`Error::TransportError`]. The reqwest transports return
+/// the [`Error::CommunicationError`] of reqwest instead. Both are retried like connection errors.
+pub trait Transport: Debug + Send + Sync {
+    /// Send a single request and return the response.
+    fn send(&self, request: TransportRequest) -> TransportFuture<'_>;
+
+    /// Send a GET request to `url`, eg. for the health check, and return the response.
+    fn get(&self, url: String) -> TransportFuture<'_>;
+}
+
+/// Sends requests to Gotenberg for the [`BlockingClient`](crate::BlockingClient). See [`Transport`].
+pub trait BlockingTransport: Debug + Send + Sync {
+    /// Send a single request to `url` and return the response.
+    fn send(
+        &self,
+        url: &str,
+        request: &GotenbergRequest,
+    ) -> Result<BlockingTransportResponse, Error>;
+
+    /// Send a GET request to `url`, eg. for the health check, and return the response.
+    fn get(&self, url: &str) -> Result<BlockingTransportResponse, Error>;
+}
+
+/// A request to send, along with the files streamed after its fields and files.
+pub struct TransportRequest {
+    /// The URL of the route, eg. `http://localhost:3000/forms/chromium/convert/url`.
+    pub url: String,
+
+    /// The fields, files and headers of the request.
+    pub request: GotenbergRequest,
+
+    /// Files streamed from disk or from a reader, sent after the files of the request. See [`FileUpload`](crate::FileUpload).
+    pub uploads: Vec<StreamedFile>,
+}
+
+/// A file sent without loading it into memory first.
+pub struct StreamedFile {
+    /// The name of the form field, eg. `files`.
+    pub name: String,
+
+    /// The filename sent to Gotenberg, eg. `document.docx`.
+    pub filename: String,
+
+    /// The length of the file in bytes, if known.
+    pub length: Option<u64>,
+
+    /// File contents.
+    pub stream: BodyStream,
+}
+
+impl StreamedFile {
+    /// Read the whole file into memory.
+    pub async fn bytes(self) -> Result<Bytes, Error> {
+        collect(self.stream).await
+    }
+}
+
+/// A response from Gotenberg, whatever its status.
+pub struct TransportResponse {
+    /// The HTTP status code.
+    pub status: u16,
+
+    /// The response headers, eg. `Content-Type`.
+    pub headers: HeaderMap,
+
+    /// The response body.
+    pub body: BodyStream,
+}
+
+impl TransportResponse {
+    /// Read the whole body into memory.
+    pub async fn bytes(self) -> Result<Bytes, Error> {
+        collect(self.body).await
+    }
+}
+
+/// A response from Gotenberg to a [`BlockingTransport`], whatever its status.
+///
+/// Reading from the response reads its body.
+pub struct BlockingTransportResponse {
+    /// The HTTP status code.
+    pub status: u16,
+
+    /// The response headers, eg. `Content-Type`.
+    pub headers: HeaderMap,
+
+    /// The response body.
+    pub body: Box<dyn Read + Send>,
+}
+
+impl Read for BlockingTransportResponse {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.body.read(buf)
+    }
+}
+
+impl Debug for TransportRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TransportRequest")
+            .field("url", &self.url)
+            .field("request", &self.request)
+            .field("uploads", &self.uploads)
+            .finish()
+    }
+}
+
+impl Debug for StreamedFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StreamedFile")
+            .field("name", &self.name)
+            .field("filename", &self.filename)
+            .field("length", &self.length)
+            .finish()
+    }
+}
+
+impl Debug for TransportResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TransportResponse")
+            .field("status", &self.status)
+            .field("headers", &self.headers)
+            .finish()
+    }
+}
+
+impl Debug for BlockingTransportResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BlockingTransportResponse")
+            .field("status", &self.status)
+            .field("headers", &self.headers)
+            .finish()
+    }
+}
+
+/// The default [`Transport`], sending requests with a [`reqwest::Client`].
+#[derive(Debug, Clone)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    /// Send requests with the given client.
+    pub fn new(client: reqwest::Client) -> Self {
+        ReqwestTransport { client }
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn send(&self, request: TransportRequest) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let mut form = request.request.form()?;
+            for upload in request.uploads {
+                form = form.part(upload.name.clone(), upload_part(upload).await?);
+            }
+
+            let mut req = self.client.post(&request.url).multipart(form);
+            for (name, value) in &request.request.headers {
+                req = req.header(name, value);
+            }
+            let response = req.send().await.map_err(Error::CommunicationError)?;
+            Ok(reqwest_response(response))
+        })
+    }
+
+    fn get(&self, url: String) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let response = self
+                .client
+                .get(&url)
+                .send()
+                .await
+                .map_err(Error::CommunicationError)?;
+            Ok(reqwest_response(response))
+        })
+    }
+}
+
+/// Stream the body of a reqwest response.
+fn reqwest_response(response: reqwest::Response) -> TransportResponse {
+    let status = response.status().as_u16();
+    let headers = response.headers().clone();
+    let body = futures_util::stream::try_unfold(response, |mut response| async move {
+        let chunk = response.chunk().await.map_err(Error::CommunicationError)?;
+        Ok(chunk.map(|chunk| (chunk, response)))
+    });
+    TransportResponse {
+        status,
+        headers,
+        body: Box::pin(body),
+    }
+}
+
+/// Stream the upload when reqwest can, and read it into memory otherwise.
+#[cfg(feature = "stream")]
+async fn upload_part(upload: StreamedFile) -> Result<reqwest::multipart::Part, Error> {
+    let body = reqwest::Body::wrap_stream(upload.stream);
+    let part = match upload.length {
+        Some(length) => reqwest::multipart::Part::stream_with_length(body, length),
+        None => reqwest::multipart::Part::stream(body),
+    };
+    Ok(part.file_name(upload.filename))
+}
+
+/// Stream the upload when reqwest can, and read it into memory otherwise.
+#[cfg(not(feature = "stream"))]
+async fn upload_part(upload: StreamedFile) -> Result<reqwest::multipart::Part, Error> {
+    let filename = upload.filename.clone();
+    let bytes = upload.bytes().await?;
+    Ok(
+        reqwest::multipart::Part::stream_with_length(bytes.clone(), bytes.len() as u64)
+            .file_name(filename),
+    )
+}
+
+/// The default [`BlockingTransport`], sending requests with a [`reqwest::blocking::Client`].
+#[cfg(feature = "blocking")]
+#[cfg_attr(docsrs, doc(cfg(feature = "blocking")))]
+#[derive(Debug, Clone)]
+pub struct ReqwestBlockingTransport {
+    client: reqwest::blocking::Client,
+}
+
+#[cfg(feature = "blocking")]
+impl ReqwestBlockingTransport {
+    /// Send requests with the given client.
+    pub fn new(client: reqwest::blocking::Client) -> Self {
+        ReqwestBlockingTransport { client }
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl BlockingTransport for ReqwestBlockingTransport {
+    fn send(
+        &self,
+        url: &str,
+        request: &GotenbergRequest,
+    ) -> Result<BlockingTransportResponse, Error> {
+        let mut req = self.client.post(url).multipart(request.blocking_form()?);
+        for (name, value) in &request.headers {
+            req = req.header(name, value);
+        }
+        let response = req.send().map_err(Error::CommunicationError)?;
+        Ok(blocking_reqwest_response(response))
+    }
+
+    fn get(&self, url: &str) -> Result<BlockingTransportResponse, Error> {
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .map_err(Error::CommunicationError)?;
+        Ok(blocking_reqwest_response(response))
+    }
+}
+
+/// Read the body of a blocking reqwest response.
+#[cfg(feature = "blocking")]
+fn blocking_reqwest_response(response: reqwest::blocking::Response) -> BlockingTransportResponse {
+    BlockingTransportResponse {
+        status: response.status().as_u16(),
+        headers: response.headers().clone(),
+        body: Box::new(response),
+    }
+}
+
+/// The value of the `Authorization` header for basic auth.
+pub(crate) fn basic_auth(username: &str, password: &str) -> String {
+    use base64::Engine;
+
+    let credentials = format!("{}:{}", username, password);
+    format!(
+        "Basic {}",
+        base64::engine::general_purpose::STANDARD.encode(credentials)
+    )
+}
+
+async fn collect(stream: BodyStream) -> Result<Bytes, Error> {
+    let chunks: Vec<Bytes> = stream.try_collect().await?;
+    match chunks.len() {
+        1 => Ok(chunks.into_iter().next().unwrap()),
+        _ => Ok(chunks.concat().into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_basic_auth() {
+        assert_eq!(
+            basic_auth("Aladdin", "open sesame"),
+            "Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ=="
+        );
+    }
+}
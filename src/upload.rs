@@ -0,0 +1,248 @@
+use super::*;
+use crate::transport::{BodyStream, StreamedFile};
+use futures::{Stream, TryStreamExt};
+use reqwest::Body;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// A file to upload without loading it into memory first. Available when the `stream` feature is enabled.
+///
+/// Files read from a path are opened again for every attempt, so they are retried like in-memory files.
+/// Readers and streams can only be sent once, so requests uploading them are never retried.
+///
+/// # Example
+///
+/// ```no_run
+/// use gotenberg_pdf::{Client, DocumentOptions, FileUpload};
+///
+/// # async fn example() -> Result<(), gotenberg_pdf::Error> {
+/// let client = Client::new("http://localhost:3000");
+///
+/// let upload = FileUpload::from_path("reports/large-spreadsheet.xlsx");
+/// let pdf = client
+///     .pdf_from_doc_upload(upload, DocumentOptions::default(), None)
+///     .await?;
+///
+/// let file = tokio::fs::File::open("reports/presentation.pptx")
+///     .await
+///     .map_err(gotenberg_pdf::Error::IoError)?;
+/// let upload = FileUpload::from_reader("presentation.pptx", file);
+/// let pdf = client
+///     .pdf_from_doc_upload(upload, DocumentOptions::default(), None)
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct FileUpload {
+    filename: String,
+    length: Option<u64>,
+    source: Source,
+}
+
+enum Source {
+    Path(PathBuf),
+    Body(Mutex<Option<BodyStream>>),
+}
+
+impl FileUpload {
+    /// Upload the file at the given path, named after the file.
+    ///
+    /// The file is opened when the request is sent, and its length is taken from the file system.
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        FileUpload {
+            filename: path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            length: None,
+            source: Source::Path(path.to_path_buf()),
+        }
+    }
+
+    /// Upload the contents of a reader, eg. a `tokio::fs::File`.
+    pub fn from_reader(filename: &str, reader: impl AsyncRead + Send + 'static) -> Self {
+        Self::from_stream(filename, reader_stream(reader))
+    }
+
+    /// Upload the chunks of a stream.
+    pub fn from_stream<S, E>(filename: &str, stream: S) -> Self
+    where
+        S: Stream<Item = Result<Bytes, E>> + Send + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    {
+        FileUpload {
+            filename: filename.to_string(),
+            length: None,
+            source: Source::Body(Mutex::new(Some(Box::pin(
+                stream.map_err(|e| Error::IoError(std::io::Error::other(e))),
+            )))),
+        }
+    }
+
+    /// Set the length of the file in bytes, sent as the `Content-Length` of the part.
+    ///
+    /// Without a length, the request is sent with chunked transfer encoding. Ignored for files read from a path,
+    /// whose length is always taken from the file system.
+    pub fn length(self, length: u64) -> Self {
+        FileUpload {
+            length: Some(length),
+            ..self
+        }
+    }
+
+    /// Set the filename sent to Gotenberg. LibreOffice uses the extension to detect the format of the document.
+    pub fn filename(self, filename: &str) -> Self {
+        FileUpload {
+            filename: filename.to_string(),
+            ..self
+        }
+    }
+
+    /// Check if the file can be sent several times, which is only the case for files read from a path.
+    pub fn is_repeatable(&self) -> bool {
+        matches!(self.source, Source::Path(_))
+    }
+
+    /// LibreOffice detects the format of a document from the extension of its filename.
+    pub(crate) fn check_filename(&self) -> Result<(), Error> {
+        match Path::new(&self.filename).extension() {
+            Some(_) => Ok(()),
+            None => Err(Error::FilenameError(format!(
+                "The filename of the upload must have an extension: `{}`",
+                self.filename
+            ))),
+        }
+    }
+
+    /// Open the upload as a form file with the given name and filename.
+    pub(crate) async fn streamed_file(
+        &self,
+        name: &str,
+        filename: &str,
+    ) -> Result<StreamedFile, Error> {
+        let (stream, length) = match &self.source {
+            Source::Path(path) => {
+                let file = tokio::fs::File::open(path).await.map_err(Error::IoError)?;
+                let length = file.metadata().await.map_err(Error::IoError)?.len();
+                let stream = reader_stream(file).map_err(Error::IoError);
+                (Box::pin(stream) as BodyStream, Some(length))
+            }
+            Source::Body(body) => {
+                let body = body.lock().unwrap().take().ok_or_else(|| {
+                    Error::IoError(std::io::Error::other(format!(
+                        "The upload of {} was read from a stream and cannot be sent again",
+                        self.filename
+                    )))
+                })?;
+                (body, self.length)
+            }
+        };
+
+        Ok(StreamedFile {
+            name: name.to_string(),
+            filename: filename.to_string(),
+            length,
+            stream,
+        })
+    }
+
+    /// The upload as the document to convert.
+    pub(crate) async fn doc_file(&self) -> Result<Vec<StreamedFile>, Error> {
+        Ok(vec![self.streamed_file("files", &self.filename).await?])
+    }
+
+    /// The upload as the PDF to process.
+    pub(crate) async fn pdf_file(&self) -> Result<Vec<StreamedFile>, Error> {
+        Ok(vec![self.streamed_file("file.pdf", "file.pdf").await?])
+    }
+
+    /// The upload as a streaming part of the document to convert.
+    pub(crate) async fn doc_part(&self) -> Result<Vec<(String, multipart::Part)>, Error> {
+        Ok(parts(self.doc_file().await?))
+    }
+
+    /// The upload as a streaming part of the PDF to process.
+    pub(crate) async fn pdf_part(&self) -> Result<Vec<(String, multipart::Part)>, Error> {
+        Ok(parts(self.pdf_file().await?))
+    }
+}
+
+/// Turn streamed files into streaming parts, named after their form field.
+fn parts(files: Vec<StreamedFile>) -> Vec<(String, multipart::Part)> {
+    files
+        .into_iter()
+        .map(|file| {
+            let body = Body::wrap_stream(file.stream);
+            let part = match file.length {
+                Some(length) => multipart::Part::stream_with_length(body, length),
+                None => multipart::Part::stream(body),
+            };
+            (file.name, part.file_name(file.filename))
+        })
+        .collect()
+}
+
+/// Read a reader in chunks of up to 64 KiB.
+fn reader_stream(
+    reader: impl AsyncRead + Send + 'static,
+) -> impl Stream<Item = Result<Bytes, std::io::Error>> + Send + 'static {
+    futures::stream::try_unfold(Box::pin(reader), |mut reader| async move {
+        let mut buffer = bytes::BytesMut::with_capacity(64 * 1024);
+        let read = reader.read_buf(&mut buffer).await?;
+        Ok::<_, std::io::Error>((read > 0).then(|| (buffer.freeze(), reader)))
+    })
+}
+
+impl Debug for FileUpload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let source = match &self.source {
+            Source::Path(path) => format!("{:?}", path),
+            Source::Body(_) => "stream".to_string(),
+        };
+        f.debug_struct("FileUpload")
+            .field("filename", &self.filename)
+            .field("length", &self.length)
+            .field("source", &source)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_from_path() {
+        let upload = FileUpload::from_path("test_files/example.docx");
+        assert!(upload.is_repeatable());
+        assert!(upload.check_filename().is_ok());
+
+        // A file read from a path can be sent again
+        assert!(upload.doc_part().await.is_ok());
+        assert!(upload.doc_part().await.is_ok());
+
+        // The length of a file read from a path always comes from the file system
+        let length = std::fs::metadata("test_files/example.docx").unwrap().len();
+        let files = upload.length(1).doc_file().await.unwrap();
+        assert_eq!(files[0].length, Some(length));
+
+        let upload = FileUpload::from_path("test_files/example.docx");
+        assert!(upload.filename("example").check_filename().is_err());
+
+        let missing = FileUpload::from_path("test_files/missing.docx");
+        assert!(matches!(missing.doc_part().await, Err(Error::IoError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_from_stream() {
+        let chunks: Vec<Result<Bytes, std::io::Error>> = vec![Ok(Bytes::from_static(b"%PDF"))];
+        let upload = FileUpload::from_stream("file.pdf", futures::stream::iter(chunks)).length(4);
+        assert!(!upload.is_repeatable());
+
+        // A stream can only be sent once
+        assert!(upload.pdf_part().await.is_ok());
+        assert!(matches!(upload.pdf_part().await, Err(Error::IoError(_))));
+    }
+}
@@ -0,0 +1,82 @@
+use crate::transport::{BlockingTransport, BlockingTransportResponse};
+use crate::{Error, GotenbergRequest};
+
+/// A [`BlockingTransport`] sending requests with a [`ureq::Agent`]. Available when the `ureq` feature is enabled.
+///
+/// HTTPS requires the `rustls-tls` or `native-tls` feature.
+///
+/// # Example
+///
+/// ```no_run
+/// use gotenberg_pdf::transport::UreqTransport;
+/// use gotenberg_pdf::{BlockingClient, WebOptions};
+///
+/// let client = BlockingClient::new("http://localhost:3000").transport(UreqTransport::new());
+/// let pdf = client.pdf_from_url("https://example.com", WebOptions::default(), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct UreqTransport {
+    agent: ureq::Agent,
+}
+
+impl UreqTransport {
+    /// Send requests with a new agent.
+    pub fn new() -> Self {
+        let config = ureq::Agent::config_builder()
+            .http_status_as_error(false)
+            .build();
+        UreqTransport {
+            agent: ureq::Agent::new_with_config(config),
+        }
+    }
+
+    /// Send requests with the given agent, eg. to share its connection pool or set timeouts.
+    ///
+    /// The agent must be configured with `http_status_as_error(false)`, so the client can read the errors reported by Gotenberg.
+    pub fn with_agent(agent: ureq::Agent) -> Self {
+        UreqTransport { agent }
+    }
+}
+
+impl Default for UreqTransport {
+    fn default() -> Self {
+        UreqTransport::new()
+    }
+}
+
+impl BlockingTransport for UreqTransport {
+    fn send(
+        &self,
+        url: &str,
+        request: &GotenbergRequest,
+    ) -> Result<BlockingTransportResponse, Error> {
+        let body = request.multipart_body();
+
+        let mut req = self.agent.post(url).content_type(body.content_type());
+        for (name, value) in &request.headers {
+            req = req.header(name, value);
+        }
+        let response = req
+            .send(&body.to_bytes()[..])
+            .map_err(|e| Error::TransportError(Box::new(e)))?;
+        Ok(blocking_response(response))
+    }
+
+    fn get(&self, url: &str) -> Result<BlockingTransportResponse, Error> {
+        let response = self
+            .agent
+            .get(url)
+            .call()
+            .map_err(|e| Error::TransportError(Box::new(e)))?;
+        Ok(blocking_response(response))
+    }
+}
+
+fn blocking_response(response: ureq::http::Response<ureq::Body>) -> BlockingTransportResponse {
+    let (parts, body) = response.into_parts();
+    BlockingTransportResponse {
+        status: parts.status.as_u16(),
+        headers: parts.headers,
+        body: Box::new(body.into_reader()),
+    }
+}
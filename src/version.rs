@@ -0,0 +1,252 @@
+use super::*;
+use std::cmp::Ordering;
+
+/// A Gotenberg release, as reported by the `/version` route.
+///
+/// # Example
+///
+/// ```
+/// use gotenberg_pdf::{Feature, GotenbergVersion};
+///
+/// let version: GotenbergVersion = "8.12.0".parse().unwrap();
+/// assert!(version.supports(Feature::Split));
+/// assert!(!version.supports(Feature::Flatten));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GotenbergVersion {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+
+    /// The pre-release identifier, eg. `rc1` for `8.0.0-rc1`.
+    pub pre: Option<String>,
+}
+
+impl GotenbergVersion {
+    pub const fn new(major: u64, minor: u64, patch: u64) -> Self {
+        GotenbergVersion {
+            major,
+            minor,
+            patch,
+            pre: None,
+        }
+    }
+
+    /// Check if this release supports the given feature.
+    pub fn supports(&self, feature: Feature) -> bool {
+        *self >= feature.since()
+    }
+}
+
+impl Ord for GotenbergVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            // A pre-release comes before the release itself
+            .then_with(|| match (&self.pre, &other.pre) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(a), Some(b)) => a.cmp(b),
+            })
+    }
+}
+
+impl PartialOrd for GotenbergVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for GotenbergVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(pre) = &self.pre {
+            write!(f, "-{}", pre)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for GotenbergVersion {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = |message: &str| {
+            Error::ParseError(
+                "GotenbergVersion".to_string(),
+                s.to_string(),
+                message.to_string(),
+            )
+        };
+
+        let trimmed = s.trim();
+        let trimmed = trimmed.strip_prefix('v').unwrap_or(trimmed);
+
+        // Build metadata does not affect the version, eg. `8.12.0+snapshot`
+        let trimmed = trimmed.split('+').next().unwrap_or(trimmed);
+        let (core, pre) = match trimmed.split_once('-') {
+            Some((core, pre)) => (core, Some(pre.to_string())),
+            None => (trimmed, None),
+        };
+
+        let mut numbers = core.split('.').map(|part| {
+            part.parse::<u64>()
+                .map_err(|_| error("expected a version like `8.12.0`"))
+        });
+        let major = numbers
+            .next()
+            .ok_or_else(|| error("missing major version"))??;
+        let minor = numbers.next().transpose()?.unwrap_or(0);
+        let patch = numbers.next().transpose()?.unwrap_or(0);
+        if numbers.next().is_some() {
+            return Err(error("expected at most three version numbers"));
+        }
+
+        Ok(GotenbergVersion {
+            major,
+            minor,
+            patch,
+            pre,
+        })
+    }
+}
+
+/// Options and routes that only exist in some Gotenberg releases.
+///
+/// Set the version of the server with [`Client::server_version`] to fail fast with
+/// [`Error::UnsupportedFeature`] instead of sending a request the server ignores or rejects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Feature {
+    /// The `pdfua` option, for PDF/UA compliant output.
+    PdfUa,
+    /// The split route and the `splitMode` option.
+    Split,
+    /// The `downloadFrom` field, for files downloaded by the server, see [`DownloadFrom`].
+    DownloadFrom,
+    /// The flatten route and the `flatten` option.
+    Flatten,
+    /// The `generateTaggedPdf` option of the Chromium routes.
+    GenerateTaggedPdf,
+    /// The encrypt route and the `userPassword` / `ownerPassword` options.
+    Encryption,
+    /// The embed route, for attaching files to a PDF.
+    EmbedFiles,
+}
+
+impl Feature {
+    /// The first Gotenberg release supporting the feature.
+    pub const fn since(&self) -> GotenbergVersion {
+        match self {
+            Feature::PdfUa => GotenbergVersion::new(8, 0, 0),
+            Feature::Split => GotenbergVersion::new(8, 10, 0),
+            Feature::DownloadFrom => GotenbergVersion::new(8, 10, 0),
+            Feature::Flatten => GotenbergVersion::new(8, 15, 0),
+            Feature::GenerateTaggedPdf => GotenbergVersion::new(8, 17, 0),
+            Feature::Encryption => GotenbergVersion::new(8, 19, 0),
+            Feature::EmbedFiles => GotenbergVersion::new(8, 20, 0),
+        }
+    }
+
+    /// The feature needed for the given route, eg. `forms/pdfengines/split`.
+    pub(crate) fn from_endpoint(endpoint: &str) -> Option<Self> {
+        match endpoint.trim_start_matches('/') {
+            "forms/pdfengines/split" => Some(Feature::Split),
+            "forms/pdfengines/flatten" => Some(Feature::Flatten),
+            "forms/pdfengines/encrypt" => Some(Feature::Encryption),
+            "forms/pdfengines/embed" => Some(Feature::EmbedFiles),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Feature::PdfUa => "PDF/UA",
+            Feature::Split => "Splitting",
+            Feature::DownloadFrom => "Downloading files from URLs",
+            Feature::Flatten => "Flattening",
+            Feature::GenerateTaggedPdf => "Tagged PDF generation",
+            Feature::Encryption => "Encryption",
+            Feature::EmbedFiles => "Embedding files",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Check that the server supports the route and the features used by the options, if its version is known.
+pub(crate) fn check_features(
+    version: Option<&GotenbergVersion>,
+    endpoint: &str,
+    features: &[Feature],
+) -> Result<(), Error> {
+    let Some(version) = version else {
+        return Ok(());
+    };
+
+    let unsupported = Feature::from_endpoint(endpoint)
+        .into_iter()
+        .chain(features.iter().copied())
+        .find(|feature| !version.supports(*feature));
+    match unsupported {
+        Some(feature) => Err(Error::UnsupportedFeature(feature, version.clone())),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(s: &str) -> GotenbergVersion {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(version("8.12.0"), GotenbergVersion::new(8, 12, 0));
+        assert_eq!(version(" v8.12.3\n"), GotenbergVersion::new(8, 12, 3));
+        assert_eq!(version("8.1"), GotenbergVersion::new(8, 1, 0));
+        assert_eq!(version("8.0.0-rc1+build").pre.as_deref(), Some("rc1"));
+        assert_eq!(version("8.0.0-rc1").to_string(), "8.0.0-rc1");
+
+        assert!("".parse::<GotenbergVersion>().is_err());
+        assert!("eight".parse::<GotenbergVersion>().is_err());
+        assert!("8.0.0.1".parse::<GotenbergVersion>().is_err());
+    }
+
+    #[test]
+    fn test_ordering() {
+        assert!(version("8.10.0") > version("8.9.9"));
+        assert!(version("8.0.0") > version("8.0.0-rc2"));
+        assert!(version("8.0.0-rc2") > version("8.0.0-rc1"));
+        assert!(version("7.10.2") < version("8.0.0"));
+    }
+
+    #[test]
+    fn test_check_features() {
+        let old = version("7.10.2");
+        assert!(check_features(None, "forms/pdfengines/flatten", &[Feature::Flatten]).is_ok());
+        assert!(check_features(Some(&old), "forms/chromium/convert/url", &[]).is_ok());
+
+        match check_features(Some(&old), "forms/pdfengines/split", &[]) {
+            Err(Error::UnsupportedFeature(feature, server)) => {
+                assert_eq!(feature, Feature::Split);
+                assert_eq!(server, old);
+            }
+            other => panic!("Expected an unsupported feature error, got {:?}", other),
+        }
+
+        let error = check_features(
+            Some(&version("8.12.0")),
+            "forms/chromium/convert/url",
+            &[Feature::PdfUa, Feature::Encryption],
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "gotenberg_pdf: Encryption requires Gotenberg 8.19.0 or later, but the server runs 8.12.0"
+        );
+    }
+}
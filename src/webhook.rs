@@ -0,0 +1,672 @@
+//! Helpers for receiving the callbacks Gotenberg sends in webhook mode.
+//!
+//! With [`WebhookOptions`](crate::WebhookOptions) set, Gotenberg uploads the resulting file to `url`,
+//! or a JSON payload with the status and message of the failure to `error_url`.
+//! [`WebhookCallback::from_parts`] turns either request into a typed result, whatever HTTP server receives it.
+//!
+//! With the `axum` feature enabled, [`WebhookCallback`] can be used as an axum extractor directly.
+//! Mind the default body limit of axum (2MB) and raise it with `DefaultBodyLimit` for large files.
+//! With the `hyper` feature enabled, [`WebhookCallback::from_hyper_request`] reads the callback from a hyper request.
+//!
+//! Callbacks are matched with the request they belong to by the trace of the request. [`Client`](crate::Client)
+//! generates a trace for webhook requests without one, see [`RenderOutput::webhook_ticket`].
+//! A [`WebhookRegistry`] hands callbacks received by your HTTP server over to the tasks waiting for them.
+
+use crate::{random_u64, ApiError, Error, ErrorKind, RenderOutput};
+use bytes::Bytes;
+use reqwest::header::{HeaderMap, CONTENT_TYPE};
+use serde::Deserialize;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::collections::{HashMap, VecDeque};
+#[cfg(not(target_arch = "wasm32"))]
+use std::future::Future;
+#[cfg(not(target_arch = "wasm32"))]
+use std::pin::Pin;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::{Arc, Mutex, Weak};
+#[cfg(not(target_arch = "wasm32"))]
+use std::task::{Context, Poll};
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::sync::oneshot;
+
+/// Identifies a request sent in webhook mode, to match it with its callback.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WebhookTicket {
+    /// The trace of the request, sent back by Gotenberg in the `Gotenberg-Trace` header of the callback.
+    pub trace_id: String,
+}
+
+/// Generate a random trace in the UUID v4 format Gotenberg uses itself.
+pub(crate) fn generate_trace_id() -> String {
+    let (high, low) = (random_u64(), random_u64());
+
+    // Set the version (4) and variant (RFC 4122) bits
+    let high = (high & 0xffff_ffff_ffff_0fff) | 0x0000_0000_0000_4000;
+    let low = (low & 0x3fff_ffff_ffff_ffff) | 0x8000_0000_0000_0000;
+    format!(
+        "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+        high >> 32,
+        (high >> 16) & 0xffff,
+        high & 0xffff,
+        low >> 48,
+        low & 0xffff_ffff_ffff
+    )
+}
+
+/// A callback received from Gotenberg in webhook mode.
+///
+/// # Example
+///
+/// ```
+/// use gotenberg_pdf::webhook::WebhookCallback;
+/// use reqwest::header::HeaderMap;
+///
+/// let mut headers = HeaderMap::new();
+/// headers.insert("Content-Type", "application/json".parse().unwrap());
+/// headers.insert("Gotenberg-Trace", "abc".parse().unwrap());
+///
+/// let body = r#"{"status": 400, "message": "Invalid form data"}"#;
+/// match WebhookCallback::from_parts(&headers, body).unwrap() {
+///     WebhookCallback::Success { bytes, .. } => println!("Received {} bytes", bytes.len()),
+///     WebhookCallback::Failure { status, message, trace } => {
+///         assert_eq!(status, 400);
+///         assert_eq!(message, "Invalid form data");
+///         assert_eq!(trace.as_deref(), Some("abc"));
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum WebhookCallback {
+    /// The resulting file, sent to the webhook `url`.
+    Success {
+        /// The rendered file.
+        bytes: Bytes,
+        /// The `Content-Type` of the file, eg. `application/pdf`.
+        content_type: Option<String>,
+        /// The filename from the `Content-Disposition` header.
+        filename: Option<String>,
+        /// The trace of the request, from the `Gotenberg-Trace` header.
+        trace: Option<String>,
+    },
+
+    /// The reason the conversion failed, sent to the webhook `error_url`.
+    Failure {
+        /// The HTTP status code Gotenberg would have answered with.
+        status: u16,
+        /// The error message.
+        message: String,
+        /// The trace of the request, from the `Gotenberg-Trace` header.
+        trace: Option<String>,
+    },
+}
+
+/// The JSON payload Gotenberg sends to the error URL.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FailurePayload {
+    status: u16,
+    message: String,
+}
+
+impl WebhookCallback {
+    /// Parse a callback from the headers and the body of the request Gotenberg sent.
+    ///
+    /// A JSON body holding only the `status` and `message` of an error is a failure. Anything else is the resulting file,
+    /// including the JSON some routes produce, eg. the metadata read from a PDF. A body sent as JSON that is not valid JSON
+    /// is rejected with an [`Error::ParseError`].
+    pub fn from_parts(headers: &HeaderMap, body: impl Into<Bytes>) -> Result<Self, Error> {
+        let output = RenderOutput::from_parts(headers, body.into());
+
+        let is_json = headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(';').next())
+            .map(|value| value.trim().eq_ignore_ascii_case("application/json"))
+            .unwrap_or(false);
+        if is_json {
+            let value: serde_json::Value = serde_json::from_slice(&output.bytes).map_err(|e| {
+                Error::ParseError(
+                    "WebhookCallback".to_string(),
+                    String::from_utf8_lossy(&output.bytes).to_string(),
+                    e.to_string(),
+                )
+            })?;
+            match serde_json::from_value::<FailurePayload>(value) {
+                Ok(payload) if payload.status >= 400 => {
+                    return Ok(WebhookCallback::Failure {
+                        status: payload.status,
+                        message: payload.message,
+                        trace: output.trace,
+                    })
+                }
+                _ => {}
+            }
+        }
+
+        Ok(WebhookCallback::Success {
+            bytes: output.bytes,
+            content_type: output.content_type,
+            filename: output.filename,
+            trace: output.trace,
+        })
+    }
+
+    /// The trace of the request the callback belongs to.
+    pub fn trace(&self) -> Option<&str> {
+        match self {
+            WebhookCallback::Success { trace, .. } | WebhookCallback::Failure { trace, .. } => {
+                trace.as_deref()
+            }
+        }
+    }
+
+    /// Turn the callback into the same result a request without webhook returns,
+    /// a failure becoming an [`Error::RenderingError`].
+    pub fn into_result(self) -> Result<RenderOutput, Error> {
+        match self {
+            WebhookCallback::Success {
+                bytes,
+                content_type,
+                filename,
+                trace,
+            } => Ok(RenderOutput {
+                bytes,
+                content_type,
+                filename,
+                trace,
+            }),
+            WebhookCallback::Failure {
+                status,
+                message,
+                trace,
+            } => Err(Error::RenderingError(ApiError {
+                kind: ErrorKind::from_status(status),
+                status,
+                message,
+                trace,
+            })),
+        }
+    }
+}
+
+/// Hands webhook callbacks over to the tasks waiting for them, matching them by trace.
+///
+/// The registry can be freely cloned and shared between the task receiving callbacks and the tasks waiting for them.
+/// Callbacks that arrive before anybody waits for them are kept until they are claimed, up to
+/// [`WebhookRegistry::max_unclaimed`] callbacks, after which the oldest ones are dropped.
+///
+/// Not available on wasm32.
+///
+/// # Example
+///
+/// ```no_run
+/// use gotenberg_pdf::webhook::{WebhookCallback, WebhookRegistry};
+/// use gotenberg_pdf::{Client, WebOptions, WebhookOptions};
+///
+/// # async fn example(registry: WebhookRegistry) -> Result<(), gotenberg_pdf::Error> {
+/// let client = Client::new("http://localhost:3000");
+/// let webhook = WebhookOptions {
+///     url: "http://my-service:8080/webhook/success".to_string(),
+///     error_url: "http://my-service:8080/webhook/failure".to_string(),
+///     ..Default::default()
+/// };
+///
+/// let output = client
+///     .pdf_from_url_with_response("https://example.com", WebOptions::default(), Some(&webhook))
+///     .await?;
+/// let ticket = output.webhook_ticket().unwrap();
+///
+/// // Meanwhile, the HTTP server receiving the callbacks calls `registry.complete(callback)`
+/// let pdf = registry.wait(&ticket).await.unwrap().into_result()?;
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Default)]
+pub struct WebhookRegistry {
+    entries: Arc<Mutex<Entries>>,
+}
+
+/// The default number of unclaimed callbacks kept by a [`WebhookRegistry`].
+#[cfg(not(target_arch = "wasm32"))]
+pub const DEFAULT_MAX_UNCLAIMED: usize = 1024;
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+struct Entries {
+    entries: HashMap<String, Entry>,
+    /// The traces of the unclaimed callbacks, oldest first.
+    unclaimed: VecDeque<String>,
+    max_unclaimed: usize,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Default for Entries {
+    fn default() -> Self {
+        Entries {
+            entries: HashMap::new(),
+            unclaimed: VecDeque::new(),
+            max_unclaimed: DEFAULT_MAX_UNCLAIMED,
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Entries {
+    fn remove(&mut self, trace: &str) -> Option<Entry> {
+        let entry = self.entries.remove(trace);
+        if let Some(Entry::Unclaimed(_)) = entry {
+            self.unclaimed.retain(|unclaimed| unclaimed != trace);
+        }
+        entry
+    }
+
+    /// Keep an unclaimed callback, dropping the oldest ones beyond the limit.
+    fn keep(&mut self, trace: String, callback: WebhookCallback) {
+        self.unclaimed.push_back(trace.clone());
+        self.entries.insert(trace, Entry::Unclaimed(callback));
+        while self.unclaimed.len() > self.max_unclaimed {
+            if let Some(oldest) = self.unclaimed.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+enum Entry {
+    Waiting(oneshot::Sender<WebhookCallback>),
+    Unclaimed(WebhookCallback),
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl WebhookRegistry {
+    /// Create an empty registry keeping up to [`DEFAULT_MAX_UNCLAIMED`] unclaimed callbacks.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the number of callbacks kept while nobody waits for them, [`DEFAULT_MAX_UNCLAIMED`] by default.
+    ///
+    /// Once the limit is reached, the oldest unclaimed callback is dropped for every new one.
+    pub fn max_unclaimed(self, max_unclaimed: usize) -> Self {
+        self.entries.lock().unwrap().max_unclaimed = max_unclaimed;
+        self
+    }
+
+    /// Wait for the callback of the request with the given ticket.
+    ///
+    /// The returned future resolves to `None` if the callback is never handed over because the wait was
+    /// replaced by a later one for the same ticket, or because it was cancelled with [`WebhookRegistry::forget`].
+    /// Dropping the future, eg. when it times out, stops waiting like [`WebhookRegistry::forget`] does.
+    pub fn wait(&self, ticket: &WebhookTicket) -> WebhookWait {
+        let (sender, receiver) = oneshot::channel();
+        let mut entries = self.entries.lock().unwrap();
+        match entries.remove(&ticket.trace_id) {
+            Some(Entry::Unclaimed(callback)) => {
+                let _ = sender.send(callback);
+            }
+            _ => {
+                entries
+                    .entries
+                    .insert(ticket.trace_id.clone(), Entry::Waiting(sender));
+            }
+        }
+
+        WebhookWait {
+            receiver,
+            trace: ticket.trace_id.clone(),
+            entries: Arc::downgrade(&self.entries),
+        }
+    }
+
+    /// Hand a received callback over to the task waiting for it.
+    ///
+    /// Returns `true` if a task was waiting. Otherwise the callback is kept until it is waited for,
+    /// unless it has no trace to match it by or it is dropped for newer unclaimed callbacks.
+    pub fn complete(&self, callback: WebhookCallback) -> bool {
+        let Some(trace) = callback.trace().map(str::to_string) else {
+            return false;
+        };
+
+        let mut entries = self.entries.lock().unwrap();
+        let callback = match entries.remove(&trace) {
+            Some(Entry::Waiting(sender)) => match sender.send(callback) {
+                Ok(()) => return true,
+                // The waiting task is gone, keep the callback in case it waits again
+                Err(callback) => callback,
+            },
+            _ => callback,
+        };
+        entries.keep(trace, callback);
+        false
+    }
+
+    /// Stop waiting for the callback with the given ticket, or drop it if it already arrived.
+    pub fn forget(&self, ticket: &WebhookTicket) {
+        self.entries.lock().unwrap().remove(&ticket.trace_id);
+    }
+
+    /// The number of requests waiting for their callback.
+    pub fn waiting(&self) -> usize {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .entries
+            .values()
+            .filter(|entry| matches!(entry, Entry::Waiting(_)))
+            .count()
+    }
+}
+
+/// A future resolving to the callback of a request, see [`WebhookRegistry::wait`].
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+pub struct WebhookWait {
+    receiver: oneshot::Receiver<WebhookCallback>,
+    trace: String,
+    entries: Weak<Mutex<Entries>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for WebhookWait {
+    fn drop(&mut self) {
+        // Closing the receiver first tells our own entry apart from a later wait for the same ticket
+        self.receiver.close();
+        let Some(entries) = self.entries.upgrade() else {
+            return;
+        };
+        let Ok(mut entries) = entries.lock() else {
+            return;
+        };
+        if let Some(Entry::Waiting(sender)) = entries.entries.get(&self.trace) {
+            if sender.is_closed() {
+                entries.entries.remove(&self.trace);
+            }
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Future for WebhookWait {
+    type Output = Option<WebhookCallback>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.receiver)
+            .poll(cx)
+            .map(|result| result.ok())
+    }
+}
+
+#[cfg(feature = "axum")]
+#[cfg_attr(docsrs, doc(cfg(feature = "axum")))]
+impl<S> axum::extract::FromRequest<S> for WebhookCallback
+where
+    S: Send + Sync,
+{
+    type Rejection = (axum::http::StatusCode, String);
+
+    async fn from_request(req: axum::extract::Request, state: &S) -> Result<Self, Self::Rejection> {
+        let headers = req.headers().clone();
+        let body = Bytes::from_request(req, state)
+            .await
+            .map_err(|rejection| (rejection.status(), rejection.body_text()))?;
+
+        WebhookCallback::from_parts(&headers, body)
+            .map_err(|error| (axum::http::StatusCode::BAD_REQUEST, error.to_string()))
+    }
+}
+
+#[cfg(all(feature = "hyper", not(target_arch = "wasm32")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "hyper")))]
+impl WebhookCallback {
+    /// Read the whole body of a request received by a hyper server and parse it as a callback.
+    ///
+    /// The body is read into memory without any limit, so put a limit in front of it for large files,
+    /// eg. with [`http_body_util::Limited`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gotenberg_pdf::webhook::{WebhookCallback, WebhookRegistry};
+    /// use hyper::body::Incoming;
+    /// use hyper::{Request, Response, StatusCode};
+    ///
+    /// async fn handle(registry: WebhookRegistry, request: Request<Incoming>) -> Response<String> {
+    ///     match WebhookCallback::from_hyper_request(request).await {
+    ///         Ok(callback) => {
+    ///             registry.complete(callback);
+    ///             Response::new(String::new())
+    ///         }
+    ///         Err(error) => {
+    ///             let mut response = Response::new(error.to_string());
+    ///             *response.status_mut() = StatusCode::BAD_REQUEST;
+    ///             response
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub async fn from_hyper_request<B>(request: hyper::Request<B>) -> Result<Self, Error>
+    where
+        B: hyper::body::Body,
+        B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        use http_body_util::BodyExt;
+
+        let (parts, body) = request.into_parts();
+        let body = body
+            .collect()
+            .await
+            .map_err(|e| Error::IoError(std::io::Error::other(e)))?
+            .to_bytes();
+        WebhookCallback::from_parts(&parts.headers, body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, value.parse().unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn test_success() {
+        let callback = WebhookCallback::from_parts(
+            &headers(&[
+                ("content-type", "application/pdf"),
+                (
+                    "content-disposition",
+                    "attachment; filename=\"invoice.pdf\"",
+                ),
+                ("gotenberg-trace", "abc"),
+            ]),
+            &b"%PDF-1.7"[..],
+        )
+        .unwrap();
+
+        assert_eq!(callback.trace(), Some("abc"));
+        let output = callback.into_result().unwrap();
+        assert_eq!(output.bytes, Bytes::from_static(b"%PDF-1.7"));
+        assert_eq!(output.filename.as_deref(), Some("invoice.pdf"));
+    }
+
+    #[test]
+    fn test_failure() {
+        let callback = WebhookCallback::from_parts(
+            &headers(&[
+                ("content-type", "application/json; charset=utf-8"),
+                ("gotenberg-trace", "abc"),
+            ]),
+            r#"{"status":503,"message":"Service Unavailable"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            callback,
+            WebhookCallback::Failure {
+                status: 503,
+                message: "Service Unavailable".to_string(),
+                trace: Some("abc".to_string()),
+            }
+        );
+        match callback.into_result() {
+            Err(Error::RenderingError(error)) => {
+                assert_eq!(error.kind, ErrorKind::Unavailable);
+                assert_eq!(error.trace.as_deref(), Some("abc"));
+            }
+            other => panic!("Expected a rendering error, got {:?}", other),
+        }
+
+        let malformed = WebhookCallback::from_parts(
+            &headers(&[("content-type", "application/json")]),
+            "not json",
+        );
+        assert!(matches!(malformed, Err(Error::ParseError(..))));
+    }
+
+    #[test]
+    fn test_json_success() {
+        // The metadata read from a PDF is JSON too, but not an error
+        let body = r#"{"file.pdf":{"Author":"Jane","PageCount":1}}"#;
+        let callback =
+            WebhookCallback::from_parts(&headers(&[("content-type", "application/json")]), body)
+                .unwrap();
+        let output = callback.into_result().unwrap();
+        assert_eq!(output.bytes, Bytes::from_static(body.as_bytes()));
+
+        // So is a payload that merely has a status
+        let body = r#"{"status":200,"message":"OK"}"#;
+        let callback =
+            WebhookCallback::from_parts(&headers(&[("content-type", "application/json")]), body)
+                .unwrap();
+        assert!(matches!(callback, WebhookCallback::Success { .. }));
+    }
+
+    #[cfg(feature = "axum")]
+    #[tokio::test]
+    async fn test_axum_extractor() {
+        use axum::extract::{FromRequest, Request};
+
+        let request = Request::builder()
+            .header("content-type", "application/pdf")
+            .header("gotenberg-trace", "abc")
+            .body(axum::body::Body::from("%PDF-1.7"))
+            .unwrap();
+        let callback = WebhookCallback::from_request(request, &()).await.unwrap();
+        assert_eq!(callback.trace(), Some("abc"));
+
+        let request = Request::builder()
+            .header("content-type", "application/json")
+            .body(axum::body::Body::from("not json"))
+            .unwrap();
+        let (status, _) = WebhookCallback::from_request(request, &())
+            .await
+            .unwrap_err();
+        assert_eq!(status, axum::http::StatusCode::BAD_REQUEST);
+    }
+
+    #[cfg(feature = "hyper")]
+    #[tokio::test]
+    async fn test_hyper_request() {
+        let request = hyper::Request::builder()
+            .header("content-type", "application/pdf")
+            .header("gotenberg-trace", "abc")
+            .body(http_body_util::Full::new(Bytes::from_static(b"%PDF-1.7")))
+            .unwrap();
+        let callback = WebhookCallback::from_hyper_request(request).await.unwrap();
+        assert_eq!(callback, self::callback("abc"));
+
+        let request = hyper::Request::builder()
+            .header("content-type", "application/json")
+            .body(http_body_util::Full::new(Bytes::from_static(b"not json")))
+            .unwrap();
+        assert!(matches!(
+            WebhookCallback::from_hyper_request(request).await,
+            Err(Error::ParseError(..))
+        ));
+    }
+
+    #[test]
+    fn test_generate_trace_id() {
+        let trace = generate_trace_id();
+        assert_eq!(trace.len(), 36);
+        assert_eq!(trace.as_bytes()[14], b'4');
+        assert_ne!(trace, generate_trace_id());
+    }
+
+    fn callback(trace: &str) -> WebhookCallback {
+        WebhookCallback::Success {
+            bytes: Bytes::from_static(b"%PDF-1.7"),
+            content_type: Some("application/pdf".to_string()),
+            filename: None,
+            trace: Some(trace.to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_registry() {
+        let registry = WebhookRegistry::new();
+        let ticket = WebhookTicket {
+            trace_id: "abc".to_string(),
+        };
+
+        // Waiting first
+        let wait = registry.wait(&ticket);
+        assert_eq!(registry.waiting(), 1);
+        assert!(registry.complete(callback("abc")));
+        assert_eq!(wait.await, Some(callback("abc")));
+        assert_eq!(registry.waiting(), 0);
+
+        // Callback first
+        assert!(!registry.complete(callback("abc")));
+        assert_eq!(registry.wait(&ticket).await, Some(callback("abc")));
+
+        // Forgotten
+        let wait = registry.wait(&ticket);
+        registry.forget(&ticket);
+        assert_eq!(wait.await, None);
+
+        // Dropped, eg. after a timeout
+        let result = tokio::time::timeout(Duration::from_millis(10), registry.wait(&ticket)).await;
+        assert!(result.is_err());
+        assert_eq!(registry.waiting(), 0);
+
+        // Dropping a replaced wait leaves the later one waiting
+        let replaced = registry.wait(&ticket);
+        let wait = registry.wait(&ticket);
+        drop(replaced);
+        assert_eq!(registry.waiting(), 1);
+        assert!(registry.complete(callback("abc")));
+        assert_eq!(wait.await, Some(callback("abc")));
+    }
+
+    #[tokio::test]
+    async fn test_registry_max_unclaimed() {
+        let registry = WebhookRegistry::new().max_unclaimed(2);
+        let ticket = |trace: &str| WebhookTicket {
+            trace_id: trace.to_string(),
+        };
+
+        assert!(!registry.complete(callback("a")));
+        assert!(!registry.complete(callback("b")));
+        // Claimed callbacks no longer count towards the limit
+        assert_eq!(registry.wait(&ticket("b")).await, Some(callback("b")));
+        assert!(!registry.complete(callback("c")));
+        assert!(!registry.complete(callback("d")));
+
+        // The oldest callback was dropped
+        let wait = registry.wait(&ticket("a"));
+        registry.forget(&ticket("a"));
+        assert_eq!(wait.await, None);
+        assert_eq!(registry.wait(&ticket("c")).await, Some(callback("c")));
+        assert_eq!(registry.wait(&ticket("d")).await, Some(callback("d")));
+    }
+}